@@ -1,16 +1,462 @@
-use crate::token::Token;
-#[derive(Debug, PartialEq)]
+use crate::image::Address;
+use crate::object::{DataType, Object};
+use crate::token::{Span, SpannedToken, Token};
+#[derive(Debug, PartialEq, Clone)]
 pub enum Node {
+    CompilationUnit(CompilationUnit),
+    Program(Program),
+    FunctionDecl(FunctionDecl),
+    FunctionBlockDecl(FunctionBlockDecl),
+    TypeDecl(TypeDecl),
+    Configuration(Configuration),
+    VarDecl(VarDecl),
     UnaryOp(UnaryOp),
     BinaryOp(BinaryOp),
     Num(Num),
+    EnumValue(EnumValue),
     Assignment(Assignment),
     Variable(Variable),
+    Member(Member),
+    Index(Index),
+    Call(Call),
     CompoundStatement(CompoundStatement),
+    IfStatement(IfStatement),
+    CaseStatement(CaseStatement),
+    ForStatement(ForStatement),
+    WhileStatement(WhileStatement),
+    RepeatStatement(RepeatStatement),
+    Exit(SpannedToken),
+    Continue(SpannedToken),
+    Return(SpannedToken),
     NoOp,
 }
 
-#[derive(Debug, PartialEq)]
+impl Node {
+    /// The source region the node was parsed from. An empty statement has
+    /// no source text and returns an empty span.
+    pub fn span(&self) -> Span {
+        match self {
+            Node::CompilationUnit(compilation_unit) => compilation_unit.span(),
+            Node::Program(program) => program.span(),
+            Node::FunctionDecl(function_decl) => function_decl.span(),
+            Node::FunctionBlockDecl(function_block_decl) => function_block_decl.span(),
+            Node::TypeDecl(type_decl) => type_decl.span(),
+            Node::Configuration(configuration) => configuration.span(),
+            Node::VarDecl(var_decl) => var_decl.span(),
+            Node::UnaryOp(unary_op) => unary_op.span(),
+            Node::BinaryOp(binary_op) => binary_op.span(),
+            Node::Num(num) => num.span(),
+            Node::EnumValue(enum_value) => enum_value.span(),
+            Node::Assignment(assignment) => assignment.span(),
+            Node::Variable(variable) => variable.span(),
+            Node::Member(member) => member.span(),
+            Node::Index(index) => index.span(),
+            Node::Call(call) => call.span(),
+            Node::CompoundStatement(compound_statement) => compound_statement.span(),
+            Node::IfStatement(if_statement) => if_statement.span(),
+            Node::CaseStatement(case_statement) => case_statement.span(),
+            Node::ForStatement(for_statement) => for_statement.span(),
+            Node::WhileStatement(while_statement) => while_statement.span(),
+            Node::RepeatStatement(repeat_statement) => repeat_statement.span(),
+            Node::Exit(token) | Node::Continue(token) | Node::Return(token) => token.span,
+            Node::NoOp => Span::default(),
+        }
+    }
+}
+
+/// The POUs of a source text, in declaration order.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompilationUnit {
+    pub pous: Vec<Node>,
+}
+
+impl CompilationUnit {
+    pub fn new(pous: Vec<Node>) -> CompilationUnit {
+        CompilationUnit { pous }
+    }
+
+    pub fn span(&self) -> Span {
+        let mut spans = self.pous.iter().map(Node::span);
+        match spans.next() {
+            Some(first) => spans.fold(first, Span::to),
+            None => Span::default(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Program {
+    token: SpannedToken,
+    pub name: String,
+    pub declarations: Vec<Node>,
+    pub body: Box<Node>,
+}
+
+impl Program {
+    pub fn new(
+        token: impl Into<SpannedToken>,
+        name: String,
+        declarations: Vec<Node>,
+        body: Node,
+    ) -> Program {
+        Program {
+            token: token.into(),
+            name,
+            declarations,
+            body: Box::new(body),
+        }
+    }
+
+    /// The declarations of the variables located `AT` a direct address.
+    pub fn located(&self) -> Vec<&VarDecl> {
+        self.declarations
+            .iter()
+            .filter_map(|declaration| match declaration {
+                Node::VarDecl(var_decl) if var_decl.address.is_some() => Some(var_decl),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The span of the `PROGRAM` keyword.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FunctionDecl {
+    token: SpannedToken,
+    pub name: String,
+    pub return_type: Type,
+    pub declarations: Vec<Node>,
+    pub body: Box<Node>,
+}
+
+impl FunctionDecl {
+    pub fn new(
+        token: impl Into<SpannedToken>,
+        name: String,
+        return_type: Type,
+        declarations: Vec<Node>,
+        body: Node,
+    ) -> FunctionDecl {
+        FunctionDecl {
+            token: token.into(),
+            name,
+            return_type,
+            declarations,
+            body: Box::new(body),
+        }
+    }
+
+    /// The `VAR_INPUT` declarations, in parameter order.
+    pub fn inputs(&self) -> Vec<&VarDecl> {
+        inputs(&self.declarations)
+    }
+
+    /// The span of the `FUNCTION` keyword.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FunctionBlockDecl {
+    token: SpannedToken,
+    pub name: String,
+    pub declarations: Vec<Node>,
+    pub body: Box<Node>,
+}
+
+impl FunctionBlockDecl {
+    pub fn new(
+        token: impl Into<SpannedToken>,
+        name: String,
+        declarations: Vec<Node>,
+        body: Node,
+    ) -> FunctionBlockDecl {
+        FunctionBlockDecl {
+            token: token.into(),
+            name,
+            declarations,
+            body: Box::new(body),
+        }
+    }
+
+    /// The `VAR_INPUT` declarations, in parameter order.
+    pub fn inputs(&self) -> Vec<&VarDecl> {
+        inputs(&self.declarations)
+    }
+
+    /// The kind of the variable `id`, `None` if the block declares no such
+    /// variable.
+    pub fn var_kind(&self, id: &str) -> Option<VarKind> {
+        self.declarations
+            .iter()
+            .find_map(|declaration| match declaration {
+                Node::VarDecl(var_decl) if var_decl.variable.id.eq_ignore_ascii_case(id) => {
+                    Some(var_decl.kind)
+                }
+                _ => None,
+            })
+    }
+
+    /// The span of the `FUNCTION_BLOCK` keyword.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+/// A data type declared in a `TYPE ... END_TYPE` block.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TypeDecl {
+    token: SpannedToken,
+    pub name: String,
+    pub definition: TypeDefinition,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TypeDefinition {
+    /// `STRUCT ... END_STRUCT` with the declarations of its members.
+    Struct(Vec<Node>),
+    /// `(Idle, Run := 5) INT`, the names with the integers they stand for
+    /// and the base type, INT unless given.
+    Enum(Vec<(String, i128)>, Type),
+    /// Another type, such as the subrange `INT (0..100)`.
+    Derived(Type),
+}
+
+impl TypeDecl {
+    pub fn new(token: impl Into<SpannedToken>, definition: TypeDefinition) -> TypeDecl {
+        let token = token.into();
+        match token.token.clone() {
+            Token::Id(name) => TypeDecl {
+                token,
+                name,
+                definition,
+            },
+            _ => panic!("Wrong token in TypeDecl constructor: {:?}", token),
+        }
+    }
+
+    /// The span of the type name.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+/// A `CONFIGURATION` and the tasks and program instances of its resources.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Configuration {
+    token: SpannedToken,
+    pub name: String,
+    pub resources: Vec<Resource>,
+}
+
+impl Configuration {
+    pub fn new(
+        token: impl Into<SpannedToken>,
+        name: String,
+        resources: Vec<Resource>,
+    ) -> Configuration {
+        Configuration {
+            token: token.into(),
+            name,
+            resources,
+        }
+    }
+
+    /// The span of the `CONFIGURATION` keyword.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Resource {
+    pub name: String,
+    pub tasks: Vec<TaskDecl>,
+    pub programs: Vec<ProgramConfiguration>,
+}
+
+impl Resource {
+    pub fn new(
+        name: String,
+        tasks: Vec<TaskDecl>,
+        programs: Vec<ProgramConfiguration>,
+    ) -> Resource {
+        Resource {
+            name,
+            tasks,
+            programs,
+        }
+    }
+}
+
+/// `TASK fast(INTERVAL := T#10ms, PRIORITY := 1)`, with the formal
+/// arguments unchecked.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TaskDecl {
+    token: SpannedToken,
+    pub name: String,
+    pub arguments: Vec<Argument>,
+}
+
+impl TaskDecl {
+    pub fn new(token: impl Into<SpannedToken>, arguments: Vec<Argument>) -> TaskDecl {
+        let token = token.into();
+        match token.token.clone() {
+            Token::Id(name) => TaskDecl {
+                token,
+                name,
+                arguments,
+            },
+            _ => panic!("Wrong token in TaskDecl constructor: {:?}", token),
+        }
+    }
+
+    /// The span of the task name.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+/// `PROGRAM p1 WITH fast : Main`, an instance of a program run by a task,
+/// or by the resource's cyclic task without `WITH`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProgramConfiguration {
+    token: SpannedToken,
+    pub name: String,
+    pub task: Option<String>,
+    pub program_type: String,
+}
+
+impl ProgramConfiguration {
+    pub fn new(
+        token: impl Into<SpannedToken>,
+        task: Option<String>,
+        program_type: String,
+    ) -> ProgramConfiguration {
+        let token = token.into();
+        match token.token.clone() {
+            Token::Id(name) => ProgramConfiguration {
+                token,
+                name,
+                task,
+                program_type,
+            },
+            _ => panic!(
+                "Wrong token in ProgramConfiguration constructor: {:?}",
+                token
+            ),
+        }
+    }
+
+    /// The span of the instance name.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+fn inputs(declarations: &[Node]) -> Vec<&VarDecl> {
+    declarations
+        .iter()
+        .filter_map(|declaration| match declaration {
+            Node::VarDecl(var_decl) if var_decl.kind == VarKind::Input => Some(var_decl),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The kind of `VAR` block a variable is declared in.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum VarKind {
+    Var,
+    Input,
+    Output,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct VarDecl {
+    pub kind: VarKind,
+    pub variable: Variable,
+    /// The direct address of a variable declared `AT %IX0.0`.
+    pub address: Option<Address>,
+    pub var_type: Type,
+    pub initial_value: Option<Box<Node>>,
+}
+
+impl VarDecl {
+    pub fn new(
+        kind: VarKind,
+        variable: Variable,
+        address: Option<Address>,
+        var_type: Type,
+        initial_value: Option<Node>,
+    ) -> VarDecl {
+        VarDecl {
+            kind,
+            variable,
+            address,
+            var_type,
+            initial_value: initial_value.map(Box::new),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        let span = self.variable.span().to(self.var_type.span());
+        match &self.initial_value {
+            Some(initial_value) => span.to(initial_value.span()),
+            None => span,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Type {
+    token: SpannedToken,
+    pub name: String,
+    /// The maximum length of a `STRING[n]` or `WSTRING[n]`.
+    pub length: Option<usize>,
+    /// The lower and upper bound of each dimension of an ARRAY type, empty
+    /// for other types. `name` and `length` then describe the element type.
+    pub dimensions: Vec<(i128, i128)>,
+    /// The bounds of a subrange of an integer type, `INT (0..100)`.
+    pub range: Option<(i128, i128)>,
+}
+
+impl Type {
+    pub fn new(token: impl Into<SpannedToken>) -> Type {
+        let token = token.into();
+        match token.token.clone() {
+            Token::Id(name) => Type {
+                token,
+                name,
+                length: None,
+                dimensions: Vec::new(),
+                range: None,
+            },
+            _ => panic!("Wrong token in Type constructor: {:?}", token),
+        }
+    }
+
+    /// The elementary type named, or the element type of an array. `None`
+    /// for a function block or unknown type.
+    pub fn data_type(&self) -> Option<DataType> {
+        match (DataType::from_name(&self.name)?, self.length) {
+            (DataType::String(_), Some(length)) => Some(DataType::String(length)),
+            (DataType::WString(_), Some(length)) => Some(DataType::WString(length)),
+            (data_type, _) => Some(data_type),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct CompoundStatement {
     pub statements: Vec<Node>,
 }
@@ -21,90 +467,426 @@ impl CompoundStatement {
             statements: Vec::new(),
         }
     }
+
+    pub fn span(&self) -> Span {
+        let mut spans = self
+            .statements
+            .iter()
+            .filter(|statement| **statement != Node::NoOp)
+            .map(Node::span);
+        match spans.next() {
+            Some(first) => spans.fold(first, Span::to),
+            None => Span::default(),
+        }
+    }
+}
+
+/// `IF condition THEN ... END_IF`, an `ELSIF` is stored as a nested
+/// `IfStatement` in `else_branch`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct IfStatement {
+    token: SpannedToken,
+    pub condition: Box<Node>,
+    pub then_branch: Box<Node>,
+    pub else_branch: Option<Box<Node>>,
+}
+
+impl IfStatement {
+    pub fn new(
+        token: impl Into<SpannedToken>,
+        condition: Node,
+        then_branch: Node,
+        else_branch: Option<Node>,
+    ) -> IfStatement {
+        IfStatement {
+            token: token.into(),
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: else_branch.map(Box::new),
+        }
+    }
+
+    /// The span of the `IF`, or for a nested branch `ELSIF`, keyword.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CaseStatement {
+    token: SpannedToken,
+    pub selector: Box<Node>,
+    pub elements: Vec<CaseElement>,
+    pub else_branch: Option<Box<Node>>,
+}
+
+impl CaseStatement {
+    pub fn new(
+        token: impl Into<SpannedToken>,
+        selector: Node,
+        elements: Vec<CaseElement>,
+        else_branch: Option<Node>,
+    ) -> CaseStatement {
+        CaseStatement {
+            token: token.into(),
+            selector: Box::new(selector),
+            elements,
+            else_branch: else_branch.map(Box::new),
+        }
+    }
+
+    /// The span of the `CASE` keyword.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+/// One `labels: statements` arm of a CASE statement.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CaseElement {
+    pub labels: Vec<CaseLabel>,
+    pub body: Box<Node>,
+}
+
+impl CaseElement {
+    pub fn new(labels: Vec<CaseLabel>, body: Node) -> CaseElement {
+        CaseElement {
+            labels,
+            body: Box::new(body),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum CaseLabel {
+    Value(Box<Node>),
+    Range(Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ForStatement {
+    token: SpannedToken,
+    pub control_variable: Variable,
+    pub start: Box<Node>,
+    pub end: Box<Node>,
+    pub step: Option<Box<Node>>,
+    pub body: Box<Node>,
+}
+
+impl ForStatement {
+    pub fn new(
+        token: impl Into<SpannedToken>,
+        control_variable: Variable,
+        start: Node,
+        end: Node,
+        step: Option<Node>,
+        body: Node,
+    ) -> ForStatement {
+        ForStatement {
+            token: token.into(),
+            control_variable,
+            start: Box::new(start),
+            end: Box::new(end),
+            step: step.map(Box::new),
+            body: Box::new(body),
+        }
+    }
+
+    /// The span of the `FOR` keyword.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct WhileStatement {
+    token: SpannedToken,
+    pub condition: Box<Node>,
+    pub body: Box<Node>,
 }
 
-#[derive(Debug, PartialEq)]
+impl WhileStatement {
+    pub fn new(token: impl Into<SpannedToken>, condition: Node, body: Node) -> WhileStatement {
+        WhileStatement {
+            token: token.into(),
+            condition: Box::new(condition),
+            body: Box::new(body),
+        }
+    }
+
+    /// The span of the `WHILE` keyword.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RepeatStatement {
+    token: SpannedToken,
+    pub body: Box<Node>,
+    pub condition: Box<Node>,
+}
+
+impl RepeatStatement {
+    pub fn new(token: impl Into<SpannedToken>, body: Node, condition: Node) -> RepeatStatement {
+        RepeatStatement {
+            token: token.into(),
+            body: Box::new(body),
+            condition: Box::new(condition),
+        }
+    }
+
+    /// The span of the `REPEAT` keyword.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Variable {
-    token: Token,
+    token: SpannedToken,
     pub id: String,
 }
 
 impl Variable {
-    pub fn new(token: Token) -> Variable {
-        match token.clone() {
-            Token::Id(id) => Variable {
-                token: token,
-                id: id,
-            },
+    pub fn new(token: impl Into<SpannedToken>) -> Variable {
+        let token = token.into();
+        match token.token.clone() {
+            Token::Id(id) => Variable { token, id },
             _ => panic!("Wrong token in Variable constructor: {:?}", token),
         }
     }
+
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+/// Access to the member `member` of the instance `base`, as in
+/// `motor1.running`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Member {
+    token: SpannedToken,
+    pub base: Box<Node>,
+    pub member: String,
+}
+
+impl Member {
+    pub fn new(base: Node, token: impl Into<SpannedToken>) -> Member {
+        let token = token.into();
+        match token.token.clone() {
+            Token::Id(member) => Member {
+                token,
+                base: Box::new(base),
+                member,
+            },
+            _ => panic!("Wrong token in Member constructor: {:?}", token),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.base.span().to(self.token.span)
+    }
 }
 
-#[derive(Debug, PartialEq)]
+/// Access to an element of the array `base`, as in `buffer[i, j]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Index {
+    /// The closing bracket.
+    token: SpannedToken,
+    pub base: Box<Node>,
+    pub indices: Vec<Node>,
+}
+
+impl Index {
+    pub fn new(base: Node, indices: Vec<Node>, token: impl Into<SpannedToken>) -> Index {
+        Index {
+            token: token.into(),
+            base: Box::new(base),
+            indices,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.base.span().to(self.token.span)
+    }
+}
+
+/// A call `name(arguments)` of a function or function block instance, all
+/// arguments are either formal (`a := 1`) or non-formal (`1`).
+#[derive(Debug, PartialEq, Clone)]
+pub struct Call {
+    token: SpannedToken,
+    /// The function or instance name, or the variable `instance` is
+    /// selected from.
+    pub name: String,
+    /// The instance called by `timers[i](...)` or `s.timer(...)`, `None`
+    /// for a call by name.
+    pub instance: Option<Box<Node>>,
+    pub arguments: Vec<Argument>,
+}
+
+impl Call {
+    pub fn new(token: impl Into<SpannedToken>, arguments: Vec<Argument>) -> Call {
+        let token = token.into();
+        match token.token.clone() {
+            Token::Id(name) => Call {
+                token,
+                name,
+                instance: None,
+                arguments,
+            },
+            _ => panic!("Wrong token in Call constructor: {:?}", token),
+        }
+    }
+
+    /// The span from the name to the closing parenthesis.
+    pub fn span(&self) -> Span {
+        self.token.span
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Argument {
+    /// The parameter name of a formal argument.
+    pub name: Option<String>,
+    pub value: Node,
+}
+
+impl Argument {
+    pub fn new(name: Option<String>, value: Node) -> Argument {
+        Argument { name, value }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Assignment {
-    token: Token,
+    token: SpannedToken,
     pub left: Box<Node>,
     pub right: Box<Node>,
     pub op: Token,
 }
 
 impl Assignment {
-    pub fn new(op: Token, left: Node, right: Node) -> Assignment {
+    pub fn new(op: impl Into<SpannedToken>, left: Node, right: Node) -> Assignment {
+        let token = op.into();
         Assignment {
-            token: op.clone(),
+            op: token.token.clone(),
+            token,
             left: Box::new(left),
             right: Box::new(right),
-            op: op,
         }
     }
+
+    pub fn span(&self) -> Span {
+        self.left.span().to(self.right.span())
+    }
 }
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct UnaryOp {
-    token: Token,
+    token: SpannedToken,
     pub expr: Box<Node>,
     pub op: Token,
 }
 
 impl UnaryOp {
-    pub fn new(op: Token, expr: Node) -> UnaryOp {
+    pub fn new(op: impl Into<SpannedToken>, expr: Node) -> UnaryOp {
+        let token = op.into();
         UnaryOp {
-            token: op.clone(),
+            op: token.token.clone(),
+            token,
             expr: Box::new(expr),
-            op: op,
         }
     }
+
+    pub fn span(&self) -> Span {
+        self.token.span.to(self.expr.span())
+    }
 }
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct BinaryOp {
-    token: Token,
+    token: SpannedToken,
     pub left: Box<Node>,
     pub right: Box<Node>,
     pub op: Token,
 }
 
 impl BinaryOp {
-    pub fn new(left: Node, right: Node, op: Token) -> BinaryOp {
+    pub fn new(left: Node, right: Node, op: impl Into<SpannedToken>) -> BinaryOp {
+        let token = op.into();
         BinaryOp {
-            token: op.clone(),
+            op: token.token.clone(),
+            token,
             left: Box::new(left),
             right: Box::new(right),
-            op: op,
         }
     }
+
+    pub fn span(&self) -> Span {
+        self.left.span().to(self.right.span())
+    }
+}
+
+/// A value of an enumerated type qualified with its type, `Mode#Run`. An
+/// unqualified `Run` is parsed as a `Variable`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EnumValue {
+    type_token: SpannedToken,
+    token: SpannedToken,
+    pub type_name: String,
+    pub name: String,
+}
+
+impl EnumValue {
+    pub fn new(type_token: impl Into<SpannedToken>, token: impl Into<SpannedToken>) -> EnumValue {
+        let (type_token, token) = (type_token.into(), token.into());
+        match (type_token.token.clone(), token.token.clone()) {
+            (Token::Id(type_name), Token::Id(name)) => EnumValue {
+                type_token,
+                token,
+                type_name,
+                name,
+            },
+            _ => panic!(
+                "Wrong tokens in EnumValue constructor: {:?}, {:?}",
+                type_token, token
+            ),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.type_token.span.to(self.token.span)
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Num {
-    token: Token,
-    pub value: i32,
+    token: SpannedToken,
+    pub value: Object,
 }
 
 impl Num {
-    pub fn new(token: Token) -> Num {
-        match token {
-            Token::Integer(value) => Num { token, value },
-            _ => panic!(),
-        }
+    pub fn new(token: impl Into<SpannedToken>) -> Num {
+        let token = token.into();
+        let value = match token.token.clone() {
+            Token::Integer(value, None) => Object::from_integer(value),
+            Token::Integer(value, Some(data_type)) => Object::from_i128(value, data_type),
+            Token::Real(value, Some(DataType::Real)) => Object::Real(value as f32),
+            Token::Real(value, _) => Object::LReal(value),
+            Token::Bool(value) => Object::Bool(value),
+            Token::Time(value) => Object::Time(value),
+            Token::LTime(value) => Object::LTime(value),
+            Token::Date(value) => Object::Date(value),
+            Token::TimeOfDay(value) => Object::TimeOfDay(value),
+            Token::DateAndTime(value) => Object::DateAndTime(value),
+            Token::String(value) => Object::string(value),
+            Token::WString(value) => Object::wstring(value),
+            _ => panic!("Wrong token in Num constructor: {:?}", token),
+        };
+        Num { token, value }
+    }
+
+    pub fn span(&self) -> Span {
+        self.token.span
     }
 }
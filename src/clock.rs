@@ -0,0 +1,91 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Source of the current time for timers. Only the difference between two
+/// readings is meaningful; the origin is arbitrary.
+pub trait Clock {
+    fn now(&self) -> Duration;
+
+    /// Blocks until the clock reads `time`, returns at once if it is past.
+    fn wait_until(&self, time: Duration);
+
+    /// Whether the time moves on its own, and not only in `wait_until`.
+    fn is_running(&self) -> bool {
+        true
+    }
+}
+
+/// The real monotonic time since the clock was created.
+pub struct MonotonicClock {
+    start: Instant,
+}
+
+impl MonotonicClock {
+    pub fn new() -> MonotonicClock {
+        MonotonicClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for MonotonicClock {
+    fn default() -> MonotonicClock {
+        MonotonicClock::new()
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn wait_until(&self, time: Duration) {
+        if let Some(remaining) = time.checked_sub(self.now()) {
+            std::thread::sleep(remaining);
+        }
+    }
+}
+
+/// A clock that only moves when advanced. Clones share the same time, so a
+/// test can keep one and hand another to the interpreter.
+#[derive(Clone, Default)]
+pub struct VirtualClock {
+    now: Rc<Cell<Duration>>,
+}
+
+impl VirtualClock {
+    pub fn new() -> VirtualClock {
+        VirtualClock::default()
+    }
+
+    #[cfg(test)]
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+
+    /// Jumps ahead instead of waiting, so simulated cycles run at full speed.
+    fn wait_until(&self, time: Duration) {
+        if time > self.now.get() {
+            self.now.set(time);
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        false
+    }
+}
+
+#[test]
+fn virtual_clock_clones_share_time() {
+    let clock = VirtualClock::new();
+    let shared = clock.clone();
+    clock.advance(Duration::from_millis(250));
+    assert_eq!(shared.now(), Duration::from_millis(250));
+}
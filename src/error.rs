@@ -0,0 +1,370 @@
+use std::fmt;
+
+use crate::object::{DataType, MAX_ARRAY_ELEMENTS};
+use crate::token::{Span, Token};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum LexError {
+    UnexpectedChar(char, Span),
+    InvalidNumber(String, Span),
+    InvalidTime(String, Span),
+    InvalidDate(String, Span),
+    InvalidAddress(String, Span),
+    /// An unknown `$` escape in a string literal.
+    InvalidEscape(String, Span),
+    UnterminatedString(Span),
+    UnterminatedComment(Span),
+}
+
+impl LexError {
+    pub fn span(&self) -> Span {
+        match self {
+            LexError::UnexpectedChar(_, span)
+            | LexError::InvalidNumber(_, span)
+            | LexError::InvalidTime(_, span)
+            | LexError::InvalidDate(_, span)
+            | LexError::InvalidAddress(_, span)
+            | LexError::InvalidEscape(_, span)
+            | LexError::UnterminatedString(span)
+            | LexError::UnterminatedComment(span) => *span,
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexError::UnexpectedChar(ch, _) => write!(f, "unexpected character '{}'", ch),
+            LexError::InvalidNumber(text, _) => write!(f, "invalid number literal {}", text),
+            LexError::InvalidTime(text, _) => write!(f, "invalid time literal {}", text),
+            LexError::InvalidDate(text, _) => write!(f, "invalid date literal {}", text),
+            LexError::InvalidAddress(text, _) => write!(f, "invalid direct address {}", text),
+            LexError::InvalidEscape(text, _) => write!(f, "invalid escape sequence {}", text),
+            LexError::UnterminatedString(_) => write!(f, "unterminated string literal"),
+            LexError::UnterminatedComment(_) => write!(f, "unterminated comment"),
+        }
+    }
+}
+
+impl std::error::Error for LexError {}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseError {
+    Lex(LexError),
+    UnexpectedToken {
+        expected: String,
+        found: Token,
+        span: Span,
+    },
+    /// EXIT or CONTINUE outside of a FOR, WHILE or REPEAT body.
+    LoopJumpOutsideLoop(Token, Span),
+    /// A call with both formal (`a := 1`) and non-formal arguments.
+    MixedArguments(Span),
+    /// An array dimension whose lower bound exceeds its upper bound.
+    InvalidArrayBounds(Span),
+    /// An array type with more than `MAX_ARRAY_ELEMENTS` elements.
+    ArrayTooLarge(String, Span),
+    /// A subrange whose lower bound exceeds its upper bound.
+    InvalidSubrange(Span),
+    /// A variable declared `AT` a direct address outside of a PROGRAM.
+    LocatedOutsideProgram(Span),
+}
+
+impl ParseError {
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::Lex(error) => error.span(),
+            ParseError::UnexpectedToken { span, .. } => *span,
+            ParseError::LoopJumpOutsideLoop(_, span) => *span,
+            ParseError::MixedArguments(span) => *span,
+            ParseError::InvalidArrayBounds(span) => *span,
+            ParseError::ArrayTooLarge(_, span) => *span,
+            ParseError::InvalidSubrange(span) => *span,
+            ParseError::LocatedOutsideProgram(span) => *span,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Lex(error) => write!(f, "{}", error),
+            ParseError::UnexpectedToken {
+                expected, found, ..
+            } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            ParseError::LoopJumpOutsideLoop(token, _) => write!(f, "{} outside of a loop", token),
+            ParseError::MixedArguments(_) => {
+                write!(f, "formal and non-formal arguments in the same call")
+            }
+            ParseError::InvalidArrayBounds(_) => {
+                write!(f, "array lower bound exceeds upper bound")
+            }
+            ParseError::ArrayTooLarge(array, _) => {
+                write!(f, "{} has more than {} elements", array, MAX_ARRAY_ELEMENTS)
+            }
+            ParseError::InvalidSubrange(_) => {
+                write!(f, "subrange lower bound exceeds upper bound")
+            }
+            ParseError::LocatedOutsideProgram(_) => {
+                write!(f, "located variable outside of a PROGRAM")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<LexError> for ParseError {
+    fn from(error: LexError) -> ParseError {
+        ParseError::Lex(error)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum RuntimeError {
+    UndeclaredVariable(String),
+    DuplicateDeclaration(String),
+    UnknownType(String),
+    DivisionByZero,
+    /// An operator applied to a type it is not defined for.
+    InvalidOperand {
+        operation: String,
+        data_type: DataType,
+    },
+    /// A binary operator applied to two types that cannot be combined.
+    IncompatibleOperands {
+        operation: String,
+        left: DataType,
+        right: DataType,
+    },
+    InvalidAssignment {
+        from: DataType,
+        to: DataType,
+    },
+    /// A value of the wrong type where the language requires e.g. a BOOL
+    /// condition.
+    UnexpectedType {
+        context: String,
+        expected: String,
+        found: DataType,
+    },
+    /// A value selected by more than one label of a CASE statement.
+    DuplicateCaseLabel(String),
+    /// A CASE range whose lower bound is above its upper bound.
+    EmptyCaseRange(i128, i128),
+    ControlVariableModified(String),
+    ZeroStep,
+    UnknownFunction(String),
+    /// A non-formal call whose argument count differs from the inputs.
+    WrongArgumentCount {
+        pou: String,
+        expected: usize,
+        found: usize,
+    },
+    UnknownParameter {
+        pou: String,
+        parameter: String,
+    },
+    DuplicateArgument {
+        pou: String,
+        parameter: String,
+    },
+    /// A standard function called without one of its inputs.
+    MissingArgument {
+        pou: String,
+        parameter: String,
+    },
+    /// IEC 61131-3 does not allow a POU to call itself, directly or
+    /// indirectly.
+    RecursiveCall(String),
+    /// A STRUCT type that contains itself, directly or indirectly.
+    RecursiveType(String),
+    NoSuchMember {
+        data_type: DataType,
+        member: String,
+    },
+    /// A name that is not one of the values of an enumerated type.
+    NoSuchValue {
+        data_type: DataType,
+        value: String,
+    },
+    /// A `VAR` of a function block instance accessed from outside.
+    InternalMember {
+        data_type: DataType,
+        member: String,
+    },
+    /// An assignment to a `VAR_OUTPUT` from outside the instance.
+    ReadOnlyMember {
+        data_type: DataType,
+        member: String,
+    },
+    /// A value outside the range allowed by its type or context, e.g. the
+    /// selector of MUX.
+    OutOfRange {
+        value: String,
+        range: String,
+    },
+    /// A STRING converted to a type it does not hold a literal of.
+    InvalidConversion {
+        text: String,
+        to: DataType,
+    },
+    IndexOutOfBounds {
+        index: i128,
+        lower: i128,
+        upper: i128,
+    },
+    /// An array accessed with fewer or more indices than it has dimensions.
+    WrongIndexCount {
+        expected: usize,
+        found: usize,
+    },
+    /// Cyclic execution of a source text without a PROGRAM.
+    NoProgram,
+    /// Cyclic execution of a task due every 0s on a clock that only moves
+    /// when waited on, so it would never reach a later time.
+    ZeroPeriod,
+    /// A program configuration `WITH` a task its resource does not declare.
+    UnknownTask(String),
+    /// A variable whose type does not fit its direct address.
+    InvalidAddress {
+        address: String,
+        data_type: DataType,
+    },
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuntimeError::UndeclaredVariable(id) => write!(f, "variable {} is not declared", id),
+            RuntimeError::DuplicateDeclaration(id) => {
+                write!(f, "{} is declared more than once", id)
+            }
+            RuntimeError::UnknownType(name) => write!(f, "unknown type {}", name),
+            RuntimeError::DivisionByZero => write!(f, "division by zero"),
+            RuntimeError::InvalidOperand {
+                operation,
+                data_type,
+            } => write!(f, "{} is not defined for type {}", operation, data_type),
+            RuntimeError::IncompatibleOperands {
+                operation,
+                left,
+                right,
+            } => write!(
+                f,
+                "{} is not defined for types {} and {}",
+                operation, left, right
+            ),
+            RuntimeError::InvalidAssignment { from, to } => {
+                write!(f, "cannot assign {} to a variable of type {}", from, to)
+            }
+            RuntimeError::UnexpectedType {
+                context,
+                expected,
+                found,
+            } => write!(f, "{} must be {}, found {}", context, expected, found),
+            RuntimeError::DuplicateCaseLabel(value) => {
+                write!(f, "duplicate CASE label {}", value)
+            }
+            RuntimeError::EmptyCaseRange(low, high) => {
+                write!(f, "CASE range {}..{} selects no value", low, high)
+            }
+            RuntimeError::ControlVariableModified(id) => {
+                write!(f, "FOR control variable {} modified inside loop body", id)
+            }
+            RuntimeError::ZeroStep => write!(f, "FOR step must not be zero"),
+            RuntimeError::UnknownFunction(name) => write!(f, "unknown function {}", name),
+            RuntimeError::WrongArgumentCount {
+                pou,
+                expected,
+                found,
+            } => write!(f, "{} expects {} arguments, found {}", pou, expected, found),
+            RuntimeError::UnknownParameter { pou, parameter } => {
+                write!(f, "{} has no input named {}", pou, parameter)
+            }
+            RuntimeError::DuplicateArgument { pou, parameter } => {
+                write!(f, "input {} of {} is assigned twice", parameter, pou)
+            }
+            RuntimeError::MissingArgument { pou, parameter } => {
+                write!(f, "input {} of {} is not assigned", parameter, pou)
+            }
+            RuntimeError::RecursiveCall(name) => write!(f, "recursive call of {}", name),
+            RuntimeError::RecursiveType(name) => write!(f, "type {} contains itself", name),
+            RuntimeError::NoSuchMember { data_type, member } => {
+                write!(f, "{} has no member {}", data_type, member)
+            }
+            RuntimeError::NoSuchValue { data_type, value } => {
+                write!(f, "{} has no value {}", data_type, value)
+            }
+            RuntimeError::InternalMember { data_type, member } => {
+                write!(f, "{} is an internal variable of {}", member, data_type)
+            }
+            RuntimeError::ReadOnlyMember { data_type, member } => {
+                write!(f, "output {} of {} cannot be assigned", member, data_type)
+            }
+            RuntimeError::OutOfRange { value, range } => {
+                write!(f, "{} is out of range for {}", value, range)
+            }
+            RuntimeError::InvalidConversion { text, to } => {
+                write!(f, "'{}' cannot be converted to {}", text, to)
+            }
+            RuntimeError::IndexOutOfBounds {
+                index,
+                lower,
+                upper,
+            } => write!(
+                f,
+                "index {} is outside the array bounds {}..{}",
+                index, lower, upper
+            ),
+            RuntimeError::WrongIndexCount { expected, found } => write!(
+                f,
+                "array has {} dimensions, found {} indices",
+                expected, found
+            ),
+            RuntimeError::NoProgram => write!(f, "no PROGRAM to run"),
+            RuntimeError::ZeroPeriod => write!(f, "a task with a period of 0s stops the clock"),
+            RuntimeError::UnknownTask(name) => write!(f, "unknown task {}", name),
+            RuntimeError::InvalidAddress { address, data_type } => {
+                write!(f, "{} cannot be located at {}", data_type, address)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+/// Any error from interpreting a source text. Runtime errors carry the span
+/// of the statement or expression that was executing.
+#[derive(Debug)]
+pub enum Error {
+    Parse(ParseError),
+    Runtime(RuntimeError, Span),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(error) => write!(f, "Parse error at {}: {}", error.span(), error),
+            Error::Runtime(error, span) => write!(f, "Runtime error at {}: {}", span, error),
+            Error::Io(error) => write!(f, "IO error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Error {
+        Error::Parse(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        Error::Io(error)
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::iter::FromIterator;
+use std::ops::Index;
+
+/// A map from identifiers to values. Identifiers are case-insensitive, so
+/// entries are keyed by the uppercased name and `Count` and `COUNT` are the
+/// same entry. Each entry keeps the spelling it was inserted with for output
+/// and diagnostics.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Identifiers<V> {
+    entries: HashMap<String, (String, V)>,
+}
+
+impl<V> Identifiers<V> {
+    pub fn new() -> Identifiers<V> {
+        Identifiers {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&V> {
+        self.entries
+            .get(&name.to_ascii_uppercase())
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut V> {
+        self.entries
+            .get_mut(&name.to_ascii_uppercase())
+            .map(|(_, value)| value)
+    }
+
+    /// Inserts `value` under `name`, replacing the value and spelling of an
+    /// entry that differs only in case.
+    pub fn insert(&mut self, name: String, value: V) -> Option<V> {
+        self.entries
+            .insert(name.to_ascii_uppercase(), (name, value))
+            .map(|(_, value)| value)
+    }
+
+    /// The entries with the spelling they were inserted with.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &V)> {
+        self.entries
+            .values()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.values().map(|(_, value)| value)
+    }
+}
+
+impl<V> Default for Identifiers<V> {
+    fn default() -> Identifiers<V> {
+        Identifiers::new()
+    }
+}
+
+impl<V> FromIterator<(String, V)> for Identifiers<V> {
+    fn from_iter<I: IntoIterator<Item = (String, V)>>(iter: I) -> Identifiers<V> {
+        let mut identifiers = Identifiers::new();
+        for (name, value) in iter {
+            identifiers.insert(name, value);
+        }
+        identifiers
+    }
+}
+
+impl<V> Index<&str> for Identifiers<V> {
+    type Output = V;
+
+    fn index(&self, name: &str) -> &V {
+        match self.get(name) {
+            Some(value) => value,
+            None => panic!("No identifier {}", name),
+        }
+    }
+}
+
+#[test]
+fn identifiers_ignore_case() {
+    let mut identifiers: Identifiers<i32> = vec![("Count".to_string(), 1)].into_iter().collect();
+    assert_eq!(identifiers.get("COUNT"), Some(&1));
+    assert_eq!(identifiers["count"], 1);
+    *identifiers.get_mut("cOUNT").unwrap() = 2;
+    assert_eq!(identifiers.insert("COUNT".to_string(), 3), Some(2));
+    assert_eq!(identifiers.iter().collect::<Vec<_>>(), vec![("COUNT", &3)]);
+}
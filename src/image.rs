@@ -0,0 +1,269 @@
+use std::fmt;
+
+use crate::error::RuntimeError;
+use crate::object::{DataType, Object};
+
+/// The size in bytes of each area of the process image.
+pub const AREA_SIZE: usize = 65536;
+
+/// The memory area of a directly represented variable.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Area {
+    Input,
+    Output,
+    Memory,
+}
+
+/// The location of a directly represented variable such as `%IX0.0` or
+/// `%QW4`. Addresses count bytes, so `%QW4` is the word in bytes 4 and 5.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Address {
+    pub area: Area,
+    /// The size in bits, 1 for `X` and 8, 16, 32 or 64 for `B`, `W`, `D`
+    /// and `L`.
+    pub width: u32,
+    pub offset: usize,
+    /// The bit within the byte at `offset` of a single bit.
+    pub bit: u32,
+}
+
+impl Address {
+    /// Parses a direct address such as `%IX0.0`, `%I0.0` or `%MD8`. The
+    /// addressed bytes must lie within the `AREA_SIZE` bytes of the area.
+    pub fn parse(text: &str) -> Option<Address> {
+        let mut chars = text.strip_prefix('%')?.chars();
+        let area = match chars.next()?.to_ascii_uppercase() {
+            'I' => Area::Input,
+            'Q' => Area::Output,
+            'M' => Area::Memory,
+            _ => return None,
+        };
+        let rest = chars.as_str();
+        let (width, location) = match rest.chars().next()?.to_ascii_uppercase() {
+            'X' => (1, &rest[1..]),
+            'B' => (8, &rest[1..]),
+            'W' => (16, &rest[1..]),
+            'D' => (32, &rest[1..]),
+            'L' => (64, &rest[1..]),
+            _ => (1, rest),
+        };
+        if !location.chars().all(|ch| ch.is_ascii_digit() || ch == '.') {
+            return None;
+        }
+        let (offset, bit) = if width == 1 {
+            let (offset, bit) = location.split_once('.')?;
+            (
+                offset.parse().ok()?,
+                bit.parse().ok().filter(|bit| *bit < 8)?,
+            )
+        } else {
+            (location.parse().ok()?, 0)
+        };
+        let address = Address {
+            area,
+            width,
+            offset,
+            bit,
+        };
+        address.end()?;
+        Some(address)
+    }
+
+    /// The offset of the byte after the address, `None` if it lies outside
+    /// the area.
+    fn end(&self) -> Option<usize> {
+        self.offset
+            .checked_add((self.width as usize).div_ceil(8))
+            .filter(|end| *end <= AREA_SIZE)
+    }
+
+    /// Checks that a variable of `data_type` can be located at the address:
+    /// a BOOL, integer, bit string or real type of the address's size.
+    pub fn check(&self, data_type: &DataType) -> Result<(), RuntimeError> {
+        let elementary = data_type.is_bit() || data_type.is_numeric();
+        if elementary && data_type.width() == self.width {
+            Ok(())
+        } else {
+            Err(RuntimeError::InvalidAddress {
+                address: self.to_string(),
+                data_type: data_type.clone(),
+            })
+        }
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let area = match self.area {
+            Area::Input => 'I',
+            Area::Output => 'Q',
+            Area::Memory => 'M',
+        };
+        match self.width {
+            1 => write!(f, "%{}X{}.{}", area, self.offset, self.bit),
+            8 => write!(f, "%{}B{}", area, self.offset),
+            16 => write!(f, "%{}W{}", area, self.offset),
+            32 => write!(f, "%{}D{}", area, self.offset),
+            _ => write!(f, "%{}L{}", area, self.offset),
+        }
+    }
+}
+
+/// The input, output and memory areas shared by the programs and the host.
+/// Multi-byte values are little-endian. The areas grow as they are written,
+/// bytes never written read as 0.
+#[derive(Debug, Default)]
+pub struct ProcessImage {
+    inputs: Vec<u8>,
+    outputs: Vec<u8>,
+    memory: Vec<u8>,
+}
+
+impl ProcessImage {
+    pub fn new() -> ProcessImage {
+        ProcessImage::default()
+    }
+
+    /// The value at `address` as an object of `data_type`, which must fit
+    /// the address, see `Address::check`.
+    pub fn get(&self, address: &Address, data_type: &DataType) -> Object {
+        let bits = self.bits(address);
+        match data_type {
+            DataType::Real => Object::Real(f32::from_bits(bits as u32)),
+            DataType::LReal => Object::LReal(f64::from_bits(bits)),
+            _ => Object::from_i128(bits.into(), data_type.clone()),
+        }
+    }
+
+    /// Stores `value` at `address`, which must lie within the area.
+    pub fn set(&mut self, address: &Address, value: &Object) -> Result<(), RuntimeError> {
+        let value = value.operand();
+        address.check(&value.data_type())?;
+        let bits = match value {
+            Object::Real(value) => value.to_bits().into(),
+            Object::LReal(value) => value.to_bits(),
+            value => value.as_i128().unwrap() as u64,
+        };
+        let length = address.end().ok_or_else(|| RuntimeError::OutOfRange {
+            value: address.to_string(),
+            range: "the process image".to_string(),
+        })?;
+        let bytes = self.area_mut(address.area);
+        if bytes.len() < length {
+            bytes.resize(length, 0);
+        }
+        if address.width == 1 {
+            let mask = 1 << address.bit;
+            let byte = &mut bytes[address.offset];
+            *byte = (*byte & !mask) | ((bits as u8 & 1) << address.bit);
+        } else {
+            for (index, byte) in bytes[address.offset..length].iter_mut().enumerate() {
+                *byte = (bits >> (8 * index)) as u8;
+            }
+        }
+        Ok(())
+    }
+
+    fn bits(&self, address: &Address) -> u64 {
+        let bytes = match address.area {
+            Area::Input => &self.inputs,
+            Area::Output => &self.outputs,
+            Area::Memory => &self.memory,
+        };
+        let byte = |index: usize| {
+            let byte = address.offset.checked_add(index).and_then(|i| bytes.get(i));
+            u64::from(byte.copied().unwrap_or(0))
+        };
+        if address.width == 1 {
+            return (byte(0) >> address.bit) & 1;
+        }
+        (0..address.width as usize / 8).fold(0, |bits, index| bits | byte(index) << (8 * index))
+    }
+
+    fn area_mut(&mut self, area: Area) -> &mut Vec<u8> {
+        match area {
+            Area::Input => &mut self.inputs,
+            Area::Output => &mut self.outputs,
+            Area::Memory => &mut self.memory,
+        }
+    }
+}
+
+#[test]
+fn parse_addresses() {
+    let address = |text| Address::parse(text).map(|address| address.to_string());
+    assert_eq!(address("%IX0.7"), Some("%IX0.7".to_string()));
+    assert_eq!(address("%i2.1"), Some("%IX2.1".to_string()));
+    assert_eq!(address("%QW4"), Some("%QW4".to_string()));
+    assert_eq!(address("%MD12"), Some("%MD12".to_string()));
+    assert_eq!(address("%MB65535"), Some("%MB65535".to_string()));
+    for text in [
+        "%IX0.8",
+        "%IX0",
+        "%QW4.1",
+        "%AW4",
+        "%IB",
+        "%IW+4",
+        "IW4",
+        "%MW65535",
+        "%QW1000000000000",
+        "%QW18446744073709551615",
+        "%QW18446744073709551616",
+    ] {
+        assert_eq!(Address::parse(text), None, "{}", text);
+    }
+}
+
+#[test]
+fn process_image_is_little_endian() {
+    let address = |text| Address::parse(text).unwrap();
+    let mut image = ProcessImage::new();
+    image.set(&address("%QW4"), &Object::Int(-2)).unwrap();
+    assert_eq!(image.outputs, vec![0, 0, 0, 0, 0xFE, 0xFF]);
+    assert_eq!(
+        image.get(&address("%QB5"), &DataType::Byte),
+        Object::Byte(0xFF)
+    );
+    assert_eq!(
+        image.get(&address("%QX4.1"), &DataType::Bool),
+        Object::Bool(true)
+    );
+    assert_eq!(
+        image.get(&address("%QX4.0"), &DataType::Bool),
+        Object::Bool(false)
+    );
+
+    image.set(&address("%MD0"), &Object::Real(1.5)).unwrap();
+    assert_eq!(
+        image.get(&address("%MD0"), &DataType::Real),
+        Object::Real(1.5)
+    );
+    image.set(&address("%IX1.3"), &Object::Bool(true)).unwrap();
+    assert_eq!(
+        image.get(&address("%IB1"), &DataType::USInt),
+        Object::USInt(8)
+    );
+    assert_eq!(
+        image.get(&address("%IW8"), &DataType::Word),
+        Object::Word(0)
+    );
+    assert_eq!(
+        image.set(&address("%IW2"), &Object::DInt(1)),
+        Err(RuntimeError::InvalidAddress {
+            address: "%IW2".to_string(),
+            data_type: DataType::DInt,
+        })
+    );
+    let outside = Address {
+        offset: usize::MAX,
+        ..address("%QW0")
+    };
+    assert_eq!(
+        image
+            .set(&outside, &Object::Word(1))
+            .unwrap_err()
+            .to_string(),
+        format!("%QW{} is out of range for the process image", usize::MAX)
+    );
+    assert_eq!(image.get(&outside, &DataType::Word), Object::Word(0));
+}
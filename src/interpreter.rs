@@ -1,162 +1,2048 @@
 use log::trace;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
 
-use crate::ast::{Assignment, BinaryOp, CompoundStatement, Node, Num, UnaryOp, Variable};
+use crate::ast::{
+    Assignment, BinaryOp, Call, CaseLabel, CaseStatement, CompilationUnit, CompoundStatement,
+    Configuration, EnumValue, ForStatement, FunctionBlockDecl, FunctionDecl, IfStatement, Index,
+    Member, Node, Num, Program, ProgramConfiguration, RepeatStatement, Resource, TaskDecl, Type,
+    TypeDecl, TypeDefinition, UnaryOp, VarDecl, VarKind, Variable, WhileStatement,
+};
 
+use crate::clock::{Clock, MonotonicClock};
+use crate::error::{Error, RuntimeError};
+use crate::identifiers::Identifiers;
+use crate::image::{Area, ProcessImage};
+use crate::object::{Array, DataType, Enumerated, Instance, Object, Struct, Subrange};
 use crate::parser::Parser;
-use crate::token::Token;
+use crate::stdlib::{self, StandardFunction};
+use crate::token::{Span, Token};
 
-pub fn walk_unary_op<V: Visitor + ?Sized>(visitor: &mut V, unary_op: &UnaryOp) {
-    visitor.visit(&unary_op.expr);
+pub fn walk_compilation_unit<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    compilation_unit: &CompilationUnit,
+) -> Result<(), V::Error> {
+    for pou in &compilation_unit.pous {
+        visitor.visit(pou)?;
+    }
+    Ok(())
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    program: &Program,
+) -> Result<(), V::Error> {
+    for declaration in &program.declarations {
+        visitor.visit(declaration)?;
+    }
+    visitor.visit(&program.body)?;
+    Ok(())
+}
+
+pub fn walk_function_decl<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    function_decl: &FunctionDecl,
+) -> Result<(), V::Error> {
+    for declaration in &function_decl.declarations {
+        visitor.visit(declaration)?;
+    }
+    visitor.visit(&function_decl.body)?;
+    Ok(())
+}
+
+pub fn walk_function_block_decl<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    function_block_decl: &FunctionBlockDecl,
+) -> Result<(), V::Error> {
+    for declaration in &function_block_decl.declarations {
+        visitor.visit(declaration)?;
+    }
+    visitor.visit(&function_block_decl.body)?;
+    Ok(())
+}
+
+pub fn walk_type_decl<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    type_decl: &TypeDecl,
+) -> Result<(), V::Error> {
+    match &type_decl.definition {
+        TypeDefinition::Struct(members) => {
+            for member in members {
+                visitor.visit(member)?;
+            }
+        }
+        TypeDefinition::Enum(..) | TypeDefinition::Derived(_) => {}
+    }
+    Ok(())
+}
+
+pub fn walk_var_decl<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    var_decl: &VarDecl,
+) -> Result<(), V::Error> {
+    if let Some(initial_value) = &var_decl.initial_value {
+        visitor.visit(initial_value)?;
+    }
+    Ok(())
+}
+
+pub fn walk_unary_op<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    unary_op: &UnaryOp,
+) -> Result<(), V::Error> {
+    visitor.visit(&unary_op.expr)?;
+    Ok(())
 }
 
-pub fn walk_binary_op<V: Visitor + ?Sized>(visitor: &mut V, binary_op: &BinaryOp) {
-    visitor.visit(&binary_op.left);
-    visitor.visit(&binary_op.right);
+pub fn walk_binary_op<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    binary_op: &BinaryOp,
+) -> Result<(), V::Error> {
+    visitor.visit(&binary_op.left)?;
+    visitor.visit(&binary_op.right)?;
+    Ok(())
 }
 
-pub fn walk_assignment<V: Visitor + ?Sized>(visitor: &mut V, assignment: &Assignment) {
-    visitor.visit(&assignment.left);
-    visitor.visit(&assignment.right);
+pub fn walk_assignment<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    assignment: &Assignment,
+) -> Result<(), V::Error> {
+    visitor.visit(&assignment.left)?;
+    visitor.visit(&assignment.right)?;
+    Ok(())
+}
+
+pub fn walk_member<V: Visitor + ?Sized>(visitor: &mut V, member: &Member) -> Result<(), V::Error> {
+    visitor.visit(&member.base)?;
+    Ok(())
+}
+
+pub fn walk_index<V: Visitor + ?Sized>(visitor: &mut V, index: &Index) -> Result<(), V::Error> {
+    visitor.visit(&index.base)?;
+    for index in &index.indices {
+        visitor.visit(index)?;
+    }
+    Ok(())
+}
+
+pub fn walk_call<V: Visitor + ?Sized>(visitor: &mut V, call: &Call) -> Result<(), V::Error> {
+    if let Some(instance) = &call.instance {
+        visitor.visit(instance)?;
+    }
+    for argument in &call.arguments {
+        visitor.visit(&argument.value)?;
+    }
+    Ok(())
+}
+
+pub fn walk_if_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    if_statement: &IfStatement,
+) -> Result<(), V::Error> {
+    visitor.visit(&if_statement.condition)?;
+    visitor.visit(&if_statement.then_branch)?;
+    if let Some(else_branch) = &if_statement.else_branch {
+        visitor.visit(else_branch)?;
+    }
+    Ok(())
+}
+
+pub fn walk_case_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    case_statement: &CaseStatement,
+) -> Result<(), V::Error> {
+    visitor.visit(&case_statement.selector)?;
+    for element in &case_statement.elements {
+        for label in &element.labels {
+            match label {
+                CaseLabel::Value(value) => visitor.visit(value)?,
+                CaseLabel::Range(low, high) => {
+                    visitor.visit(low)?;
+                    visitor.visit(high)?;
+                }
+            }
+        }
+        visitor.visit(&element.body)?;
+    }
+    if let Some(else_branch) = &case_statement.else_branch {
+        visitor.visit(else_branch)?;
+    }
+    Ok(())
+}
+
+pub fn walk_for_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    for_statement: &ForStatement,
+) -> Result<(), V::Error> {
+    visitor.visit_variable(&for_statement.control_variable)?;
+    visitor.visit(&for_statement.start)?;
+    visitor.visit(&for_statement.end)?;
+    if let Some(step) = &for_statement.step {
+        visitor.visit(step)?;
+    }
+    visitor.visit(&for_statement.body)?;
+    Ok(())
+}
+
+pub fn walk_while_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    while_statement: &WhileStatement,
+) -> Result<(), V::Error> {
+    visitor.visit(&while_statement.condition)?;
+    visitor.visit(&while_statement.body)?;
+    Ok(())
+}
+
+pub fn walk_repeat_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    repeat_statement: &RepeatStatement,
+) -> Result<(), V::Error> {
+    visitor.visit(&repeat_statement.body)?;
+    visitor.visit(&repeat_statement.condition)?;
+    Ok(())
 }
 
 pub trait Visitor {
-    fn visit(&mut self, node: &Node) {
+    type Error;
+
+    fn visit(&mut self, node: &Node) -> Result<(), Self::Error> {
         match node {
-            Node::UnaryOp(unary_op) => self.visit_unary_op(&unary_op),
-            Node::BinaryOp(binary_op) => self.visit_binary_op(&binary_op),
-            Node::Num(num) => self.visit_num(&num),
-            Node::Assignment(assignment) => self.visit_assignment(&assignment),
-            Node::Variable(variable) => self.visit_variable(&variable),
+            Node::CompilationUnit(compilation_unit) => {
+                self.visit_compilation_unit(compilation_unit)
+            }
+            Node::Program(program) => self.visit_program(program),
+            Node::FunctionDecl(function_decl) => self.visit_function_decl(function_decl),
+            Node::FunctionBlockDecl(function_block_decl) => {
+                self.visit_function_block_decl(function_block_decl)
+            }
+            Node::TypeDecl(type_decl) => self.visit_type_decl(type_decl),
+            Node::Configuration(configuration) => self.visit_configuration(configuration),
+            Node::VarDecl(var_decl) => self.visit_var_decl(var_decl),
+            Node::UnaryOp(unary_op) => self.visit_unary_op(unary_op),
+            Node::BinaryOp(binary_op) => self.visit_binary_op(binary_op),
+            Node::Num(num) => self.visit_num(num),
+            Node::EnumValue(enum_value) => self.visit_enum_value(enum_value),
+            Node::Assignment(assignment) => self.visit_assignment(assignment),
+            Node::Variable(variable) => self.visit_variable(variable),
+            Node::Member(member) => self.visit_member(member),
+            Node::Index(index) => self.visit_index(index),
+            Node::Call(call) => self.visit_call(call),
             Node::CompoundStatement(compound_statement) => {
-                self.visit_compound_statement(&compound_statement)
+                self.visit_compound_statement(compound_statement)
+            }
+            Node::IfStatement(if_statement) => self.visit_if_statement(if_statement),
+            Node::CaseStatement(case_statement) => self.visit_case_statement(case_statement),
+            Node::ForStatement(for_statement) => self.visit_for_statement(for_statement),
+            Node::WhileStatement(while_statement) => self.visit_while_statement(while_statement),
+            Node::RepeatStatement(repeat_statement) => {
+                self.visit_repeat_statement(repeat_statement)
             }
-            Node::NoOp => {}
+            Node::Exit(_) => self.visit_exit(),
+            Node::Continue(_) => self.visit_continue(),
+            Node::Return(_) => self.visit_return(),
+            Node::NoOp => Ok(()),
         }
     }
 
-    fn visit_unary_op(&mut self, unary_op: &UnaryOp) {
-        walk_unary_op(self, unary_op);
+    fn visit_compilation_unit(
+        &mut self,
+        compilation_unit: &CompilationUnit,
+    ) -> Result<(), Self::Error> {
+        walk_compilation_unit(self, compilation_unit)
+    }
+
+    fn visit_program(&mut self, program: &Program) -> Result<(), Self::Error> {
+        walk_program(self, program)
+    }
+
+    fn visit_function_decl(&mut self, function_decl: &FunctionDecl) -> Result<(), Self::Error> {
+        walk_function_decl(self, function_decl)
+    }
+
+    fn visit_function_block_decl(
+        &mut self,
+        function_block_decl: &FunctionBlockDecl,
+    ) -> Result<(), Self::Error> {
+        walk_function_block_decl(self, function_block_decl)
+    }
+
+    fn visit_type_decl(&mut self, type_decl: &TypeDecl) -> Result<(), Self::Error> {
+        walk_type_decl(self, type_decl)
+    }
+
+    #[allow(unused_variables)]
+    fn visit_configuration(&mut self, configuration: &Configuration) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_var_decl(&mut self, var_decl: &VarDecl) -> Result<(), Self::Error> {
+        walk_var_decl(self, var_decl)
+    }
+
+    fn visit_unary_op(&mut self, unary_op: &UnaryOp) -> Result<(), Self::Error> {
+        walk_unary_op(self, unary_op)
     }
 
-    fn visit_binary_op(&mut self, binary_op: &BinaryOp) {
-        walk_binary_op(self, binary_op);
+    fn visit_binary_op(&mut self, binary_op: &BinaryOp) -> Result<(), Self::Error> {
+        walk_binary_op(self, binary_op)
     }
 
-    fn visit_assignment(&mut self, assignment: &Assignment) {
-        walk_assignment(self, assignment);
+    fn visit_assignment(&mut self, assignment: &Assignment) -> Result<(), Self::Error> {
+        walk_assignment(self, assignment)
     }
 
     #[allow(unused_variables)]
-    fn visit_num(&mut self, num: &Num) {}
+    fn visit_num(&mut self, num: &Num) -> Result<(), Self::Error> {
+        Ok(())
+    }
 
     #[allow(unused_variables)]
-    fn visit_variable(&mut self, variable: &Variable) {}
+    fn visit_enum_value(&mut self, enum_value: &EnumValue) -> Result<(), Self::Error> {
+        Ok(())
+    }
 
-    fn visit_compound_statement(&mut self, compound_statement: &CompoundStatement) {
+    #[allow(unused_variables)]
+    fn visit_variable(&mut self, variable: &Variable) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_member(&mut self, member: &Member) -> Result<(), Self::Error> {
+        walk_member(self, member)
+    }
+
+    fn visit_index(&mut self, index: &Index) -> Result<(), Self::Error> {
+        walk_index(self, index)
+    }
+
+    fn visit_call(&mut self, call: &Call) -> Result<(), Self::Error> {
+        walk_call(self, call)
+    }
+
+    fn visit_compound_statement(
+        &mut self,
+        compound_statement: &CompoundStatement,
+    ) -> Result<(), Self::Error> {
         trace!("Visiting compound statement");
         for node in &compound_statement.statements {
-            match node {
-                Node::Assignment(assignment) => {
-                    self.visit_assignment(&assignment);
-                }
-                Node::NoOp => trace!("Visited NoOp!"),
-                _ => {
-                    panic!("No valid node found in statement list {:?}", node);
+            self.visit(node)?;
+        }
+        Ok(())
+    }
+
+    fn visit_if_statement(&mut self, if_statement: &IfStatement) -> Result<(), Self::Error> {
+        walk_if_statement(self, if_statement)
+    }
+
+    fn visit_case_statement(&mut self, case_statement: &CaseStatement) -> Result<(), Self::Error> {
+        walk_case_statement(self, case_statement)
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ForStatement) -> Result<(), Self::Error> {
+        walk_for_statement(self, for_statement)
+    }
+
+    fn visit_while_statement(
+        &mut self,
+        while_statement: &WhileStatement,
+    ) -> Result<(), Self::Error> {
+        walk_while_statement(self, while_statement)
+    }
+
+    fn visit_repeat_statement(
+        &mut self,
+        repeat_statement: &RepeatStatement,
+    ) -> Result<(), Self::Error> {
+        walk_repeat_statement(self, repeat_statement)
+    }
+
+    fn visit_exit(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_continue(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_return(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Pending EXIT or CONTINUE, unwinding statement lists up to the
+/// innermost loop, or RETURN, unwinding up to the POU body.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ControlFlow {
+    Normal,
+    Exit,
+    Continue,
+    Return,
+}
+
+/// A step from a variable to the part of it an assignment stores to.
+enum Selector<'a> {
+    Member(&'a str),
+    /// The evaluated indices and the span of the subscript.
+    Index(Vec<i128>, Span),
+}
+
+/// The labels of a CASE statement, resolved once when its POU is declared.
+struct CaseLabels {
+    /// The type of the labels, checked against the selector when the
+    /// statement runs.
+    data_type: Option<DataType>,
+    /// The span of the first label, locating a selector of the wrong type.
+    span: Span,
+    /// The values each label selects and the index of its element.
+    ranges: Vec<(i128, i128, usize)>,
+}
+
+/// Resolves the labels of the CASE statements in the POUs it visits, see
+/// `Interpreter::resolve_case`.
+struct CaseResolver<'a> {
+    interpreter: &'a mut Interpreter,
+    /// The enumerated type of each variable of the POU being visited.
+    enum_variables: Identifiers<String>,
+}
+
+impl CaseResolver<'_> {
+    fn enter(&mut self, declarations: &[Node]) {
+        self.enum_variables = Identifiers::new();
+        for declaration in declarations {
+            if let Node::VarDecl(var_decl) = declaration {
+                let is_enum = matches!(
+                    self.interpreter.types.get(&var_decl.var_type.name),
+                    Some(type_decl) if matches!(type_decl.definition, TypeDefinition::Enum(..))
+                );
+                if is_enum && var_decl.var_type.dimensions.is_empty() {
+                    self.enum_variables
+                        .insert(var_decl.variable.id.clone(), var_decl.var_type.name.clone());
                 }
             }
         }
     }
 }
 
-enum Object {
-    Integer(i32),
+impl Visitor for CaseResolver<'_> {
+    type Error = RuntimeError;
+
+    fn visit_program(&mut self, program: &Program) -> Result<(), RuntimeError> {
+        self.enter(&program.declarations);
+        walk_program(self, program)
+    }
+
+    fn visit_function_decl(&mut self, function_decl: &FunctionDecl) -> Result<(), RuntimeError> {
+        self.enter(&function_decl.declarations);
+        walk_function_decl(self, function_decl)
+    }
+
+    fn visit_function_block_decl(
+        &mut self,
+        function_block_decl: &FunctionBlockDecl,
+    ) -> Result<(), RuntimeError> {
+        self.enter(&function_block_decl.declarations);
+        walk_function_block_decl(self, function_block_decl)
+    }
+
+    fn visit_case_statement(&mut self, case_statement: &CaseStatement) -> Result<(), RuntimeError> {
+        let selector_enum = match case_statement.selector.as_ref() {
+            Node::Variable(variable) => self.enum_variables.get(&variable.id).cloned(),
+            _ => None,
+        };
+        self.interpreter
+            .resolve_case(case_statement, selector_enum.as_deref())?;
+        walk_case_statement(self, case_statement)
+    }
+}
+
+/// The local variables of one function invocation, or the variables of the
+/// function block instance being executed.
+struct Frame {
+    function: String,
+    scope: Identifiers<Object>,
+}
+
+impl Frame {
+    fn new(function: String) -> Frame {
+        Frame {
+            function,
+            scope: Identifiers::new(),
+        }
+    }
+}
+
+/// A program run by a task. An instance declared in a configuration keeps
+/// its variables in an `Object::Instance` in the global scope, under its
+/// name. Without a configuration a program uses the global scope itself.
+#[derive(Clone)]
+struct ProgramInstance {
+    name: String,
+    program: Rc<Program>,
+    instance: bool,
+}
+
+/// A task of a resource, running its programs every `interval`, or in
+/// every cycle of the interpreter without one.
+struct Task {
+    name: String,
+    interval: Option<Duration>,
+    /// Tasks due at the same time run in order of priority, 0 first.
+    priority: i128,
+    /// The time the task is next released.
+    due: Duration,
+    programs: Vec<ProgramInstance>,
+    /// The TASK declaration, or for a cyclic task the first program it runs.
+    span: Span,
 }
+
+impl Task {
+    fn new(name: String, priority: i128, due: Duration, span: Span) -> Task {
+        Task {
+            name,
+            interval: None,
+            priority,
+            due,
+            programs: Vec::new(),
+            span,
+        }
+    }
+
+    /// Moves the release after a run at `now` to the first one past it.
+    /// Releases missed while the task could not run are skipped.
+    fn release(&mut self, now: Duration, period: Duration) {
+        let interval = self.interval.unwrap_or(period).as_nanos();
+        if interval == 0 {
+            self.due = now;
+            return;
+        }
+        let missed = (now - self.due).as_nanos() / interval;
+        self.due += Duration::from_nanos(((missed + 1) * interval) as u64);
+    }
+}
+
 pub struct Interpreter {
     parser: Parser,
-    object: i32,
-    pub global_scope: HashMap<String, i32>,
+    object: Object,
+    control_flow: ControlFlow,
+    functions: Identifiers<Rc<FunctionDecl>>,
+    function_blocks: Identifiers<Rc<FunctionBlockDecl>>,
+    /// The data types declared in TYPE blocks.
+    types: Identifiers<Rc<TypeDecl>>,
+    /// The labels of each CASE statement, by the offset of its CASE keyword.
+    case_labels: HashMap<usize, Rc<CaseLabels>>,
+    standard_functions: HashMap<String, &'static StandardFunction>,
+    /// Native bodies of the standard function blocks, by type name.
+    standard_bodies: HashMap<String, stdlib::Body>,
+    call_stack: Vec<Frame>,
+    /// The tasks running the programs in cyclic execution, see `load`.
+    tasks: Vec<Task>,
+    /// The time source of the standard timers.
+    clock: Box<dyn Clock>,
+    /// Span of the innermost statement or expression being executed, used
+    /// to locate runtime errors.
+    span: Span,
+    pub global_scope: Identifiers<Object>,
+    /// The areas of the located variables, which a host reads and writes
+    /// between cycles.
+    pub image: ProcessImage,
 }
 
 impl Interpreter {
     pub fn new(parser: Parser) -> Interpreter {
+        Interpreter::with_clock(parser, Box::new(MonotonicClock::new()))
+    }
+
+    pub fn with_clock(parser: Parser, clock: Box<dyn Clock>) -> Interpreter {
+        let mut function_blocks = Identifiers::new();
+        let mut standard_bodies = HashMap::new();
+        for standard in stdlib::FUNCTION_BLOCKS {
+            let declaration = standard.declaration();
+            standard_bodies.insert(declaration.name.clone(), standard.body);
+            function_blocks.insert(declaration.name.clone(), Rc::new(declaration));
+        }
+        let standard_functions = stdlib::functions().collect();
         Interpreter {
             parser,
-            object: 0,
-            global_scope: HashMap::new(),
+            object: Object::from_integer(0),
+            control_flow: ControlFlow::Normal,
+            functions: Identifiers::new(),
+            function_blocks,
+            types: Identifiers::new(),
+            case_labels: HashMap::new(),
+            standard_functions,
+            standard_bodies,
+            call_stack: Vec::new(),
+            tasks: Vec::new(),
+            clock,
+            span: Span::default(),
+            global_scope: Identifiers::new(),
+            image: ProcessImage::new(),
         }
     }
 
-    pub fn interpret(&mut self) {
-        self.interpreter_writer(&mut std::io::stdout());
+    pub fn interpret(&mut self) -> Result<(), Error> {
+        self.interpreter_writer(&mut std::io::stdout())
     }
 
-    pub fn interpreter_writer(&mut self, mut writer: &mut impl std::io::Write) {
+    pub fn interpreter_writer(
+        &mut self,
+        mut writer: &mut impl std::io::Write,
+    ) -> Result<(), Error> {
         trace! {"Start interpreting"}
-        let tree = self.parser.parse();
+        let tree = self.parser.parse()?;
         trace!("Start visiting");
-        self.visit(&tree);
+        self.span = tree.span();
+        self.visit(&tree)
+            .map_err(|error| Error::Runtime(error, self.span))?;
         trace!("End visiting");
 
-        match writeln!(&mut writer, "{}", self.object) {
-            Ok(_) => {}
-            Err(error) => panic!("Error in writeln! interpreter_writer: {}", error),
+        writeln!(&mut writer, "{}", self.object)?;
+        Ok(())
+    }
+
+    /// Parses the source and prepares its programs for cyclic execution,
+    /// without running them. The program instances of a configuration run
+    /// in their tasks; without a configuration every program runs in each
+    /// cycle, with its variables in the global scope.
+    pub fn load(&mut self) -> Result<(), Error> {
+        let tree = self.parser.parse()?;
+        self.span = tree.span();
+        let compilation_unit = match &tree {
+            Node::CompilationUnit(compilation_unit) => compilation_unit,
+            _ => return Err(Error::Runtime(RuntimeError::NoProgram, self.span)),
+        };
+        self.declare(compilation_unit)
+            .and_then(|_| self.schedule(compilation_unit))
+            .map_err(|error| Error::Runtime(error, self.span))?;
+        if self.tasks.iter().all(|task| task.programs.is_empty()) {
+            return Err(Error::Runtime(RuntimeError::NoProgram, self.span));
+        }
+        Ok(())
+    }
+
+    /// Runs the tasks due at the current time of the clock, by priority
+    /// with 0 first and then in declaration order, and returns the names of
+    /// the program instances run. Tasks without an INTERVAL are due every
+    /// `period`. Variables keep their values between cycles.
+    pub fn tick(&mut self, period: Duration) -> Result<Vec<String>, Error> {
+        let now = self.clock.now();
+        let mut due: Vec<usize> = (0..self.tasks.len())
+            .filter(|&index| self.tasks[index].due <= now)
+            .collect();
+        due.sort_by_key(|&index| self.tasks[index].priority);
+        let mut executed = Vec::new();
+        for index in due {
+            trace!("Running task {}", self.tasks[index].name);
+            for instance in self.tasks[index].programs.clone() {
+                self.run_program(&instance)
+                    .map_err(|error| Error::Runtime(error, self.span))?;
+                executed.push(instance.name);
+            }
+            self.tasks[index].release(now, period);
+        }
+        Ok(executed)
+    }
+
+    /// Runs `cycles` cycles, see `run_until`. Unlike there, a `period` of 0
+    /// runs the cycles back to back on any clock.
+    pub fn run_cycles(&mut self, cycles: u64, period: Duration) -> Result<(), Error> {
+        self.cycle_until(period, |_, completed| completed >= cycles)
+            .map(|_| ())
+    }
+
+    /// Runs cycles for `duration` of the clock, returns the number of cycles
+    /// run.
+    pub fn run_for(&mut self, duration: Duration, period: Duration) -> Result<u64, Error> {
+        let end = self.clock.now() + duration;
+        self.run_until(period, |interpreter, _| interpreter.clock.now() >= end)
+    }
+
+    /// Runs the tasks due, see `tick`, and waits until the next one is due,
+    /// until `stop` returns true. `stop` is asked before each cycle with the
+    /// number of cycles completed. Returns the number of cycles run.
+    ///
+    /// Fails if a task is due every 0s and the clock only moves when waited
+    /// on, as its time would stand still.
+    pub fn run_until(
+        &mut self,
+        period: Duration,
+        stop: impl FnMut(&Interpreter, u64) -> bool,
+    ) -> Result<u64, Error> {
+        if !self.clock.is_running() {
+            let stopped = self
+                .tasks
+                .iter()
+                .find(|task| task.interval.unwrap_or(period).is_zero());
+            if let Some(task) = stopped {
+                return Err(Error::Runtime(RuntimeError::ZeroPeriod, task.span));
+            }
+        }
+        self.cycle_until(period, stop)
+    }
+
+    fn cycle_until(
+        &mut self,
+        period: Duration,
+        mut stop: impl FnMut(&Interpreter, u64) -> bool,
+    ) -> Result<u64, Error> {
+        let mut completed = 0;
+        while !stop(self, completed) {
+            self.tick(period)?;
+            completed += 1;
+            if let Some(due) = self.tasks.iter().map(|task| task.due).min() {
+                self.clock.wait_until(due);
+            }
+        }
+        Ok(completed)
+    }
+
+    /// Creates the tasks and program instances of the configurations in
+    /// `compilation_unit`, or without one a task running all its programs.
+    fn schedule(&mut self, compilation_unit: &CompilationUnit) -> Result<(), RuntimeError> {
+        let mut programs = Identifiers::new();
+        for pou in &compilation_unit.pous {
+            if let Node::Program(program) = pou {
+                programs.insert(program.name.clone(), Rc::new(program.clone()));
+            }
+        }
+        let mut configured = false;
+        for pou in &compilation_unit.pous {
+            if let Node::Configuration(configuration) = pou {
+                trace!("Loading configuration {}", configuration.name);
+                for resource in &configuration.resources {
+                    self.load_resource(resource, &programs)?;
+                }
+                configured = true;
+            }
+        }
+        if configured {
+            return Ok(());
+        }
+
+        let span = compilation_unit.span();
+        let mut task = Task::new(String::new(), 0, self.clock.now(), span);
+        for pou in &compilation_unit.pous {
+            if let Node::Program(program) = pou {
+                trace!("Loading program {}", program.name);
+                for declaration in &program.declarations {
+                    self.visit(declaration)?;
+                }
+                task.programs.push(ProgramInstance {
+                    name: program.name.clone(),
+                    program: Rc::new(program.clone()),
+                    instance: false,
+                });
+            }
+        }
+        self.tasks.push(task);
+        Ok(())
+    }
+
+    /// Adds the tasks of `resource` and instantiates its programs. Programs
+    /// without a task run in a cyclic task of the lowest priority.
+    fn load_resource(
+        &mut self,
+        resource: &Resource,
+        programs: &Identifiers<Rc<Program>>,
+    ) -> Result<(), RuntimeError> {
+        trace!("Loading resource {}", resource.name);
+        let first = self.tasks.len();
+        for task_decl in &resource.tasks {
+            self.span = task_decl.span();
+            if self.tasks[first..]
+                .iter()
+                .any(|task| task.name.eq_ignore_ascii_case(&task_decl.name))
+            {
+                return Err(RuntimeError::DuplicateDeclaration(task_decl.name.clone()));
+            }
+            let task = self.task(task_decl)?;
+            self.tasks.push(task);
+        }
+
+        let span = resource
+            .programs
+            .iter()
+            .find(|program_configuration| program_configuration.task.is_none())
+            .map_or_else(Span::default, ProgramConfiguration::span);
+        let mut cyclic = Task::new(resource.name.clone(), i128::MAX, self.clock.now(), span);
+        for program_configuration in &resource.programs {
+            self.span = program_configuration.span();
+            let name = &program_configuration.name;
+            let program = match programs.get(&program_configuration.program_type) {
+                Some(program) => Rc::clone(program),
+                None => {
+                    return Err(RuntimeError::UnknownType(
+                        program_configuration.program_type.clone(),
+                    ))
+                }
+            };
+            if self.global_scope.get(name).is_some() {
+                return Err(RuntimeError::DuplicateDeclaration(name.clone()));
+            }
+            let task = match &program_configuration.task {
+                Some(task_name) => match self.tasks[first..]
+                    .iter()
+                    .position(|task| task.name.eq_ignore_ascii_case(task_name))
+                {
+                    Some(index) => Some(first + index),
+                    None => return Err(RuntimeError::UnknownTask(task_name.clone())),
+                },
+                None => None,
+            };
+
+            let vars = self.initial_values(&program.name, &program.declarations)?;
+            self.global_scope.insert(
+                name.clone(),
+                Object::Instance(Instance::new(program.name.clone(), vars)),
+            );
+            let instance = ProgramInstance {
+                name: name.clone(),
+                program,
+                instance: true,
+            };
+            match task {
+                Some(index) => self.tasks[index].programs.push(instance),
+                None => cyclic.programs.push(instance),
+            }
+        }
+        if !cyclic.programs.is_empty() {
+            self.tasks.push(cyclic);
+        }
+        Ok(())
+    }
+
+    /// The task declared by `task_decl`, due at once.
+    fn task(&mut self, task_decl: &TaskDecl) -> Result<Task, RuntimeError> {
+        let mut task = Task::new(
+            task_decl.name.clone(),
+            0,
+            self.clock.now(),
+            task_decl.span(),
+        );
+        for argument in &task_decl.arguments {
+            let parameter = argument.name.as_deref().unwrap_or_default();
+            match parameter.to_ascii_uppercase().as_str() {
+                "INTERVAL" => {
+                    self.visit(&argument.value)?;
+                    self.span = argument.value.span();
+                    task.interval = match self.object {
+                        Object::Time(interval) if interval > 0 => {
+                            Some(Duration::from_nanos(interval as u64))
+                        }
+                        Object::Time(_) => {
+                            return Err(RuntimeError::OutOfRange {
+                                value: self.object.to_string(),
+                                range: "INTERVAL".to_string(),
+                            })
+                        }
+                        _ => {
+                            return Err(RuntimeError::UnexpectedType {
+                                context: "task INTERVAL".to_string(),
+                                expected: "TIME".to_string(),
+                                found: self.object.data_type(),
+                            })
+                        }
+                    };
+                }
+                "PRIORITY" => {
+                    task.priority = self.integer_value(&argument.value, "task PRIORITY")?;
+                }
+                _ => {
+                    self.span = task_decl.span();
+                    return Err(RuntimeError::UnknownParameter {
+                        pou: task_decl.name.clone(),
+                        parameter: parameter.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(task)
+    }
+
+    /// Runs the body of a program once. An instance runs with its own
+    /// variables as scope, which are stored back afterwards.
+    fn run_program(&mut self, instance: &ProgramInstance) -> Result<(), RuntimeError> {
+        trace!("Running program {}", instance.name);
+        if !instance.instance {
+            return self.run_body(&instance.program);
+        }
+        let vars = match self.global_scope.get_mut(&instance.name) {
+            Some(Object::Instance(program)) => std::mem::take(&mut program.vars),
+            _ => unreachable!(),
+        };
+        self.call_stack.push(Frame {
+            function: instance.program.name.clone(),
+            scope: vars,
+        });
+        let result = self.run_body(&instance.program);
+        let frame = self.call_stack.pop().unwrap();
+        if let Some(Object::Instance(program)) = self.global_scope.get_mut(&instance.name) {
+            program.vars = frame.scope;
+        }
+        result
+    }
+
+    /// Runs the body of `program` in the current scope, between copying its
+    /// located inputs from the process image and its located outputs to it.
+    /// Located memory variables are copied both ways.
+    fn run_body(&mut self, program: &Program) -> Result<(), RuntimeError> {
+        let located = program.located();
+        for var_decl in &located {
+            let address = var_decl.address.unwrap();
+            if address.area != Area::Output {
+                self.span = var_decl.span();
+                let id = &var_decl.variable.id;
+                let value = self.image.get(&address, &self.scope()[id].data_type());
+                self.scope_mut().get_mut(id).unwrap().store(value)?;
+            }
+        }
+        self.visit(&program.body)?;
+        self.control_flow = ControlFlow::Normal;
+        for var_decl in &located {
+            let address = var_decl.address.unwrap();
+            if address.area != Area::Input {
+                self.span = var_decl.span();
+                let value = self.scope()[&var_decl.variable.id].operand();
+                self.image.set(&address, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Registers the functions, function blocks and types of
+    /// `compilation_unit` and resolves the labels of its CASE statements.
+    fn declare(&mut self, compilation_unit: &CompilationUnit) -> Result<(), RuntimeError> {
+        for pou in &compilation_unit.pous {
+            let name = match pou {
+                Node::FunctionDecl(function_decl) => &function_decl.name,
+                Node::FunctionBlockDecl(function_block_decl) => &function_block_decl.name,
+                Node::TypeDecl(type_decl) => &type_decl.name,
+                _ => continue,
+            };
+            if self.functions.get(name).is_some()
+                || self
+                    .standard_functions
+                    .contains_key(&name.to_ascii_uppercase())
+                || self.function_blocks.get(name).is_some()
+                || self.types.get(name).is_some()
+                || DataType::from_name(name).is_some()
+            {
+                self.span = pou.span();
+                return Err(RuntimeError::DuplicateDeclaration(name.clone()));
+            }
+            match pou {
+                Node::FunctionDecl(function_decl) => {
+                    self.functions
+                        .insert(name.clone(), Rc::new(function_decl.clone()));
+                }
+                Node::FunctionBlockDecl(function_block_decl) => {
+                    self.function_blocks
+                        .insert(name.clone(), Rc::new(function_block_decl.clone()));
+                }
+                Node::TypeDecl(type_decl) => {
+                    if let TypeDefinition::Enum(values, base) = &type_decl.definition {
+                        self.span = pou.span();
+                        Interpreter::check_enum(values, base)?;
+                    }
+                    self.types.insert(name.clone(), Rc::new(type_decl.clone()));
+                }
+                _ => unreachable!(),
+            }
+        }
+        let mut resolver = CaseResolver {
+            interpreter: self,
+            enum_variables: Identifiers::new(),
+        };
+        for pou in &compilation_unit.pous {
+            resolver.visit(pou)?;
+        }
+        Ok(())
+    }
+
+    /// The CASE selector or label `node`, an integer or enumerated value,
+    /// as an integer together with its type.
+    fn case_value(&mut self, node: &Node) -> Result<(i128, DataType), RuntimeError> {
+        self.visit(node)?;
+        self.span = node.span();
+        let data_type = self.object.data_type();
+        match &self.object {
+            Object::Enum(value) => Ok((value.value, data_type)),
+            object if data_type.is_integer() => Ok((object.as_i128().unwrap(), data_type)),
+            _ => Err(RuntimeError::UnexpectedType {
+                context: "CASE selector".to_string(),
+                expected: "an integer or enumerated value".to_string(),
+                found: data_type,
+            }),
+        }
+    }
+
+    /// The value of a CASE label and its type. A name such as `Idle` is a
+    /// value of `selector_enum`, the enumerated type of the selector, if it
+    /// has one.
+    fn case_label(
+        &mut self,
+        node: &Node,
+        selector_enum: Option<&str>,
+    ) -> Result<(i128, DataType), RuntimeError> {
+        if let (Node::Variable(variable), Some(type_name)) = (node, selector_enum) {
+            if let Ok(Object::Enum(value)) = self.enum_value(type_name, &variable.id) {
+                self.span = node.span();
+                let integer = value.value;
+                self.object = Object::Enum(value);
+                return Ok((integer, DataType::Enum(type_name.into())));
+            }
+        }
+        self.case_value(node)
+    }
+
+    /// Resolves the labels of `case_statement` for `visit_case_statement`.
+    /// Labels must have the same type if one is an enumerated value, and a
+    /// value may only be selected by one label.
+    fn resolve_case(
+        &mut self,
+        case_statement: &CaseStatement,
+        selector_enum: Option<&str>,
+    ) -> Result<(), RuntimeError> {
+        let mut labels = CaseLabels {
+            data_type: selector_enum.map(|type_name| DataType::Enum(type_name.into())),
+            span: Span::default(),
+            ranges: Vec::new(),
+        };
+        for (index, element) in case_statement.elements.iter().enumerate() {
+            for label in &element.labels {
+                let ((low, low_type), (high, high_type), span) = match label {
+                    CaseLabel::Value(value) => {
+                        let resolved = self.case_label(value, selector_enum)?;
+                        (resolved.clone(), resolved, value.span())
+                    }
+                    CaseLabel::Range(low, high) => (
+                        self.case_label(low, selector_enum)?,
+                        self.case_label(high, selector_enum)?,
+                        low.span().to(high.span()),
+                    ),
+                };
+                self.span = span;
+                for label_type in [low_type, high_type] {
+                    match &labels.data_type {
+                        Some(data_type) if !case_compatible(data_type, &label_type) => {
+                            return Err(RuntimeError::IncompatibleOperands {
+                                operation: "CASE".to_string(),
+                                left: data_type.clone(),
+                                right: label_type,
+                            });
+                        }
+                        Some(_) => {}
+                        None => labels.data_type = Some(label_type),
+                    }
+                }
+                if low > high {
+                    return Err(RuntimeError::EmptyCaseRange(low, high));
+                }
+                if let Some(&(other_low, _, _)) = labels
+                    .ranges
+                    .iter()
+                    .find(|(other_low, other_high, _)| low <= *other_high && *other_low <= high)
+                {
+                    let value = match label {
+                        CaseLabel::Value(_) => self.object.to_string(),
+                        CaseLabel::Range(..) => low.max(other_low).to_string(),
+                    };
+                    return Err(RuntimeError::DuplicateCaseLabel(value));
+                }
+                if labels.ranges.is_empty() {
+                    labels.span = span;
+                }
+                labels.ranges.push((low, high, index));
+            }
+        }
+        self.case_labels
+            .insert(case_statement.span().start, Rc::new(labels));
+        Ok(())
+    }
+
+    fn integer_value(&mut self, node: &Node, context: &str) -> Result<i128, RuntimeError> {
+        self.visit(node)?;
+        self.span = node.span();
+        match self.object.as_i128() {
+            Some(value) if self.object.data_type().is_integer() => Ok(value),
+            _ => Err(RuntimeError::UnexpectedType {
+                context: context.to_string(),
+                expected: "an integer".to_string(),
+                found: self.object.data_type(),
+            }),
+        }
+    }
+
+    fn condition(&mut self, node: &Node, context: &str) -> Result<bool, RuntimeError> {
+        self.visit(node)?;
+        self.span = node.span();
+        match self.object {
+            Object::Bool(value) => Ok(value),
+            _ => Err(RuntimeError::UnexpectedType {
+                context: context.to_string(),
+                expected: "BOOL".to_string(),
+                found: self.object.data_type(),
+            }),
+        }
+    }
+
+    /// Consumes a pending EXIT or CONTINUE after a loop body, returns true if
+    /// the loop should terminate. A pending RETURN also terminates the loop
+    /// but is left for the enclosing POU.
+    fn leave_loop(&mut self) -> bool {
+        match self.control_flow {
+            ControlFlow::Return => true,
+            control_flow => {
+                self.control_flow = ControlFlow::Normal;
+                control_flow == ControlFlow::Exit
+            }
+        }
+    }
+
+    /// The variables visible to the code being executed: the innermost
+    /// function's locals, or the program variables outside of any call.
+    fn scope(&self) -> &Identifiers<Object> {
+        match self.call_stack.last() {
+            Some(frame) => &frame.scope,
+            None => &self.global_scope,
+        }
+    }
+
+    fn scope_mut(&mut self) -> &mut Identifiers<Object> {
+        match self.call_stack.last_mut() {
+            Some(frame) => &mut frame.scope,
+            None => &mut self.global_scope,
+        }
+    }
+
+    /// Evaluates the arguments of `call` in the caller's scope and pairs them
+    /// with the `inputs` of the called POU.
+    fn bind_arguments(
+        &mut self,
+        inputs: &[&VarDecl],
+        call: &Call,
+    ) -> Result<Vec<(String, Object)>, RuntimeError> {
+        let formal = call
+            .arguments
+            .iter()
+            .any(|argument| argument.name.is_some());
+        if !formal && call.arguments.len() != inputs.len() {
+            return Err(RuntimeError::WrongArgumentCount {
+                pou: call.name.clone(),
+                expected: inputs.len(),
+                found: call.arguments.len(),
+            });
+        }
+
+        let mut bindings: Vec<(String, Object)> = Vec::new();
+        for (position, argument) in call.arguments.iter().enumerate() {
+            let parameter = match &argument.name {
+                Some(name) => match inputs
+                    .iter()
+                    .find(|input| input.variable.id.eq_ignore_ascii_case(name))
+                {
+                    Some(input) => &input.variable.id,
+                    None => {
+                        return Err(RuntimeError::UnknownParameter {
+                            pou: call.name.clone(),
+                            parameter: name.clone(),
+                        })
+                    }
+                },
+                None => &inputs[position].variable.id,
+            };
+            if bindings.iter().any(|(id, _)| id == parameter) {
+                return Err(RuntimeError::DuplicateArgument {
+                    pou: call.name.clone(),
+                    parameter: parameter.clone(),
+                });
+            }
+            self.visit(&argument.value)?;
+            bindings.push((parameter.clone(), self.object.clone()));
+        }
+        Ok(bindings)
+    }
+
+    /// Evaluates the arguments of a call to a standard function in the order
+    /// of its inputs. Unlike user functions, every input must be assigned.
+    fn standard_arguments(
+        &mut self,
+        function: &StandardFunction,
+        call: &Call,
+    ) -> Result<Vec<Object>, RuntimeError> {
+        let formal = call
+            .arguments
+            .iter()
+            .any(|argument| argument.name.is_some());
+        let count = call.arguments.len();
+        let expected = function.input_count();
+        if !formal && (count < expected || (count > expected && !function.is_extensible())) {
+            return Err(RuntimeError::WrongArgumentCount {
+                pou: call.name.clone(),
+                expected,
+                found: count,
+            });
+        }
+
+        let mut arguments: Vec<Option<Object>> = vec![None; expected];
+        for (position, argument) in call.arguments.iter().enumerate() {
+            let index = match &argument.name {
+                Some(name) => match function.input_index(name) {
+                    Some(index) => index,
+                    None => {
+                        return Err(RuntimeError::UnknownParameter {
+                            pou: call.name.clone(),
+                            parameter: name.clone(),
+                        })
+                    }
+                },
+                None => position,
+            };
+            if index >= arguments.len() {
+                arguments.resize(index + 1, None);
+            }
+            if arguments[index].is_some() {
+                return Err(RuntimeError::DuplicateArgument {
+                    pou: call.name.clone(),
+                    parameter: function.input_name(index),
+                });
+            }
+            self.visit(&argument.value)?;
+            arguments[index] = Some(self.object.clone());
+        }
+        arguments
+            .into_iter()
+            .enumerate()
+            .map(|(index, argument)| {
+                argument.ok_or_else(|| RuntimeError::MissingArgument {
+                    pou: call.name.clone(),
+                    parameter: function.input_name(index),
+                })
+            })
+            .collect()
+    }
+
+    /// Runs the body of `function` in the frame on top of the call stack and
+    /// leaves its return value in `self.object`.
+    fn call_function(
+        &mut self,
+        function: &FunctionDecl,
+        bindings: Vec<(String, Object)>,
+    ) -> Result<(), RuntimeError> {
+        let return_value = self.default_value(&function.return_type)?;
+        self.scope_mut().insert(function.name.clone(), return_value);
+        for declaration in &function.declarations {
+            self.visit(declaration)?;
+        }
+        for (id, value) in bindings {
+            self.scope_mut().get_mut(&id).unwrap().store(value)?;
+        }
+
+        self.visit(&function.body)?;
+        self.control_flow = ControlFlow::Normal;
+        self.object = self.scope()[&function.name].operand();
+        Ok(())
+    }
+
+    /// The initial value of a variable of type `var_type`, a new instance
+    /// for a function block type.
+    fn default_value(&mut self, var_type: &Type) -> Result<Object, RuntimeError> {
+        let mut value = match var_type.data_type() {
+            Some(data_type) => data_type.default_value(),
+            None => match self.function_blocks.get(&var_type.name) {
+                Some(function_block) => self.instantiate(&Rc::clone(function_block))?,
+                None => match self.types.get(&var_type.name) {
+                    Some(type_decl) => self.type_value(&Rc::clone(type_decl))?,
+                    None => return Err(RuntimeError::UnknownType(var_type.name.clone())),
+                },
+            },
+        };
+        if let Some((lower, upper)) = var_type.range {
+            value = Interpreter::subrange(value, lower, upper)?;
+        }
+        if var_type.dimensions.is_empty() {
+            Ok(value)
+        } else {
+            Ok(Object::Array(Array::new(
+                value,
+                var_type.dimensions.clone(),
+            )))
+        }
+    }
+
+    /// A subrange variable with the base type of `value`, which must be an
+    /// integer type containing the bounds. It starts at the lower bound.
+    fn subrange(value: Object, lower: i128, upper: i128) -> Result<Object, RuntimeError> {
+        let data_type = value.data_type();
+        if !data_type.is_integer() {
+            return Err(RuntimeError::UnexpectedType {
+                context: "subrange base type".to_string(),
+                expected: "ANY_INT".to_string(),
+                found: data_type,
+            });
+        }
+        let (min, max) = data_type.range();
+        for bound in [lower, upper] {
+            if bound < min || bound > max {
+                return Err(RuntimeError::OutOfRange {
+                    value: bound.to_string(),
+                    range: data_type.to_string(),
+                });
+            }
+        }
+        Ok(Object::Subrange(Subrange::new(
+            Object::from_i128(lower, data_type),
+            lower,
+            upper,
+        )))
+    }
+
+    /// The value `name` of the enumerated type `type_name`.
+    fn enum_value(&self, type_name: &str, name: &str) -> Result<Object, RuntimeError> {
+        let (type_name, values) = match self.types.get(type_name) {
+            Some(type_decl) => match &type_decl.definition {
+                TypeDefinition::Enum(values, _) => (&type_decl.name, values),
+                _ => return Err(RuntimeError::UnknownType(type_name.to_string())),
+            },
+            None => return Err(RuntimeError::UnknownType(type_name.to_string())),
+        };
+        match values
+            .iter()
+            .find(|(value, _)| value.eq_ignore_ascii_case(name))
+        {
+            Some((name, value)) => Ok(Object::Enum(Enumerated::new(
+                type_name.clone(),
+                name.clone(),
+                *value,
+            ))),
+            None => Err(RuntimeError::NoSuchValue {
+                data_type: DataType::Enum(type_name.as_str().into()),
+                value: name.to_string(),
+            }),
+        }
+    }
+
+    /// Checks that the values of an enumerated type are distinct and fit its
+    /// integer base type.
+    fn check_enum(values: &[(String, i128)], base: &Type) -> Result<(), RuntimeError> {
+        let data_type = match base.data_type() {
+            Some(data_type) if data_type.is_integer() => data_type,
+            Some(data_type) => {
+                return Err(RuntimeError::UnexpectedType {
+                    context: "enumerated base type".to_string(),
+                    expected: "ANY_INT".to_string(),
+                    found: data_type,
+                })
+            }
+            None => return Err(RuntimeError::UnknownType(base.name.clone())),
+        };
+        let (min, max) = data_type.range();
+        for (index, (name, value)) in values.iter().enumerate() {
+            if values[..index]
+                .iter()
+                .any(|(other, _)| other.eq_ignore_ascii_case(name))
+            {
+                return Err(RuntimeError::DuplicateDeclaration(name.clone()));
+            }
+            if *value < min || *value > max {
+                return Err(RuntimeError::OutOfRange {
+                    value: value.to_string(),
+                    range: data_type.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates the subscripts of `index`, which must be integers.
+    fn indices(&mut self, index: &Index) -> Result<Vec<i128>, RuntimeError> {
+        let mut indices = Vec::new();
+        for expr in &index.indices {
+            self.visit(expr)?;
+            self.span = expr.span();
+            if !self.object.data_type().is_integer() {
+                return Err(RuntimeError::UnexpectedType {
+                    context: "array index".to_string(),
+                    expected: "ANY_INT".to_string(),
+                    found: self.object.data_type(),
+                });
+            }
+            indices.push(self.object.as_i128().unwrap());
+        }
+        Ok(indices)
+    }
+
+    /// Creates an instance of `function_block` with its variables at their
+    /// initial values.
+    fn instantiate(&mut self, function_block: &FunctionBlockDecl) -> Result<Object, RuntimeError> {
+        if self
+            .call_stack
+            .iter()
+            .any(|frame| frame.function == function_block.name)
+        {
+            return Err(RuntimeError::RecursiveCall(function_block.name.clone()));
+        }
+        let vars = self.initial_values(&function_block.name, &function_block.declarations)?;
+        Ok(Object::Instance(Instance::new(
+            function_block.name.clone(),
+            vars,
+        )))
+    }
+
+    /// The initial value of a variable of the user-defined type `type_decl`.
+    fn type_value(&mut self, type_decl: &TypeDecl) -> Result<Object, RuntimeError> {
+        if self
+            .call_stack
+            .iter()
+            .any(|frame| frame.function == type_decl.name)
+        {
+            return Err(RuntimeError::RecursiveType(type_decl.name.clone()));
+        }
+        match &type_decl.definition {
+            TypeDefinition::Struct(members) => {
+                let members = self.initial_values(&type_decl.name, members)?;
+                Ok(Object::Struct(Struct::new(type_decl.name.clone(), members)))
+            }
+            TypeDefinition::Enum(values, _) => {
+                let (name, value) = &values[0];
+                Ok(Object::Enum(Enumerated::new(
+                    type_decl.name.clone(),
+                    name.clone(),
+                    *value,
+                )))
+            }
+            TypeDefinition::Derived(base) => {
+                self.call_stack.push(Frame::new(type_decl.name.clone()));
+                let result = self.default_value(base);
+                self.call_stack.pop();
+                result
+            }
+        }
+    }
+
+    /// The variables of `declarations` at their initial values, declared in
+    /// a frame of their own named `name`.
+    fn initial_values(
+        &mut self,
+        name: &str,
+        declarations: &[Node],
+    ) -> Result<Identifiers<Object>, RuntimeError> {
+        self.call_stack.push(Frame::new(name.to_string()));
+        let result = declarations
+            .iter()
+            .try_for_each(|declaration| self.visit(declaration));
+        let frame = self.call_stack.pop().unwrap();
+        result?;
+        Ok(frame.scope)
+    }
+
+    /// Runs the body of the function block instance that `path` selects
+    /// from the variable `id` with the instance's variables as scope, and
+    /// stores them back afterwards so they persist until the next call.
+    fn call_instance(
+        &mut self,
+        call: &Call,
+        id: &str,
+        path: &[Selector],
+    ) -> Result<(), RuntimeError> {
+        let type_name = match self.select_mut(id, path, false)? {
+            Object::Instance(instance) => instance.type_name.clone(),
+            object => {
+                return Err(RuntimeError::InvalidOperand {
+                    operation: "Calling".to_string(),
+                    data_type: object.data_type(),
+                })
+            }
+        };
+        let function_block = Rc::clone(&self.function_blocks[&type_name]);
+        if self
+            .call_stack
+            .iter()
+            .any(|frame| frame.function == type_name)
+        {
+            return Err(RuntimeError::RecursiveCall(type_name));
+        }
+        // Inputs not passed keep their value from the previous call.
+        let bindings = if call.arguments.is_empty() {
+            Vec::new()
+        } else {
+            self.bind_arguments(&function_block.inputs(), call)?
+        };
+
+        let vars = match self.select_mut(id, path, false)? {
+            Object::Instance(instance) => std::mem::take(&mut instance.vars),
+            _ => unreachable!(),
+        };
+        self.call_stack.push(Frame {
+            function: type_name.clone(),
+            scope: vars,
+        });
+        let result = self.run_instance(&function_block, bindings);
+        let frame = self.call_stack.pop().unwrap();
+        match self.select_mut(id, path, false)? {
+            Object::Instance(instance) => instance.vars = frame.scope,
+            _ => unreachable!(),
+        }
+        result
+    }
+
+    /// The instance `call` invokes, `None` for a call of a function.
+    fn instance_call<'a>(
+        &mut self,
+        call: &'a Call,
+    ) -> Result<Option<(&'a str, Vec<Selector<'a>>)>, RuntimeError> {
+        self.span = call.span();
+        if let Some(instance) = &call.instance {
+            let selected = self.selectors(instance)?;
+            self.span = call.span();
+            return Ok(Some(selected));
+        }
+        match self.scope().get(&call.name) {
+            Some(Object::Instance(_)) => Ok(Some((&call.name, Vec::new()))),
+            _ => Ok(None),
+        }
+    }
+
+    fn run_instance(
+        &mut self,
+        function_block: &FunctionBlockDecl,
+        bindings: Vec<(String, Object)>,
+    ) -> Result<(), RuntimeError> {
+        for (id, value) in bindings {
+            self.scope_mut().get_mut(&id).unwrap().store(value)?;
+        }
+        match self.standard_bodies.get(&function_block.name) {
+            Some(body) => {
+                let now = self.clock.now().as_nanos() as i64;
+                body(self.scope_mut(), now);
+            }
+            None => {
+                self.visit(&function_block.body)?;
+                self.control_flow = ControlFlow::Normal;
+            }
         }
+        Ok(())
+    }
+
+    /// Checks that `member` of `object` may be read, or written if `write`
+    /// is set, from outside a function block instance. Every member of a
+    /// STRUCT is accessible.
+    fn check_member_access(
+        function_blocks: &Identifiers<Rc<FunctionBlockDecl>>,
+        object: &Object,
+        member: &str,
+        write: bool,
+    ) -> Result<(), RuntimeError> {
+        let kind = match object {
+            Object::Struct(value) if value.members.get(member).is_some() => return Ok(()),
+            Object::Instance(instance) => function_blocks[&instance.type_name].var_kind(member),
+            _ => None,
+        };
+        let data_type = object.data_type();
+        match kind {
+            None => Err(RuntimeError::NoSuchMember {
+                data_type,
+                member: member.to_string(),
+            }),
+            Some(VarKind::Var) => Err(RuntimeError::InternalMember {
+                data_type,
+                member: member.to_string(),
+            }),
+            Some(VarKind::Output) if write => Err(RuntimeError::ReadOnlyMember {
+                data_type,
+                member: member.to_string(),
+            }),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Stores `value` in the variable, instance member or array element
+    /// `target`.
+    fn assign(&mut self, target: &Node, value: Object) -> Result<(), RuntimeError> {
+        let span = self.span;
+        let (id, path) = self.selectors(target)?;
+        self.span = span;
+        self.select_mut(id, &path, true)?.store(value)
+    }
+
+    /// Splits `target`, a variable or a member or element of one, into the
+    /// variable's name and the selectors from the variable to `target`,
+    /// evaluating the indices.
+    fn selectors<'a>(
+        &mut self,
+        target: &'a Node,
+    ) -> Result<(&'a str, Vec<Selector<'a>>), RuntimeError> {
+        let mut path = Vec::new();
+        let mut node = target;
+        loop {
+            match node {
+                Node::Member(member) => {
+                    path.push(Selector::Member(member.member.as_str()));
+                    node = &member.base;
+                }
+                Node::Index(index) => {
+                    path.push(Selector::Index(self.indices(index)?, index.span()));
+                    node = &index.base;
+                }
+                _ => break,
+            }
+        }
+        let id = match node {
+            Node::Variable(variable) => &variable.id,
+            _ => panic!("Incorrect node in selectors"),
+        };
+        path.reverse();
+        Ok((id, path))
+    }
+
+    /// The part of the variable `id` that `path` selects. Members must be
+    /// accessible for writing if `write` is set, for reading otherwise.
+    fn select_mut(
+        &mut self,
+        id: &str,
+        path: &[Selector],
+        write: bool,
+    ) -> Result<&mut Object, RuntimeError> {
+        let span = self.span;
+        let scope = match self.call_stack.last_mut() {
+            Some(frame) => &mut frame.scope,
+            None => &mut self.global_scope,
+        };
+        let mut variable = match scope.get_mut(id) {
+            Some(variable) => variable,
+            None => return Err(RuntimeError::UndeclaredVariable(id.to_string())),
+        };
+        for selector in path {
+            variable = match selector {
+                Selector::Member(member) => {
+                    Interpreter::check_member_access(
+                        &self.function_blocks,
+                        variable,
+                        member,
+                        write,
+                    )?;
+                    match variable {
+                        Object::Struct(value) => value.members.get_mut(member).unwrap(),
+                        Object::Instance(instance) => instance.vars.get_mut(member).unwrap(),
+                        _ => unreachable!(),
+                    }
+                }
+                Selector::Index(indices, index_span) => match variable {
+                    Object::Array(array) => {
+                        self.span = *index_span;
+                        let offset = array.offset(indices)?;
+                        self.span = span;
+                        &mut array.elements[offset]
+                    }
+                    _ => {
+                        self.span = *index_span;
+                        return Err(RuntimeError::InvalidOperand {
+                            operation: "Indexing".to_string(),
+                            data_type: variable.data_type(),
+                        });
+                    }
+                },
+            };
+        }
+        Ok(variable)
     }
 }
 
+/// Whether a CASE selector or label of type `left` may be compared with
+/// one of type `right`: any integers, or values of the same enumerated type.
+fn case_compatible(left: &DataType, right: &DataType) -> bool {
+    let is_enum = |data_type: &DataType| matches!(data_type, DataType::Enum(_));
+    left == right || !(is_enum(left) || is_enum(right))
+}
+
 impl Visitor for Interpreter {
-    fn visit_unary_op(&mut self, unary_op: &UnaryOp) {
+    type Error = RuntimeError;
+
+    fn visit_compilation_unit(
+        &mut self,
+        compilation_unit: &CompilationUnit,
+    ) -> Result<(), RuntimeError> {
+        trace!("Visiting compilation unit");
+        self.declare(compilation_unit)?;
+        walk_compilation_unit(self, compilation_unit)
+    }
+
+    fn visit_program(&mut self, program: &Program) -> Result<(), RuntimeError> {
+        trace!("Visiting program {}", program.name);
+        for declaration in &program.declarations {
+            self.visit(declaration)?;
+        }
+        self.run_body(program)
+    }
+
+    /// Functions only run when called, see `visit_call`.
+    fn visit_function_decl(&mut self, function_decl: &FunctionDecl) -> Result<(), RuntimeError> {
+        trace!("Skipping function {}", function_decl.name);
+        Ok(())
+    }
+
+    /// Configurations only take effect in cyclic execution, see `load`.
+    fn visit_configuration(&mut self, configuration: &Configuration) -> Result<(), RuntimeError> {
+        trace!("Skipping configuration {}", configuration.name);
+        Ok(())
+    }
+
+    /// Types are only used when a variable is declared, see `default_value`.
+    fn visit_type_decl(&mut self, type_decl: &TypeDecl) -> Result<(), RuntimeError> {
+        trace!("Skipping type {}", type_decl.name);
+        Ok(())
+    }
+
+    /// Function blocks only run when an instance is called, see `visit_call`.
+    fn visit_function_block_decl(
+        &mut self,
+        function_block_decl: &FunctionBlockDecl,
+    ) -> Result<(), RuntimeError> {
+        trace!("Skipping function block {}", function_block_decl.name);
+        Ok(())
+    }
+
+    fn visit_member(&mut self, member: &Member) -> Result<(), RuntimeError> {
+        trace!("Visiting member {}", member.member);
+        // Resolved in place like an index, so that only the member is
+        // copied and not the whole struct or instance.
+        let (id, mut path) = self.selectors(&member.base)?;
+        path.push(Selector::Member(member.member.as_str()));
+        self.span = member.span();
+        self.object = self.select_mut(id, &path, false)?.operand();
+        Ok(())
+    }
+
+    fn visit_index(&mut self, index: &Index) -> Result<(), RuntimeError> {
+        trace!("Visiting index");
+        // Resolved in place like an assignment target, so that only the
+        // element is copied and not the whole array.
+        let (id, mut path) = self.selectors(&index.base)?;
+        path.push(Selector::Index(self.indices(index)?, index.span()));
+        self.span = index.span();
+        self.object = self.select_mut(id, &path, false)?.operand();
+        Ok(())
+    }
+
+    fn visit_call(&mut self, call: &Call) -> Result<(), RuntimeError> {
+        trace!("Visiting call to {}", call.name);
+        self.span = call.span();
+        if let Some((id, path)) = self.instance_call(call)? {
+            self.call_instance(call, id, &path)?;
+            // As an operand the call is the instance itself.
+            self.object = self.select_mut(id, &path, false)?.operand();
+            return Ok(());
+        }
+        let name = call.name.to_ascii_uppercase();
+        let function = match self.functions.get(&call.name) {
+            Some(function) => Rc::clone(function),
+            None => match self.standard_functions.get(&name) {
+                Some(function) => {
+                    let function = *function;
+                    let arguments = self.standard_arguments(function, call)?;
+                    self.span = call.span();
+                    self.object = (function.body)(&name, &arguments)?;
+                    return Ok(());
+                }
+                None => return Err(RuntimeError::UnknownFunction(call.name.clone())),
+            },
+        };
+        if self
+            .call_stack
+            .iter()
+            .any(|frame| frame.function == function.name)
+        {
+            return Err(RuntimeError::RecursiveCall(function.name.clone()));
+        }
+
+        let bindings = self.bind_arguments(&function.inputs(), call)?;
+        self.call_stack.push(Frame::new(function.name.clone()));
+        let result = self.call_function(&function, bindings);
+        self.call_stack.pop();
+        result
+    }
+
+    fn visit_var_decl(&mut self, var_decl: &VarDecl) -> Result<(), RuntimeError> {
+        trace!("Visiting var decl");
+        self.span = var_decl.span();
+        let id = &var_decl.variable.id;
+        if self.scope().get(id).is_some() {
+            return Err(RuntimeError::DuplicateDeclaration(id.clone()));
+        }
+
+        let mut value = self.default_value(&var_decl.var_type)?;
+        if let Some(address) = &var_decl.address {
+            address.check(&value.data_type())?;
+        }
+        if let Some(initial_value) = &var_decl.initial_value {
+            self.visit(initial_value)?;
+            self.span = var_decl.span();
+            value.store(self.object.clone())?;
+        }
+        self.scope_mut().insert(id.clone(), value);
+        Ok(())
+    }
+
+    fn visit_unary_op(&mut self, unary_op: &UnaryOp) -> Result<(), RuntimeError> {
         trace!("Visiting unary op");
-        self.visit(&unary_op.expr);
+        self.visit(&unary_op.expr)?;
+        self.span = unary_op.span();
         match unary_op.op {
-            Token::Plus => {}
-            Token::Minus => self.object *= -1,
+            Token::Plus => {
+                if !self.object.data_type().is_numeric() {
+                    return Err(RuntimeError::InvalidOperand {
+                        operation: "Unary plus".to_string(),
+                        data_type: self.object.data_type(),
+                    });
+                }
+            }
+            Token::Minus => self.object = self.object.clone().neg()?,
+            Token::Not => self.object = self.object.clone().not()?,
             _ => panic!("Incorrect token in visit_unary_op"),
         }
+        Ok(())
     }
-    fn visit_binary_op(&mut self, binary_op: &BinaryOp) {
+
+    fn visit_binary_op(&mut self, binary_op: &BinaryOp) -> Result<(), RuntimeError> {
         trace!("Visiting binary op");
-        let lhs: i32;
-        let rhs: i32;
-        self.visit(&binary_op.left);
-        lhs = self.object;
-        self.visit(&binary_op.right);
-        rhs = self.object;
-
-        match binary_op.op {
-            Token::Plus => self.object = lhs + rhs,
-            Token::Minus => self.object = lhs - rhs,
-            Token::Mul => self.object = lhs * rhs,
-            Token::Div => self.object = lhs / rhs,
+        self.visit(&binary_op.left)?;
+        let lhs = self.object.clone();
+        self.visit(&binary_op.right)?;
+        let rhs = self.object.clone();
+        self.span = binary_op.span();
+
+        self.object = match binary_op.op {
+            Token::Plus => lhs.add(rhs)?,
+            Token::Minus => lhs.sub(rhs)?,
+            Token::Mul => lhs.mul(rhs)?,
+            Token::Div => lhs.div(rhs)?,
+            Token::Mod => lhs.modulo(rhs)?,
+            Token::Power => lhs.pow(rhs)?,
+            Token::And => lhs.and(rhs)?,
+            Token::Or => lhs.or(rhs)?,
+            Token::Xor => lhs.xor(rhs)?,
+            Token::Equal => Object::Bool(lhs.compare(rhs)? == Some(Ordering::Equal)),
+            Token::NotEqual => Object::Bool(lhs.compare(rhs)? != Some(Ordering::Equal)),
+            Token::Less => Object::Bool(lhs.compare(rhs)? == Some(Ordering::Less)),
+            Token::Greater => Object::Bool(lhs.compare(rhs)? == Some(Ordering::Greater)),
+            Token::LessEqual => {
+                let ordering = lhs.compare(rhs)?;
+                Object::Bool(ordering.is_some() && ordering != Some(Ordering::Greater))
+            }
+            Token::GreaterEqual => {
+                let ordering = lhs.compare(rhs)?;
+                Object::Bool(ordering.is_some() && ordering != Some(Ordering::Less))
+            }
             _ => panic!("Incorrect token in visit_binary_op"),
-        }
+        };
+        Ok(())
     }
 
-    fn visit_num(&mut self, num: &Num) {
+    fn visit_num(&mut self, num: &Num) -> Result<(), RuntimeError> {
         trace!("Visiting num");
-        self.object = num.value;
+        self.object = num.value.clone();
+        Ok(())
     }
 
-    fn visit_assignment(&mut self, assignment: &Assignment) {
+    fn visit_assignment(&mut self, assignment: &Assignment) -> Result<(), RuntimeError> {
         trace!("Visiting assignment");
-        self.visit(&assignment.right);
-        match &*assignment.left {
-            Node::Variable(variable) => {
-                trace!("Variable {:?}, inserted in global scope", variable);
-                self.global_scope.insert(variable.id.clone(), self.object);
+        self.visit(&assignment.right)?;
+        self.span = assignment.span();
+        self.assign(&assignment.left, self.object.clone())
+    }
+
+    fn visit_if_statement(&mut self, if_statement: &IfStatement) -> Result<(), RuntimeError> {
+        trace!("Visiting if statement");
+        if self.condition(&if_statement.condition, "IF condition")? {
+            self.visit(&if_statement.then_branch)?;
+        } else if let Some(else_branch) = &if_statement.else_branch {
+            self.visit(else_branch)?;
+        }
+        Ok(())
+    }
+
+    fn visit_case_statement(&mut self, case_statement: &CaseStatement) -> Result<(), RuntimeError> {
+        trace!("Visiting case statement");
+        let (selector, selector_type) = self.case_value(&case_statement.selector)?;
+
+        let labels = Rc::clone(&self.case_labels[&case_statement.span().start]);
+        if let Some(data_type) = &labels.data_type {
+            if !case_compatible(data_type, &selector_type) {
+                self.span = labels.span;
+                return Err(RuntimeError::IncompatibleOperands {
+                    operation: "CASE".to_string(),
+                    left: selector_type,
+                    right: data_type.clone(),
+                });
+            }
+        }
+        match labels
+            .ranges
+            .iter()
+            .find(|(low, high, _)| *low <= selector && selector <= *high)
+        {
+            Some(&(_, _, index)) => self.visit(&case_statement.elements[index].body),
+            None => match &case_statement.else_branch {
+                Some(else_branch) => self.visit(else_branch),
+                None => Ok(()),
+            },
+        }
+    }
+
+    fn visit_compound_statement(
+        &mut self,
+        compound_statement: &CompoundStatement,
+    ) -> Result<(), RuntimeError> {
+        trace!("Visiting compound statement");
+        for node in &compound_statement.statements {
+            match node {
+                // The value of a call statement is discarded, so an instance
+                // is not copied after it runs.
+                Node::Call(call) => match self.instance_call(call)? {
+                    Some((id, path)) => self.call_instance(call, id, &path)?,
+                    None => self.visit_call(call)?,
+                },
+                _ => self.visit(node)?,
+            }
+            if self.control_flow != ControlFlow::Normal {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ForStatement) -> Result<(), RuntimeError> {
+        trace!("Visiting for statement");
+        self.span = for_statement.span();
+        let id = &for_statement.control_variable.id;
+        let data_type = match self.scope().get(id) {
+            Some(value) if value.data_type().is_integer() => value.data_type(),
+            Some(value) => {
+                return Err(RuntimeError::UnexpectedType {
+                    context: format!("FOR control variable {}", id),
+                    expected: "an integer".to_string(),
+                    found: value.data_type(),
+                })
+            }
+            None => return Err(RuntimeError::UndeclaredVariable(id.clone())),
+        };
+
+        let mut counter = self.integer_value(&for_statement.start, "FOR start value")?;
+        let end = self.integer_value(&for_statement.end, "FOR end value")?;
+        let step = match &for_statement.step {
+            Some(step) => self.integer_value(step, "FOR step")?,
+            None => 1,
+        };
+        if step == 0 {
+            return Err(RuntimeError::ZeroStep);
+        }
+
+        let (min, max) = data_type.range();
+        let fits = |counter: i128| counter >= min && counter <= max;
+        while (step > 0 && counter <= end) || (step < 0 && counter >= end) {
+            if !fits(counter) {
+                return Err(RuntimeError::OutOfRange {
+                    value: counter.to_string(),
+                    range: data_type.to_string(),
+                });
             }
+            let value = Object::from_i128(counter, data_type.clone());
+            self.scope_mut().get_mut(id).unwrap().store(value.clone())?;
 
-            _ => panic!("Incorrect node in visit_assignment"),
+            self.visit(&for_statement.body)?;
+            self.span = for_statement.span();
+            if self.scope().get(id).map(Object::operand) != Some(value) {
+                return Err(RuntimeError::ControlVariableModified(id.clone()));
+            }
+            if self.leave_loop() {
+                return Ok(());
+            }
+            counter += step;
         }
+        // Past the end of its type or subrange, as after `FOR i := 120 TO 127`
+        // with a SINT, a control variable keeps its last value.
+        if fits(counter) {
+            let value = Object::from_i128(counter, data_type);
+            self.scope_mut().get_mut(id).unwrap().store(value).ok();
+        }
+        Ok(())
     }
 
-    fn visit_variable(&mut self, variable: &Variable) {
+    fn visit_while_statement(
+        &mut self,
+        while_statement: &WhileStatement,
+    ) -> Result<(), RuntimeError> {
+        trace!("Visiting while statement");
+        while self.condition(&while_statement.condition, "WHILE condition")? {
+            self.visit(&while_statement.body)?;
+            if self.leave_loop() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_repeat_statement(
+        &mut self,
+        repeat_statement: &RepeatStatement,
+    ) -> Result<(), RuntimeError> {
+        trace!("Visiting repeat statement");
+        loop {
+            self.visit(&repeat_statement.body)?;
+            if self.leave_loop() {
+                break;
+            }
+            if self.condition(&repeat_statement.condition, "REPEAT condition")? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_exit(&mut self) -> Result<(), RuntimeError> {
+        trace!("Visiting exit");
+        self.control_flow = ControlFlow::Exit;
+        Ok(())
+    }
+
+    fn visit_continue(&mut self) -> Result<(), RuntimeError> {
+        trace!("Visiting continue");
+        self.control_flow = ControlFlow::Continue;
+        Ok(())
+    }
+
+    fn visit_return(&mut self) -> Result<(), RuntimeError> {
+        trace!("Visiting return");
+        self.control_flow = ControlFlow::Return;
+        Ok(())
+    }
+
+    fn visit_variable(&mut self, variable: &Variable) -> Result<(), RuntimeError> {
         trace!("Visiting variable");
-        if let Some(value) = self.global_scope.get(&variable.id) {
-            self.object = *value;
-        } else {
-            panic!("Variable id not in scope");
+        self.span = variable.span();
+        if let Some(value) = self.scope().get(&variable.id) {
+            self.object = value.operand();
+            return Ok(());
+        }
+        // Not a variable, but possibly the value of exactly one enumerated type.
+        let mut types = self.types.values().filter(|type_decl| {
+            matches!(&type_decl.definition, TypeDefinition::Enum(values, _)
+                if values.iter().any(|(name, _)| name.eq_ignore_ascii_case(&variable.id)))
+        });
+        match (types.next(), types.next()) {
+            (Some(type_decl), None) => {
+                self.object = self.enum_value(&type_decl.name, &variable.id)?;
+                Ok(())
+            }
+            _ => Err(RuntimeError::UndeclaredVariable(variable.id.clone())),
         }
     }
+
+    fn visit_enum_value(&mut self, enum_value: &EnumValue) -> Result<(), RuntimeError> {
+        trace!("Visiting enum value {}", enum_value.name);
+        self.span = enum_value.span();
+        self.object = self.enum_value(&enum_value.type_name, &enum_value.name)?;
+        Ok(())
+    }
 }
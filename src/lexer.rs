@@ -1,10 +1,18 @@
 use log::trace;
 
-use crate::token::Token;
+use crate::calendar::{self, NANOSECONDS_PER_DAY};
+use crate::error::LexError;
+use crate::image::Address;
+use crate::object::DataType;
+use crate::token::{Span, SpannedToken, Token};
 use std::collections::HashMap;
+use std::convert::TryFrom;
 pub struct Lexer {
     text: Vec<char>,
     pos: usize,
+    offset: usize,
+    line: usize,
+    column: usize,
     current_char: Option<char>,
     reserved_keywords: HashMap<String, Token>,
 }
@@ -14,16 +22,69 @@ impl Lexer {
         let mut reserved_keywords: HashMap<String, Token> = HashMap::new();
         reserved_keywords.insert("PROGRAM".to_string(), Token::Program);
         reserved_keywords.insert("END_PROGRAM".to_string(), Token::EndProgram);
+        reserved_keywords.insert("FUNCTION".to_string(), Token::Function);
+        reserved_keywords.insert("END_FUNCTION".to_string(), Token::EndFunction);
+        reserved_keywords.insert("FUNCTION_BLOCK".to_string(), Token::FunctionBlock);
+        reserved_keywords.insert("END_FUNCTION_BLOCK".to_string(), Token::EndFunctionBlock);
+        reserved_keywords.insert("CONFIGURATION".to_string(), Token::Configuration);
+        reserved_keywords.insert("END_CONFIGURATION".to_string(), Token::EndConfiguration);
+        reserved_keywords.insert("RESOURCE".to_string(), Token::Resource);
+        reserved_keywords.insert("END_RESOURCE".to_string(), Token::EndResource);
+        reserved_keywords.insert("TASK".to_string(), Token::Task);
+        reserved_keywords.insert("WITH".to_string(), Token::With);
+        reserved_keywords.insert("ON".to_string(), Token::On);
+        reserved_keywords.insert("VAR".to_string(), Token::Var);
+        reserved_keywords.insert("VAR_INPUT".to_string(), Token::VarInput);
+        reserved_keywords.insert("VAR_OUTPUT".to_string(), Token::VarOutput);
+        reserved_keywords.insert("END_VAR".to_string(), Token::EndVar);
+        reserved_keywords.insert("AT".to_string(), Token::At);
+        reserved_keywords.insert("TYPE".to_string(), Token::Type);
+        reserved_keywords.insert("END_TYPE".to_string(), Token::EndType);
+        reserved_keywords.insert("STRUCT".to_string(), Token::Struct);
+        reserved_keywords.insert("END_STRUCT".to_string(), Token::EndStruct);
+        reserved_keywords.insert("ARRAY".to_string(), Token::Array);
+        reserved_keywords.insert("IF".to_string(), Token::If);
+        reserved_keywords.insert("THEN".to_string(), Token::Then);
+        reserved_keywords.insert("ELSIF".to_string(), Token::Elsif);
+        reserved_keywords.insert("ELSE".to_string(), Token::Else);
+        reserved_keywords.insert("END_IF".to_string(), Token::EndIf);
+        reserved_keywords.insert("CASE".to_string(), Token::Case);
+        reserved_keywords.insert("OF".to_string(), Token::Of);
+        reserved_keywords.insert("END_CASE".to_string(), Token::EndCase);
+        reserved_keywords.insert("FOR".to_string(), Token::For);
+        reserved_keywords.insert("TO".to_string(), Token::To);
+        reserved_keywords.insert("BY".to_string(), Token::By);
+        reserved_keywords.insert("DO".to_string(), Token::Do);
+        reserved_keywords.insert("END_FOR".to_string(), Token::EndFor);
+        reserved_keywords.insert("WHILE".to_string(), Token::While);
+        reserved_keywords.insert("END_WHILE".to_string(), Token::EndWhile);
+        reserved_keywords.insert("REPEAT".to_string(), Token::Repeat);
+        reserved_keywords.insert("UNTIL".to_string(), Token::Until);
+        reserved_keywords.insert("END_REPEAT".to_string(), Token::EndRepeat);
+        reserved_keywords.insert("EXIT".to_string(), Token::Exit);
+        reserved_keywords.insert("CONTINUE".to_string(), Token::Continue);
+        reserved_keywords.insert("RETURN".to_string(), Token::Return);
+        reserved_keywords.insert("MOD".to_string(), Token::Mod);
+        reserved_keywords.insert("AND".to_string(), Token::And);
+        reserved_keywords.insert("OR".to_string(), Token::Or);
+        reserved_keywords.insert("XOR".to_string(), Token::Xor);
+        reserved_keywords.insert("NOT".to_string(), Token::Not);
+        reserved_keywords.insert("TRUE".to_string(), Token::Bool(true));
+        reserved_keywords.insert("FALSE".to_string(), Token::Bool(false));
         trace!("New Lexer");
         Lexer {
             text: text.chars().collect(),
             pos: 0,
+            offset: 0,
+            line: 1,
+            column: 1,
             current_char: text.chars().nth(0),
             reserved_keywords,
         }
     }
 
-    fn id(&mut self) -> Token {
+    fn id(&mut self, start: Span) -> Result<Token, LexError> {
+        let first = self.pos;
         let mut result = "".to_string();
         while let Some(ch) = self.current_char {
             if ch.is_alphanumeric() || ch == '_' {
@@ -34,16 +95,259 @@ impl Lexer {
             }
         }
 
-        if self.reserved_keywords.contains_key(&result) {
-            trace!("Reserved keyword {}", result);
-            self.reserved_keywords.get(&result).unwrap().clone()
+        let upper = result.to_ascii_uppercase();
+        if self.current_char == Some('#') {
+            match upper.as_str() {
+                "T" | "TIME" => return self.duration(start).map(Token::Time),
+                "LT" | "LTIME" => return self.duration(start).map(Token::LTime),
+                "D" | "DATE" => {
+                    return self
+                        .date_time(start, parse_date, LexError::InvalidDate)
+                        .map(Token::Date)
+                }
+                "TOD" | "TIME_OF_DAY" => {
+                    return self
+                        .date_time(start, parse_time_of_day, LexError::InvalidTime)
+                        .map(Token::TimeOfDay)
+                }
+                "DT" | "DATE_AND_TIME" => {
+                    return self
+                        .date_time(start, parse_date_and_time, LexError::InvalidDate)
+                        .map(Token::DateAndTime)
+                }
+                _ => {}
+            }
+            if let Some(data_type) = DataType::from_name(&result) {
+                return self.typed_literal(data_type, first, start);
+            }
+        }
+        Ok(if self.reserved_keywords.contains_key(&upper) {
+            trace!("Reserved keyword {}", upper);
+            self.reserved_keywords.get(&upper).unwrap().clone()
         } else {
             trace!("Token::Id({})", result);
             Token::Id(result)
+        })
+    }
+
+    /// Lexes a direct address such as `%IX0.0` or `%QW4`.
+    fn direct_address(&mut self, start: Span) -> Result<Token, LexError> {
+        let mut result = "%".to_string();
+        self.advance();
+        while let Some(ch) = self.current_char {
+            if ch.is_ascii_alphanumeric() || ch == '.' {
+                result.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        match Address::parse(&result) {
+            Some(address) => Ok(Token::DirectAddress(address)),
+            None => Err(LexError::InvalidAddress(result, start.to(self.location()))),
+        }
+    }
+
+    /// Lexes the part of a `T#1h2m3s4ms` or `LTIME#5us` literal after the
+    /// prefix.
+    fn duration(&mut self, start: Span) -> Result<i64, LexError> {
+        self.advance();
+        let mut result = "".to_string();
+        if self.current_char == Some('-') {
+            result.push('-');
+            self.advance();
+        }
+        while let Some(ch) = self.current_char {
+            if ch.is_ascii_alphanumeric() || ch == '_' || ch == '.' {
+                result.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        match parse_duration(&result) {
+            Some(value) => {
+                trace!("Duration {}ns", value);
+                Ok(value)
+            }
+            None => Err(LexError::InvalidTime(result, start.to(self.location()))),
         }
     }
 
+    /// Lexes the part of a `D#2024-01-31`, `TOD#12:30:00` or
+    /// `DT#2024-01-31-12:30:00` literal after the prefix.
+    fn date_time(
+        &mut self,
+        start: Span,
+        parse: fn(&str) -> Option<i64>,
+        error: fn(String, Span) -> LexError,
+    ) -> Result<i64, LexError> {
+        self.advance();
+        let mut result = "".to_string();
+        while let Some(ch) = self.current_char {
+            if ch.is_ascii_digit() || ch == '-' || ch == ':' || ch == '.' {
+                result.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        match parse(&result) {
+            Some(value) => {
+                trace!("Date or time {}ns", value);
+                Ok(value)
+            }
+            None => Err(error(result, start.to(self.location()))),
+        }
+    }
+
+    /// Lexes the part of a typed literal such as `INT#-5`, `REAL#1.5`,
+    /// `WORD#16#FFFF` or `BOOL#TRUE` after the type name, checking that the
+    /// value fits the type.
+    fn typed_literal(
+        &mut self,
+        data_type: DataType,
+        first: usize,
+        start: Span,
+    ) -> Result<Token, LexError> {
+        self.advance();
+        let negative = self.current_char == Some('-');
+        if let Some('-') | Some('+') = self.current_char {
+            self.advance();
+        }
+        let value = match self.current_char {
+            Some(ch) if ch.is_alphabetic() => Some(self.id(start)?),
+            Some(ch) if ch.is_ascii_digit() => Some(self.number(start)?),
+            _ => None,
+        };
+
+        let token = match value {
+            Some(Token::Bool(value)) if data_type == DataType::Bool && !negative => {
+                Some(Token::Bool(value))
+            }
+            Some(Token::Integer(value, None)) if data_type == DataType::Bool && !negative => {
+                match value {
+                    0 | 1 => Some(Token::Bool(value == 1)),
+                    _ => None,
+                }
+            }
+            Some(Token::Integer(value, None)) => {
+                let value = if negative { -value } else { value };
+                if data_type.is_real() {
+                    Some(Token::Real(value as f64, Some(data_type)))
+                } else {
+                    let (min, max) = data_type.range();
+                    if data_type != DataType::Bool && value >= min && value <= max {
+                        Some(Token::Integer(value, Some(data_type)))
+                    } else {
+                        None
+                    }
+                }
+            }
+            Some(Token::Real(value, None)) if data_type.is_real() => {
+                let value = if negative { -value } else { value };
+                if data_type == DataType::Real && value.abs() > f64::from(f32::MAX) {
+                    None
+                } else {
+                    Some(Token::Real(value, Some(data_type)))
+                }
+            }
+            _ => None,
+        };
+        match token {
+            Some(token) => {
+                trace!("{:?}", token);
+                Ok(token)
+            }
+            None => Err(LexError::InvalidNumber(
+                self.text_from(first),
+                start.to(self.location()),
+            )),
+        }
+    }
+
+    /// Lexes a single-quoted STRING or double-quoted WSTRING literal.
+    fn string(&mut self, start: Span) -> Result<Token, LexError> {
+        let quote = self.current_char.unwrap();
+        self.advance();
+        let mut result = "".to_string();
+        loop {
+            match self.current_char {
+                None => return Err(LexError::UnterminatedString(start.to(self.location()))),
+                Some(ch) if ch == quote => {
+                    self.advance();
+                    break;
+                }
+                Some('$') => {
+                    let escape = self.location();
+                    self.advance();
+                    result.push(self.escape(quote, escape)?);
+                }
+                Some(ch) => {
+                    result.push(ch);
+                    self.advance();
+                }
+            }
+        }
+        Ok(if quote == '\'' {
+            Token::String(result)
+        } else {
+            Token::WString(result)
+        })
+    }
+
+    /// Resolves the escape after a `$`. A character code has two hex digits
+    /// in a STRING and four in a WSTRING.
+    fn escape(&mut self, quote: char, start: Span) -> Result<char, LexError> {
+        let ch = match self.current_char {
+            Some(ch) => ch,
+            None => return Err(LexError::UnterminatedString(start.to(self.location()))),
+        };
+        self.advance();
+        let escaped = match ch.to_ascii_uppercase() {
+            '$' | '\'' | '"' => Some(ch),
+            'L' | 'N' => Some('\n'),
+            'P' => Some('\x0C'),
+            'R' => Some('\r'),
+            'T' => Some('\t'),
+            _ if ch.is_ascii_hexdigit() => {
+                let count = if quote == '\'' { 2 } else { 4 };
+                let mut code = ch.to_string();
+                while code.len() < count {
+                    match self.current_char {
+                        Some(digit) if digit.is_ascii_hexdigit() => {
+                            code.push(digit);
+                            self.advance();
+                        }
+                        _ => break,
+                    }
+                }
+                u32::from_str_radix(&code, 16)
+                    .ok()
+                    .filter(|_| code.len() == count)
+                    .and_then(std::char::from_u32)
+            }
+            _ => None,
+        };
+        escaped
+            .ok_or_else(|| LexError::InvalidEscape(format!("${}", ch), start.to(self.location())))
+    }
+
+    /// The source text from character position `first` to the current one.
+    fn text_from(&self, first: usize) -> String {
+        self.text[first..self.pos].iter().collect()
+    }
+
     fn advance(&mut self) {
+        if let Some(ch) = self.current_char {
+            self.offset += ch.len_utf8();
+            if ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
         self.pos += 1;
         if self.pos > self.text.len() - 1 {
             self.current_char = None;
@@ -62,33 +366,154 @@ impl Lexer {
         }
     }
 
-    fn integer(&mut self) -> i32 {
-        let mut result = "".to_string();
+    /// Skips a `(* ... *)` or `/* ... */` comment. Comments of the same kind
+    /// nest, so a commented-out region may itself contain comments.
+    fn skip_block_comment(&mut self, start: Span) -> Result<(), LexError> {
+        let open = self.current_char.unwrap();
+        let close = if open == '(' { ')' } else { '/' };
+        self.advance();
+        self.advance();
+        let mut depth = 1;
+        while depth > 0 {
+            match (self.current_char, self.peek()) {
+                (None, _) => return Err(LexError::UnterminatedComment(start.to(self.location()))),
+                (Some('*'), Some(ch)) if ch == close => {
+                    self.advance();
+                    self.advance();
+                    depth -= 1;
+                }
+                (Some(ch), Some('*')) if ch == open => {
+                    self.advance();
+                    self.advance();
+                    depth += 1;
+                }
+                _ => self.advance(),
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips a `//` comment up to the end of the line.
+    fn skip_line_comment(&mut self) {
         while let Some(ch) = self.current_char {
-            if ch.is_digit(10) {
+            if ch == '\n' {
+                break;
+            }
+            self.advance();
+        }
+    }
+
+    /// Reads decimal digits, dropping single underscores between them.
+    fn digits(&mut self, result: &mut String) {
+        while let Some(ch) = self.current_char {
+            let is_separator = ch == '_'
+                && result.ends_with(|ch: char| ch.is_ascii_digit())
+                && matches!(self.peek(), Some(next) if next.is_ascii_digit());
+            if ch.is_ascii_digit() {
                 result.push(ch);
                 self.advance();
+            } else if is_separator {
+                self.advance();
             } else {
                 break;
             }
         }
+    }
+
+    /// An empty span at the current position.
+    fn location(&self) -> Span {
+        Span::new(self.offset, self.offset, self.line, self.column)
+    }
+
+    fn number(&mut self, start: Span) -> Result<Token, LexError> {
+        let first = self.pos;
+        let mut result = "".to_string();
+        self.digits(&mut result);
+        if self.current_char == Some('#') {
+            return self.based_number(result, first, start);
+        }
+
+        let is_fraction = matches!(self.peek(), Some(ch) if ch.is_ascii_digit());
+        if self.current_char == Some('.') && is_fraction {
+            result.push('.');
+            self.advance();
+            self.digits(&mut result);
 
-        result.parse().unwrap()
+            if let Some('e') | Some('E') = self.current_char {
+                result.push('e');
+                self.advance();
+                if let Some(sign @ '+') | Some(sign @ '-') = self.current_char {
+                    result.push(sign);
+                    self.advance();
+                }
+                self.digits(&mut result);
+            }
+            result
+                .parse()
+                .map(|value| Token::Real(value, None))
+                .map_err(|_| {
+                    LexError::InvalidNumber(self.text_from(first), start.to(self.location()))
+                })
+        } else {
+            match result.parse::<i128>() {
+                Ok(value) if value <= DataType::ULInt.range().1 => Ok(Token::Integer(value, None)),
+                _ => Err(LexError::InvalidNumber(
+                    self.text_from(first),
+                    start.to(self.location()),
+                )),
+            }
+        }
+    }
+
+    /// Lexes the digits of a `2#`, `8#` or `16#` literal after the base.
+    fn based_number(&mut self, base: String, first: usize, start: Span) -> Result<Token, LexError> {
+        self.advance();
+        let mut digits = "".to_string();
+        while let Some(ch) = self.current_char {
+            if ch.is_ascii_alphanumeric() || ch == '_' {
+                digits.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        let radix = match base.as_str() {
+            "2" => Some(2),
+            "8" => Some(8),
+            "16" => Some(16),
+            _ => None,
+        };
+        let separators_valid =
+            !digits.starts_with('_') && !digits.ends_with('_') && !digits.contains("__");
+        let value = radix
+            .filter(|_| separators_valid)
+            .and_then(|radix| i128::from_str_radix(&digits.replace('_', ""), radix).ok())
+            .filter(|value| *value <= DataType::ULInt.range().1);
+        match value {
+            Some(value) => Ok(Token::Integer(value, None)),
+            None => Err(LexError::InvalidNumber(
+                self.text_from(first),
+                start.to(self.location()),
+            )),
+        }
     }
 
     fn peek(&mut self) -> Option<char> {
-        if self.pos + 1 > self.text.len() {
+        if self.pos + 1 >= self.text.len() {
             None
         } else {
             Some(self.text[self.pos + 1])
         }
     }
 
-    pub fn get_next_token(&mut self) -> Option<Token> {
+    /// Returns the next token, or `Token::Eof` once the text is exhausted.
+    pub fn get_next_token(&mut self) -> Result<SpannedToken, LexError> {
         let mut token: Option<Token> = None;
+        let mut start = self.location();
         while let Some(ch) = self.current_char {
+            start = self.location();
             if ch.is_alphabetic() {
-                token = Some(self.id());
+                token = Some(self.id(start)?);
                 break;
             } else if ch == ':' && self.peek() == Some('=') {
                 self.advance();
@@ -96,19 +521,62 @@ impl Lexer {
                 trace!("Token::Assign");
                 token = Some(Token::Assign);
                 break;
+            } else if ch == ':' {
+                self.advance();
+                trace!("Token::Colon");
+                token = Some(Token::Colon);
+                break;
+            } else if ch == ',' {
+                self.advance();
+                trace!("Token::Comma");
+                token = Some(Token::Comma);
+                break;
+            } else if ch == '.' && self.peek() == Some('.') {
+                self.advance();
+                self.advance();
+                trace!("Token::DotDot");
+                token = Some(Token::DotDot);
+                break;
+            } else if ch == '.' {
+                self.advance();
+                trace!("Token::Dot");
+                token = Some(Token::Dot);
+                break;
             } else if ch == ';' {
                 self.advance();
                 trace!("Token::Semicolon");
                 token = Some(Token::Semicolon);
                 break;
+            } else if ch == '#'
+                && self.pos > 0
+                && self.text[self.pos - 1].is_alphanumeric()
+                && matches!(self.peek(), Some(next) if next.is_alphabetic() || next == '_')
+            {
+                self.advance();
+                trace!("Token::Hash");
+                token = Some(Token::Hash);
+                break;
+            } else if ch == '%' {
+                let address = self.direct_address(start)?;
+                trace!("{:?}", address);
+                token = Some(address);
+                break;
             } else if ch.is_whitespace() {
                 self.skip_whitespace();
                 trace!("Skipping whitespace");
                 continue;
-            } else if ch.is_digit(10) {
-                let integer = self.integer();
-                trace!("Token::Integer({})", integer);
-                token = Some(Token::Integer(integer));
+            } else if (ch == '(' || ch == '/') && self.peek() == Some('*') {
+                self.skip_block_comment(start)?;
+                trace!("Skipping comment");
+                continue;
+            } else if ch == '/' && self.peek() == Some('/') {
+                self.skip_line_comment();
+                trace!("Skipping comment");
+                continue;
+            } else if ch.is_ascii_digit() {
+                let number = self.number(start)?;
+                trace!("{:?}", number);
+                token = Some(number);
                 break;
             } else if ch == '+' {
                 self.advance();
@@ -120,6 +588,12 @@ impl Lexer {
                 trace!("Token::Minus");
                 token = Some(Token::Minus);
                 break;
+            } else if ch == '*' && self.peek() == Some('*') {
+                self.advance();
+                self.advance();
+                trace!("Token::Power");
+                token = Some(Token::Power);
+                break;
             } else if ch == '*' {
                 self.advance();
                 trace!("Token::Mul");
@@ -130,6 +604,59 @@ impl Lexer {
                 trace!("Token::Div");
                 token = Some(Token::Div);
                 break;
+            } else if ch == '=' {
+                self.advance();
+                trace!("Token::Equal");
+                token = Some(Token::Equal);
+                break;
+            } else if ch == '<' && self.peek() == Some('>') {
+                self.advance();
+                self.advance();
+                trace!("Token::NotEqual");
+                token = Some(Token::NotEqual);
+                break;
+            } else if ch == '<' && self.peek() == Some('=') {
+                self.advance();
+                self.advance();
+                trace!("Token::LessEqual");
+                token = Some(Token::LessEqual);
+                break;
+            } else if ch == '<' {
+                self.advance();
+                trace!("Token::Less");
+                token = Some(Token::Less);
+                break;
+            } else if ch == '>' && self.peek() == Some('=') {
+                self.advance();
+                self.advance();
+                trace!("Token::GreaterEqual");
+                token = Some(Token::GreaterEqual);
+                break;
+            } else if ch == '>' {
+                self.advance();
+                trace!("Token::Greater");
+                token = Some(Token::Greater);
+                break;
+            } else if ch == '&' {
+                self.advance();
+                trace!("Token::And");
+                token = Some(Token::And);
+                break;
+            } else if ch == '\'' || ch == '"' {
+                let string = self.string(start)?;
+                trace!("{:?}", string);
+                token = Some(string);
+                break;
+            } else if ch == '[' {
+                self.advance();
+                trace!("Token::Lbracket");
+                token = Some(Token::Lbracket);
+                break;
+            } else if ch == ']' {
+                self.advance();
+                trace!("Token::Rbracket");
+                token = Some(Token::Rbracket);
+                break;
             } else if ch == '(' {
                 self.advance();
                 trace!("Token::Lparen");
@@ -141,9 +668,357 @@ impl Lexer {
                 token = Some(Token::Rparen);
                 break;
             } else {
-                panic!("Unexpected char in Lexer: {}", ch);
+                self.advance();
+                return Err(LexError::UnexpectedChar(ch, start.to(self.location())));
+            }
+        }
+        match token {
+            Some(token) => Ok(SpannedToken::new(token, start.to(self.location()))),
+            None => Ok(SpannedToken::new(Token::Eof, self.location())),
+        }
+    }
+}
+
+/// Parses the value of a duration literal such as `-1h2m3.5s` into
+/// nanoseconds. Only the last component may have a fraction.
+pub fn parse_duration(text: &str) -> Option<i64> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let text = text.replace('_', "");
+    let mut rest = text.as_str();
+    let mut total: i128 = 0;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(split);
+        let split = tail
+            .find(|ch: char| !ch.is_ascii_alphabetic())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(split);
+        let unit: i128 = match unit.to_ascii_lowercase().as_str() {
+            "d" => 86_400_000_000_000,
+            "h" => 3_600_000_000_000,
+            "m" => 60_000_000_000,
+            "s" => 1_000_000_000,
+            "ms" => 1_000_000,
+            "us" => 1_000,
+            "ns" => 1,
+            _ => return None,
+        };
+        let part = if number.contains('.') {
+            if !tail.is_empty() {
+                return None;
             }
+            (number.parse::<f64>().ok()? * unit as f64).round() as i128
+        } else {
+            number.parse::<i128>().ok()?.checked_mul(unit)?
+        };
+        total = total.checked_add(part)?;
+        rest = tail;
+    }
+    if text.is_empty() {
+        return None;
+    }
+    let total = if negative { -total } else { total };
+    i64::try_from(total).ok()
+}
+
+/// Parses `2024-01-31` to nanoseconds since 1970-01-01.
+fn parse_date(text: &str) -> Option<i64> {
+    let mut parts = text.split('-').map(|part| {
+        if !part.is_empty() && part.chars().all(|ch| ch.is_ascii_digit()) {
+            part.parse::<u32>().ok()
+        } else {
+            None
         }
-        token
+    });
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() || !(1..=12).contains(&month) {
+        return None;
+    }
+    let year = i64::from(year);
+    if day < 1 || day > calendar::days_in_month(year, month) {
+        return None;
+    }
+    calendar::days_from_civil(year, month, day).checked_mul(NANOSECONDS_PER_DAY)
+}
+
+/// Parses `12:30:00.5` to nanoseconds since midnight.
+fn parse_time_of_day(text: &str) -> Option<i64> {
+    let (time, fraction) = match text.split_once('.') {
+        Some((time, fraction)) => (time, fraction),
+        None => (text, "0"),
+    };
+    let parts = time
+        .split(':')
+        .map(|part| match part.len() {
+            1 | 2 if part.chars().all(|ch| ch.is_ascii_digit()) => part.parse::<i64>().ok(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let valid_fraction = !fraction.is_empty()
+        && fraction.len() <= 9
+        && fraction.chars().all(|ch| ch.is_ascii_digit());
+    match parts[..] {
+        [hours, minutes, seconds]
+            if hours < 24 && minutes < 60 && seconds < 60 && valid_fraction =>
+        {
+            let nanoseconds: i64 = format!("{:0<9}", fraction).parse().ok()?;
+            Some(((hours * 60 + minutes) * 60 + seconds) * 1_000_000_000 + nanoseconds)
+        }
+        _ => None,
+    }
+}
+
+/// Parses `2024-01-31-12:30:00` to nanoseconds since 1970-01-01-00:00:00.
+fn parse_date_and_time(text: &str) -> Option<i64> {
+    let split = text.match_indices('-').nth(2)?.0;
+    let date = parse_date(&text[..split])?;
+    date.checked_add(parse_time_of_day(&text[split + 1..])?)
+}
+
+#[test]
+fn lex_durations() {
+    let lex = |text: &str| {
+        Lexer::new(text.to_string())
+            .get_next_token()
+            .map(|t| t.token)
+    };
+    assert_eq!(lex("T#1h2m3s4ms"), Ok(Token::Time(3_723_004_000_000)));
+    assert_eq!(lex("TIME#-5s"), Ok(Token::Time(-5_000_000_000)));
+    assert_eq!(lex("T#1.5s"), Ok(Token::Time(1_500_000_000)));
+    assert_eq!(lex("T#1_000ms"), Ok(Token::Time(1_000_000_000)));
+    assert_eq!(
+        lex("T#5x"),
+        Err(LexError::InvalidTime(
+            "5x".to_string(),
+            Span::new(0, 4, 1, 1)
+        ))
+    );
+    assert!(matches!(
+        lex("T#170141183460469231731687303715884105727ns1ns"),
+        Err(LexError::InvalidTime(..))
+    ));
+    assert!(matches!(
+        lex("LTIME#9223372036854775807ns1ns"),
+        Err(LexError::InvalidTime(..))
+    ));
+    assert_eq!(
+        parse_duration("106751d23h47m16s854ms775us807ns"),
+        Some(i64::MAX)
+    );
+}
+
+#[test]
+fn lex_dates_and_times_of_day() {
+    const DAY: i64 = NANOSECONDS_PER_DAY;
+    let lex = |text: &str| {
+        Lexer::new(text.to_string())
+            .get_next_token()
+            .map(|t| t.token)
+    };
+    assert_eq!(lex("LT#1.5us"), Ok(Token::LTime(1_500)));
+    assert_eq!(lex("D#2024-01-01"), Ok(Token::Date(19_723 * DAY)));
+    assert_eq!(lex("DATE#1969-12-31"), Ok(Token::Date(-DAY)));
+    assert_eq!(
+        lex("TOD#12:00:00.25"),
+        Ok(Token::TimeOfDay(43_200_250_000_000))
+    );
+    assert_eq!(
+        lex("DT#2024-02-29-23:59:59"),
+        Ok(Token::DateAndTime(19_782 * DAY + DAY - 1_000_000_000))
+    );
+    for text in ["D#2023-02-29", "DT#2024-01-01", "D#2024-1"].iter() {
+        assert!(
+            matches!(lex(text), Err(LexError::InvalidDate(..))),
+            "{} should not lex",
+            text
+        );
     }
+    assert!(matches!(
+        lex("TIME_OF_DAY#24:00:00"),
+        Err(LexError::InvalidTime(..))
+    ));
+}
+
+#[test]
+fn lex_literals() {
+    let lex = |text: &str| {
+        Lexer::new(text.to_string())
+            .get_next_token()
+            .map(|t| t.token)
+    };
+    assert_eq!(lex("2#1010"), Ok(Token::Integer(10, None)));
+    assert_eq!(lex("8#777"), Ok(Token::Integer(511, None)));
+    assert_eq!(lex("16#FF_FF"), Ok(Token::Integer(65535, None)));
+    assert_eq!(lex("1_000_000"), Ok(Token::Integer(1_000_000, None)));
+    assert_eq!(lex("1.5E-3"), Ok(Token::Real(1.5e-3, None)));
+    assert_eq!(lex("INT#-5"), Ok(Token::Integer(-5, Some(DataType::Int))));
+    assert_eq!(lex("REAL#1.5"), Ok(Token::Real(1.5, Some(DataType::Real))));
+    assert_eq!(lex("LREAL#2"), Ok(Token::Real(2.0, Some(DataType::LReal))));
+    assert_eq!(
+        lex("WORD#16#FFFF"),
+        Ok(Token::Integer(65535, Some(DataType::Word)))
+    );
+    assert_eq!(lex("BOOL#1"), Ok(Token::Bool(true)));
+    assert_eq!(lex("BOOL#FALSE"), Ok(Token::Bool(false)));
+    for text in [
+        "SINT#128", "UINT#-1", "3#12", "16#FG", "16#_F", "INT#1.5", "BOOL#2",
+    ]
+    .iter()
+    {
+        assert!(
+            matches!(lex(text), Err(LexError::InvalidNumber(..))),
+            "{} should not lex",
+            text
+        );
+    }
+}
+
+#[test]
+fn lex_strings() {
+    let lex = |text: &str| {
+        Lexer::new(text.to_string())
+            .get_next_token()
+            .map(|t| t.token)
+    };
+    assert_eq!(
+        lex("'it$'s $$5$N'"),
+        Ok(Token::String("it's $5\n".to_string()))
+    );
+    assert_eq!(lex("'$41$0a'"), Ok(Token::String("A\n".to_string())));
+    assert_eq!(
+        lex("\"$\"$00C4\""),
+        Ok(Token::WString("\"\u{C4}".to_string()))
+    );
+    assert_eq!(lex("''"), Ok(Token::String("".to_string())));
+    assert_eq!(
+        lex("'$Q'"),
+        Err(LexError::InvalidEscape(
+            "$Q".to_string(),
+            Span::new(1, 3, 1, 2)
+        ))
+    );
+    assert!(matches!(lex("\"$4\""), Err(LexError::InvalidEscape(..))));
+    assert_eq!(
+        lex("'abc"),
+        Err(LexError::UnterminatedString(Span::new(0, 4, 1, 1)))
+    );
+}
+
+#[test]
+fn lex_errors() {
+    let mut lexer = Lexer::new("x ? 1".to_string());
+    assert_eq!(
+        lexer.get_next_token().map(|spanned| spanned.token),
+        Ok(Token::Id("x".to_string()))
+    );
+    assert_eq!(
+        lexer.get_next_token(),
+        Err(LexError::UnexpectedChar('?', Span::new(2, 3, 1, 3)))
+    );
+
+    let mut lexer = Lexer::new("99999999999999999999".to_string());
+    assert_eq!(
+        lexer.get_next_token(),
+        Err(LexError::InvalidNumber(
+            "99999999999999999999".to_string(),
+            Span::new(0, 20, 1, 1)
+        ))
+    );
+}
+
+#[test]
+fn lex_spans() {
+    let mut lexer = Lexer::new("x :=\n  1.5;".to_string());
+    let mut spans = Vec::new();
+    loop {
+        let spanned = lexer.get_next_token().unwrap();
+        spans.push(spanned.span);
+        if spanned.token == Token::Eof {
+            break;
+        }
+    }
+    assert_eq!(
+        spans,
+        vec![
+            Span::new(0, 1, 1, 1),
+            Span::new(2, 4, 1, 3),
+            Span::new(7, 10, 2, 3),
+            Span::new(10, 11, 2, 6),
+            Span::new(11, 11, 2, 7),
+        ]
+    );
+}
+
+#[test]
+fn lex_comments() {
+    let mut lexer = Lexer::new(
+        "(* header (* nested *) *) x // to the end
+        /* block (* not nested *) */ := (*)*) 1 / 2"
+            .to_string(),
+    );
+    let mut tokens = Vec::new();
+    loop {
+        let spanned = lexer.get_next_token().unwrap();
+        if spanned.token == Token::Eof {
+            break;
+        }
+        tokens.push((spanned.token, spanned.span));
+    }
+    assert_eq!(
+        tokens,
+        vec![
+            (Token::Id("x".to_string()), Span::new(26, 27, 1, 27)),
+            (Token::Assign, Span::new(79, 81, 2, 38)),
+            (Token::Integer(1, None), Span::new(88, 89, 2, 47)),
+            (Token::Div, Span::new(90, 91, 2, 49)),
+            (Token::Integer(2, None), Span::new(92, 93, 2, 51)),
+        ]
+    );
+
+    let mut lexer = Lexer::new("x (* (* *)".to_string());
+    lexer.get_next_token().unwrap();
+    assert_eq!(
+        lexer.get_next_token(),
+        Err(LexError::UnterminatedComment(Span::new(2, 10, 1, 3)))
+    );
+}
+
+#[test]
+fn lex_direct_addresses() {
+    let mut lexer = Lexer::new("x at %QW4; y AT %ix0.1".to_string());
+    let mut tokens = Vec::new();
+    loop {
+        let spanned = lexer.get_next_token().unwrap();
+        if spanned.token == Token::Eof {
+            break;
+        }
+        tokens.push(spanned.token);
+    }
+    let address = |text| Token::DirectAddress(Address::parse(text).unwrap());
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Id("x".to_string()),
+            Token::At,
+            address("%QW4"),
+            Token::Semicolon,
+            Token::Id("y".to_string()),
+            Token::At,
+            address("%IX0.1"),
+        ]
+    );
+
+    let mut lexer = Lexer::new("%QX1".to_string());
+    assert_eq!(
+        lexer.get_next_token(),
+        Err(LexError::InvalidAddress(
+            "%QX1".to_string(),
+            Span::new(0, 4, 1, 1)
+        ))
+    );
 }
@@ -1,16 +1,110 @@
+use std::convert::TryFrom;
 use std::io::{stdin, stdout, Write};
+use std::time::Duration;
 use std::{env, fs};
 
 mod ast;
+mod calendar;
+mod clock;
+mod error;
+mod identifiers;
+mod image;
 mod interpreter;
 mod lexer;
+mod object;
 mod parser;
+mod stdlib;
 mod token;
 
+use error::Error;
+use identifiers::Identifiers;
 use interpreter::Interpreter;
 use lexer::Lexer;
+use object::Object;
 use parser::Parser;
 
+/// Options of cyclic execution: `--cycles N` or `--duration TIME`, and
+/// `--period TIME`, with TIME written like a TIME literal, e.g. `10ms`.
+/// Without a limit the cycles run until the process is stopped. With
+/// `--simulate` the clock jumps to the next due task instead of waiting.
+/// Only `--cycles` may leave out the period, to run the cycles back to back.
+#[derive(Debug, PartialEq)]
+struct CycleOptions {
+    cycles: Option<u64>,
+    duration: Option<Duration>,
+    period: Duration,
+    simulate: bool,
+}
+
+impl CycleOptions {
+    fn parse(args: &[String]) -> Option<CycleOptions> {
+        let mut options = CycleOptions {
+            cycles: None,
+            duration: None,
+            period: Duration::ZERO,
+            simulate: false,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--simulate" {
+                options.simulate = true;
+                continue;
+            }
+            let value = args.next()?;
+            match arg.as_str() {
+                "--cycles" => options.cycles = Some(value.parse().ok()?),
+                "--duration" => options.duration = Some(parse_time(value)?),
+                "--period" => options.period = parse_time(value).filter(|p| !p.is_zero())?,
+                _ => return None,
+            }
+        }
+        if options.cycles.is_some() && options.duration.is_some() {
+            return None;
+        }
+        if options.cycles.is_none() && options.period.is_zero() {
+            return None;
+        }
+        Some(options)
+    }
+}
+
+fn parse_time(text: &str) -> Option<Duration> {
+    let nanoseconds = lexer::parse_duration(text)?;
+    u64::try_from(nanoseconds).ok().map(Duration::from_nanos)
+}
+
+/// Loads the programs of `interpreter`, runs their scan cycles and prints
+/// the final values of their variables.
+fn run_cyclic(interpreter: &mut Interpreter, options: &CycleOptions) -> Result<(), Error> {
+    interpreter.load()?;
+    match (options.cycles, options.duration) {
+        (Some(cycles), _) => interpreter.run_cycles(cycles, options.period)?,
+        (None, Some(duration)) => {
+            interpreter.run_for(duration, options.period)?;
+        }
+        (None, None) => {
+            interpreter.run_until(options.period, |_, _| false)?;
+        }
+    }
+    print_variables("", &interpreter.global_scope);
+    Ok(())
+}
+
+/// Prints `variables` sorted by name, with the variables of instances as
+/// `instance.name`.
+fn print_variables(prefix: &str, variables: &Identifiers<Object>) {
+    let mut variables: Vec<_> = variables.iter().collect();
+    variables.sort_by_key(|(name, _)| *name);
+    for (name, value) in variables {
+        match value {
+            Object::Instance(instance) => {
+                print_variables(&format!("{}{}.", prefix, name), &instance.vars)
+            }
+            value => println!("{}{} = {}", prefix, name, value),
+        }
+    }
+}
+
 fn main() -> std::io::Result<()> {
     env_logger::init();
 
@@ -29,22 +123,49 @@ fn main() -> std::io::Result<()> {
                 let lexer = Lexer::new(text.clone());
                 let parser = Parser::new(lexer);
                 let mut interpreter = Interpreter::new(parser);
-                interpreter.interpret();
+                if let Err(error) = interpreter.interpret() {
+                    println!("{}", error);
+                }
             }
         }
         2 => {
             // Program argument
             let path = std::path::PathBuf::from(args[1].clone());
-            let text =
-                fs::read_to_string(path.clone()).expect(&format!("Could not open file {:?}", path));
+            let text = fs::read_to_string(path.clone())
+                .unwrap_or_else(|_| panic!("Could not open file {:?}", path));
             let lexer = Lexer::new(text);
             let parser = Parser::new(lexer);
             let mut interpreter = Interpreter::new(parser);
-            interpreter.interpret();
-        }
-        _ => {
-            println!("Usage: 1 program file argument or no argument for REPL");
+            if let Err(error) = interpreter.interpret() {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
         }
+        _ => match CycleOptions::parse(&args[2..]) {
+            Some(options) => {
+                let path = std::path::PathBuf::from(args[1].clone());
+                let text = fs::read_to_string(path.clone())
+                    .unwrap_or_else(|_| panic!("Could not open file {:?}", path));
+                let parser = Parser::new(Lexer::new(text));
+                let mut interpreter = if options.simulate {
+                    Interpreter::with_clock(parser, Box::new(clock::VirtualClock::new()))
+                } else {
+                    Interpreter::new(parser)
+                };
+                if let Err(error) = run_cyclic(&mut interpreter, &options) {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            }
+            None => {
+                println!(
+                    "Usage: 1 program file argument or no argument for REPL, \
+                     optionally followed by [--cycles N | --duration TIME] [--period TIME] \
+                     [--simulate] to run the program cyclically, with a --period above 0 \
+                     unless --cycles is given"
+                );
+            }
+        },
     }
     Ok(())
 }
@@ -59,7 +180,7 @@ fn interpret_addition() {
 
     let mut buffer: Vec<u8> = Vec::new();
 
-    interpreter.interpreter_writer(&mut buffer);
+    interpreter.interpreter_writer(&mut buffer).unwrap();
 
     assert_eq!(buffer[0], b'3');
 }
@@ -67,7 +188,10 @@ fn interpret_addition() {
 #[test]
 fn interpret_program() {
     let _ = env_logger::builder().is_test(true).try_init();
-    let text = "PROGRAM
+    let text = "PROGRAM main
+        VAR
+            y : INT;
+        END_VAR
         y := 3;
     END_PROGRAM"
         .to_string();
@@ -77,7 +201,7 @@ fn interpret_program() {
     let mut interpreter = Interpreter::new(parser);
     let mut buffer: Vec<u8> = Vec::new();
 
-    interpreter.interpreter_writer(&mut buffer);
+    interpreter.interpreter_writer(&mut buffer).unwrap();
 
     assert_eq!(buffer[0], b'3');
 }
@@ -86,7 +210,10 @@ fn interpret_program() {
 fn interpret_program_with_assignment() {
     let _ = env_logger::builder().is_test(true).try_init();
 
-    let text = "PROGRAM
+    let text = "PROGRAM main
+        VAR
+            x : INT;
+        END_VAR
         x := 2;
     END_PROGRAM"
         .to_string();
@@ -97,7 +224,1256 @@ fn interpret_program_with_assignment() {
 
     let mut buffer: Vec<u8> = Vec::new();
 
-    interpreter.interpreter_writer(&mut buffer);
+    interpreter.interpreter_writer(&mut buffer).unwrap();
+
+    assert_eq!(*interpreter.global_scope.get("x").unwrap(), Object::Int(2));
+}
+
+/// Interprets `text`, returning the interpreter with the variables it left
+/// and the printed result or the error it stopped with.
+#[cfg(test)]
+fn run(text: &str) -> (Interpreter, Result<String, Error>) {
+    let mut interpreter = Interpreter::new(Parser::new(Lexer::new(text.to_string())));
+    let mut buffer: Vec<u8> = Vec::new();
+    let result = interpreter
+        .interpreter_writer(&mut buffer)
+        .map(|()| String::from_utf8(buffer).unwrap().trim().to_string());
+    (interpreter, result)
+}
+
+/// The interpreter after running `text`, which must succeed.
+#[cfg(test)]
+fn interpret(text: &str) -> Interpreter {
+    match run(text) {
+        (interpreter, Ok(_)) => interpreter,
+        (_, Err(error)) => panic!("{} in {}", error, text),
+    }
+}
+
+/// The interpreter after running `text` and the error it stopped with.
+#[cfg(test)]
+fn interpret_error(text: &str) -> (Interpreter, String) {
+    match run(text) {
+        (interpreter, Err(error)) => (interpreter, error.to_string()),
+        (_, Ok(output)) => panic!("Expected an error in {}, found {}", text, output),
+    }
+}
+
+/// The error loading `text` for cyclic execution.
+#[cfg(test)]
+fn load_error(text: &str) -> String {
+    let mut interpreter = Interpreter::new(Parser::new(Lexer::new(text.to_string())));
+    match interpreter.load() {
+        Err(error) => error.to_string(),
+        Ok(()) => panic!("Expected an error loading {}", text),
+    }
+}
+
+#[test]
+fn interpret_program_with_initial_values() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let text = "PROGRAM main
+        VAR
+            x : INT := 2;
+            y : INT;
+        END_VAR
+        y := x * 3;
+    END_PROGRAM";
+    let interpreter = interpret(text);
+
+    assert_eq!(*interpreter.global_scope.get("y").unwrap(), Object::Int(6));
+}
+
+#[test]
+fn interpret_program_rejects_undeclared_variable() {
+    let text = "PROGRAM main
+        x := 2;
+    END_PROGRAM";
+
+    assert!(matches!(
+        run(text).1,
+        Err(error::Error::Runtime(error::RuntimeError::UndeclaredVariable(id), _)) if id == "x"
+    ));
+}
+
+#[test]
+fn interpret_elementary_types() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let text = "PROGRAM main
+        VAR
+            flag : BOOL := TRUE;
+            small : SINT := 127;
+            word : WORD;
+            ratio : REAL;
+        END_VAR
+        small := small + 1;
+        word := 65535 + 2;
+        ratio := 3 / 2.0;
+    END_PROGRAM";
+    let interpreter = interpret(text);
+
+    let scope = &interpreter.global_scope;
+    assert_eq!(*scope.get("flag").unwrap(), Object::Bool(true));
+    assert_eq!(*scope.get("small").unwrap(), Object::SInt(-128));
+    assert_eq!(*scope.get("word").unwrap(), Object::Word(1));
+    assert_eq!(*scope.get("ratio").unwrap(), Object::Real(1.5));
+}
+
+#[test]
+fn interpret_if_statement() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let text = "PROGRAM main
+        VAR
+            a : BOOL;
+            b : BOOL := TRUE;
+            x : INT;
+            y : INT;
+        END_VAR
+        IF a THEN
+            x := 1;
+        ELSIF b THEN
+            x := 2;
+            IF a THEN y := 1; ELSE y := 2; END_IF;
+        ELSE
+            x := 3;
+        END_IF;
+    END_PROGRAM";
+    let interpreter = interpret(text);
+
+    assert_eq!(*interpreter.global_scope.get("x").unwrap(), Object::Int(2));
+    assert_eq!(*interpreter.global_scope.get("y").unwrap(), Object::Int(2));
+}
+
+#[test]
+fn interpret_case_statement() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let text = "PROGRAM main
+        VAR
+            state : INT;
+            a : INT;
+            b : INT;
+            c : INT;
+        END_VAR
+        CASE 2 OF
+            0: a := 1;
+            1, 2: a := 2;
+        ELSE
+            a := 3;
+        END_CASE;
+        state := 15;
+        CASE state OF
+            1: b := 1;
+            10..20: b := 2;
+        END_CASE;
+        CASE state + 10 OF
+            1: c := 1;
+        ELSE
+            c := -1;
+        END_CASE;
+    END_PROGRAM";
+    let interpreter = interpret(text);
+
+    assert_eq!(*interpreter.global_scope.get("a").unwrap(), Object::Int(2));
+    assert_eq!(*interpreter.global_scope.get("b").unwrap(), Object::Int(2));
+    assert_eq!(*interpreter.global_scope.get("c").unwrap(), Object::Int(-1));
+}
+
+#[test]
+fn interpret_case_statement_rejects_duplicate_selector() {
+    let text = "PROGRAM main
+        VAR
+            a : INT;
+        END_VAR
+        CASE a OF
+            0..5: a := 1;
+            5, 6: a := 2;
+        END_CASE;
+    END_PROGRAM";
+
+    assert!(matches!(
+        run(text).1,
+        Err(error::Error::Runtime(error::RuntimeError::DuplicateCaseLabel(value), _)) if value == "5"
+    ));
+
+    for (statement, message) in [
+        (
+            "IF FALSE THEN CASE a OF 1: a := 2; 1: a := 3; END_CASE; END_IF;",
+            "Runtime error at 1:70: duplicate CASE label 1",
+        ),
+        (
+            "CASE a OF 10..5: a := 1; END_CASE;",
+            "Runtime error at 1:45: CASE range 10..5 selects no value",
+        ),
+    ] {
+        let text = format!(
+            "PROGRAM main VAR a : INT; END_VAR {} END_PROGRAM",
+            statement
+        );
+        assert_eq!(interpret_error(&text).1, message);
+        assert_eq!(load_error(&text), message);
+    }
+}
+
+#[test]
+fn interpret_loops() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let text = "PROGRAM main
+        VAR
+            i : INT;
+            sum : INT;
+            down : INT;
+            running : BOOL := TRUE;
+            count : INT;
+            done : BOOL;
+        END_VAR
+        FOR i := 1 TO 10 DO
+            IF i = 3 THEN CONTINUE; END_IF;
+            sum := sum + i;
+        END_FOR;
+        FOR i := 10 TO 1 BY -3 DO
+            down := down * 10 + i;
+        END_FOR;
+        WHILE running DO
+            count := count + 1;
+            CASE count OF 5: running := FALSE; END_CASE;
+        END_WHILE;
+        REPEAT
+            count := count + 1;
+            IF count >= 8 THEN EXIT; END_IF;
+        UNTIL done
+        END_REPEAT;
+    END_PROGRAM";
+    let interpreter = interpret(text);
+
+    let scope = &interpreter.global_scope;
+    assert_eq!(*scope.get("sum").unwrap(), Object::Int(52));
+    assert_eq!(*scope.get("down").unwrap(), Object::Int(10741));
+    assert_eq!(*scope.get("count").unwrap(), Object::Int(8));
+}
+
+#[test]
+fn interpret_for_rejects_modified_control_variable() {
+    let text = "PROGRAM main
+        VAR
+            i : INT;
+        END_VAR
+        FOR i := 1 TO 10 DO
+            i := i + 1;
+        END_FOR;
+    END_PROGRAM";
+
+    assert!(matches!(
+        run(text).1,
+        Err(error::Error::Runtime(error::RuntimeError::ControlVariableModified(id), _)) if id == "i"
+    ));
+}
+
+#[test]
+fn interpret_for_stops_at_the_end_of_the_type() {
+    let text = "PROGRAM main
+        VAR i : SINT; count : INT; u : USINT; END_VAR
+        FOR i := 120 TO 127 DO
+            count := count + 1;
+        END_FOR;
+        FOR u := 5 TO 0 BY -5 DO
+            count := count + 1;
+        END_FOR;
+    END_PROGRAM";
+    let interpreter = interpret(text);
+    let scope = &interpreter.global_scope;
+    assert_eq!(scope["i"], Object::SInt(127));
+    assert_eq!(scope["u"], Object::USInt(0));
+    assert_eq!(scope["count"], Object::Int(10));
+
+    let text =
+        "PROGRAM main VAR i : SINT; END_VAR FOR i := 100 TO 200 BY 50 DO END_FOR; END_PROGRAM";
+    assert_eq!(
+        interpret_error(text).1,
+        "Runtime error at 1:36: 150 is out of range for SINT"
+    );
+}
+
+#[test]
+fn interpret_operators() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let expressions = [
+        ("1 - 2 * 3", "-5"),
+        ("7 MOD 4 + 2 ** 3", "11"),
+        ("1 + 2 = 3 AND 2 <> 3", "TRUE"),
+        ("NOT (1 < 2) OR 3 >= 3 XOR TRUE", "FALSE"),
+        ("2.5 <= 2 & TRUE", "FALSE"),
+    ];
+    for (text, expected) in expressions.iter() {
+        assert_eq!(run(text).1.unwrap(), *expected, "{}", text);
+    }
+}
+
+#[test]
+fn interpret_reports_errors() {
+    let errors = [
+        ("1 / 0", "Runtime error at 1:1: division by zero"),
+        (
+            "1 + TRUE",
+            "Runtime error at 1:1: Addition is not defined for type BOOL",
+        ),
+        (
+            "1 +",
+            "Parse error at 1:4: expected expression, found end of input",
+        ),
+        ("1 # 2", "Parse error at 1:3: unexpected character '#'"),
+        (
+            "PROGRAM main
+  VAR x : INT; END_VAR
+  x := 1;
+  x := x / (x - 1);
+END_PROGRAM",
+            "Runtime error at 4:8: division by zero",
+        ),
+        (
+            "PROGRAM main
+  VAR x : INT; END_VAR
+  IF x THEN x := 1; END_IF
+END_PROGRAM",
+            "Runtime error at 3:6: IF condition must be BOOL, found INT",
+        ),
+    ];
+    for (text, expected) in errors.iter() {
+        assert_eq!(interpret_error(text).1, *expected);
+    }
+}
+
+#[test]
+fn interpret_function_calls() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let text = "FUNCTION scale : DINT
+        VAR_INPUT value : INT; factor : INT := 10; END_VAR
+        VAR result : DINT; END_VAR
+        result := value * factor;
+        IF result > 1000 THEN
+            scale := 1000;
+            RETURN;
+        END_IF;
+        scale := result;
+    END_FUNCTION
+    FUNCTION sum : INT
+        VAR_INPUT a, b : INT; END_VAR
+        sum := a + b;
+    END_FUNCTION
+    PROGRAM main
+        VAR x, y, z : DINT; END_VAR
+        x := scale(3, 2);
+        y := scale(value := sum(4, 5));
+        z := scale(factor := 500, value := sum(b := 1, a := 2));
+    END_PROGRAM";
+    let interpreter = interpret(text);
+
+    let scope = &interpreter.global_scope;
+    assert_eq!(*scope.get("x").unwrap(), Object::DInt(6));
+    assert_eq!(*scope.get("y").unwrap(), Object::DInt(90));
+    assert_eq!(*scope.get("z").unwrap(), Object::DInt(1000));
+    assert_eq!(scope.get("result"), None);
+}
+
+#[test]
+fn interpret_function_call_errors() {
+    let function = "FUNCTION f : INT
+        VAR_INPUT a : INT; END_VAR
+        f := g(a);
+    END_FUNCTION
+    FUNCTION g : INT
+        VAR_INPUT a : INT; END_VAR
+        IF a > 0 THEN g := f(a - 1); END_IF;
+    END_FUNCTION";
+    let errors = [
+        ("x := f(1, 2);", "f expects 1 arguments, found 2"),
+        ("x := f(b := 2);", "f has no input named b"),
+        ("x := f(a := 1, a := 2);", "input a of f is assigned twice"),
+        ("x := h(1);", "unknown function h"),
+        ("x := f(1);", "recursive call of f"),
+        ("x := a;", "variable a is not declared"),
+    ];
+    for (statement, expected) in errors.iter() {
+        let text = format!(
+            "{} PROGRAM main VAR x : INT; END_VAR {} END_PROGRAM",
+            function, statement
+        );
+        match run(&text).1 {
+            Err(error::Error::Runtime(error, _)) => assert_eq!(error.to_string(), *expected),
+            result => panic!(
+                "Expected runtime error for {}, found {:?}",
+                statement, result
+            ),
+        }
+    }
+}
+
+#[test]
+fn interpret_function_blocks() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let text = "FUNCTION_BLOCK Counter
+        VAR_INPUT enable : BOOL; END_VAR
+        VAR_OUTPUT count : INT; END_VAR
+        IF enable THEN count := count + 1; END_IF;
+    END_FUNCTION_BLOCK
+    FUNCTION_BLOCK MotorCtrl
+        VAR_INPUT start, stop : BOOL; END_VAR
+        VAR_OUTPUT running : BOOL; starts : INT; END_VAR
+        VAR edges : Counter; was_running : BOOL; END_VAR
+        running := (running OR start) AND NOT stop;
+        edges(enable := running AND NOT was_running);
+        was_running := running;
+        starts := edges.count;
+    END_FUNCTION_BLOCK
+    PROGRAM main
+        VAR motor1, motor2 : MotorCtrl; a, b : BOOL; n : INT; END_VAR
+        motor1(start := TRUE);
+        motor1(start := FALSE);
+        a := motor1.running;
+        motor2.start := TRUE;
+        motor2();
+        motor2(stop := TRUE);
+        motor2(stop := FALSE);
+        motor2();
+        b := motor2.running;
+        n := motor2.starts;
+    END_PROGRAM";
+    let interpreter = interpret(text);
+
+    let scope = &interpreter.global_scope;
+    assert_eq!(*scope.get("a").unwrap(), Object::Bool(true));
+    assert_eq!(*scope.get("b").unwrap(), Object::Bool(true));
+    assert_eq!(*scope.get("n").unwrap(), Object::Int(2));
+}
+
+#[test]
+fn interpret_function_block_member_errors() {
+    let function_block = "FUNCTION_BLOCK Fb
+        VAR_INPUT i : INT; END_VAR
+        VAR_OUTPUT q : INT; END_VAR
+        VAR state : INT; END_VAR
+        q := i;
+    END_FUNCTION_BLOCK";
+    let errors = [
+        ("x := fb.state;", "state is an internal variable of Fb"),
+        ("fb.q := 1;", "output q of Fb cannot be assigned"),
+        ("x := fb.r;", "Fb has no member r"),
+        ("x := x.q;", "INT has no member q"),
+        (
+            "x := fb(i := 1);",
+            "cannot assign Fb to a variable of type INT",
+        ),
+    ];
+    for (statement, expected) in errors.iter() {
+        let text = format!(
+            "{} PROGRAM main VAR fb : Fb; x : INT; END_VAR {} END_PROGRAM",
+            function_block, statement
+        );
+        match run(&text).1 {
+            Err(error::Error::Runtime(error, _)) => assert_eq!(error.to_string(), *expected),
+            result => panic!(
+                "Expected runtime error for {}, found {:?}",
+                statement, result
+            ),
+        }
+    }
+}
+
+#[test]
+fn interpret_standard_timers() {
+    let text = "PROGRAM main
+        VAR t : TON; p : TP; q, pulse, started : BOOL; et : TIME; END_VAR
+        t(IN := TRUE, PT := T#5s);
+        q := t.Q;
+        et := t.ET;
+        started := t.ET < T#1s;
+        p(IN := TRUE, PT := T#1s);
+        pulse := p.Q;
+    END_PROGRAM"
+        .to_string();
+    let clock = clock::VirtualClock::new();
+    clock.advance(std::time::Duration::from_secs(3));
+    let parser = Parser::new(Lexer::new(text));
+    let mut interpreter = Interpreter::with_clock(parser, Box::new(clock));
+    interpreter.interpreter_writer(&mut Vec::new()).unwrap();
+    assert_eq!(interpreter.global_scope["q"], Object::Bool(false));
+    assert_eq!(interpreter.global_scope["et"], Object::Time(0));
+    assert_eq!(interpreter.global_scope["started"], Object::Bool(true));
+    assert_eq!(interpreter.global_scope["pulse"], Object::Bool(true));
+
+    assert!(matches!(
+        run("FUNCTION_BLOCK TON END_FUNCTION_BLOCK").1,
+        Err(error::Error::Runtime(
+            error::RuntimeError::DuplicateDeclaration(_),
+            _
+        ))
+    ));
+}
+
+#[test]
+fn interpret_standard_counters() {
+    let text = "PROGRAM main
+        VAR counter : CTU; edge : F_TRIG; latch : RS; i, falls : INT; done : BOOL; END_VAR
+        FOR i := 1 TO 7 DO
+            counter(CU := i MOD 2 = 0, PV := 3);
+            edge(CLK := i MOD 3 = 0);
+            IF edge.Q THEN falls := falls + 1; END_IF;
+        END_FOR;
+        latch(S := counter.Q, R1 := FALSE);
+        latch(S := FALSE, R1 := FALSE);
+        done := latch.Q1;
+    END_PROGRAM";
+    let interpreter = interpret(text);
+    let scope = &interpreter.global_scope;
+    match &scope["counter"] {
+        Object::Instance(instance) => assert_eq!(instance.vars["CV"], Object::Int(3)),
+        object => panic!("Expected CTU instance, found {:?}", object),
+    }
+    // F_TRIG also reports the first call, where CLK starts FALSE.
+    assert_eq!(scope["falls"], Object::Int(3));
+    assert_eq!(scope["done"], Object::Bool(true));
+}
+
+#[test]
+fn interpret_standard_functions() {
+    let text = "PROGRAM main
+        VAR r : REAL; l, e : LREAL; x, y, z : INT; b : BOOL; END_VAR
+        r := 2.25;
+        r := SQRT(r);
+        l := EXPT(2.0, 10);
+        e := EXPT(2, 3) + 2 ** 3;
+        x := LIMIT(MN := 0, IN := 150, MX := 100);
+        y := MUX(2, 10, 20, 30) + ADD(1, 2, 3, 4);
+        z := MAX(IN1 := -3, IN3 := ABS(-7), IN2 := 5);
+        b := GT(5, 3, 1) AND NOT EQ(1, 1, 2);
+    END_PROGRAM";
+    let interpreter = interpret(text);
+    let scope = &interpreter.global_scope;
+    assert_eq!(scope["r"], Object::Real(1.5));
+    assert_eq!(scope["l"], Object::LReal(1024.0));
+    assert_eq!(scope["e"], Object::LReal(16.0));
+    assert_eq!(scope["x"], Object::Int(100));
+    assert_eq!(scope["y"], Object::Int(40));
+    assert_eq!(scope["z"], Object::Int(7));
+    assert_eq!(scope["b"], Object::Bool(true));
+
+    let errors = [
+        (
+            "x := MUX(5, 1, 2);",
+            "5 is out of range for K of MUX (0..1)",
+        ),
+        (
+            "x := LIMIT(MN := 0, IN := 1);",
+            "input MX of LIMIT is not assigned",
+        ),
+        ("x := ABS(1, 2);", "ABS expects 1 arguments, found 2"),
+        (
+            "x := ABS(INT#-32768);",
+            "ABS(-32768) is out of range for INT",
+        ),
+        (
+            "x := SEL(G := TRUE, IN2 := 1);",
+            "SEL has no input named IN2",
+        ),
+        ("r := LN(1);", "input of LN must be ANY_REAL, found DINT"),
+    ];
+    for (statement, expected) in errors.iter() {
+        let text = format!(
+            "PROGRAM main VAR x : INT; r : REAL; END_VAR {} END_PROGRAM",
+            statement
+        );
+        match run(&text).1 {
+            Err(error::Error::Runtime(error, _)) => assert_eq!(error.to_string(), *expected),
+            result => panic!(
+                "Expected runtime error for {}, found {:?}",
+                statement, result
+            ),
+        }
+    }
+}
+
+#[test]
+fn interpret_conversions() {
+    let text = "PROGRAM main
+        VAR i : INT; r : REAL; d : DINT; w : WORD; s : SINT; END_VAR
+        i := 7;
+        r := INT_TO_REAL(i) / 2.0;
+        d := REAL_TO_DINT(r) + TRUNC(r);
+        w := INT_TO_BCD(TO_INT(d * 3));
+        i := WORD_TO_INT(w);
+        s := INT_TO_SINT(i * 10);
+    END_PROGRAM";
+    let (interpreter, error) = interpret_error(text);
+    assert_eq!(error, "Runtime error at 8:14: 330 is out of range for SINT");
+    let scope = &interpreter.global_scope;
+    assert_eq!(scope["r"], Object::Real(3.5));
+    assert_eq!(scope["d"], Object::DInt(7));
+    assert_eq!(scope["w"], Object::Word(0x21));
+    assert_eq!(scope["i"], Object::Int(33));
+
+    let text = "PROGRAM main
+        VAR i : INT; d : DINT; t : TIME; s : STRING; w : WORD; r : REAL; END_VAR
+        d := TIME_TO_DINT(T#1m);
+        t := DINT_TO_TIME(d / 2);
+        s := CONCAT(INT_TO_STRING(-5), ' ', TIME_TO_STRING(t));
+        i := STRING_TO_INT('123') + BCD_TO_INT(16#12);
+        w := WORD_TO_BCD(WORD#1234);
+        r := LREAL_TO_REAL(1.0E300);
+    END_PROGRAM";
+    let (interpreter, error) = interpret_error(text);
+    assert_eq!(
+        error,
+        "Runtime error at 8:14: 1e300 is out of range for REAL"
+    );
+    let scope = &interpreter.global_scope;
+    assert_eq!(scope["d"], Object::DInt(60000));
+    assert_eq!(scope["t"], Object::Time(30_000_000_000));
+    assert_eq!(scope["s"].to_string(), "-5 T#30s");
+    assert_eq!(scope["i"], Object::Int(135));
+    assert_eq!(scope["w"], Object::Word(0x1234));
+    assert_eq!(
+        interpret_error("PROGRAM main VAR i : INT; END_VAR i := BCD_TO_INT(16#1A); END_PROGRAM").1,
+        "Runtime error at 1:40: 16#1A is out of range for BCD"
+    );
+
+    for (expression, message) in [
+        ("INT_TO_REAL(100000)", "100000 is out of range for INT"),
+        ("SINT_TO_INT(200)", "200 is out of range for SINT"),
+        ("UINT_TO_DINT(-1)", "-1 is out of range for UINT"),
+        ("INT_TO_BCD(40000)", "40000 is out of range for INT"),
+        ("REAL_TRUNC_INT(1.0E39)", "1e39 is out of range for REAL"),
+    ] {
+        let text = format!(
+            "PROGRAM main VAR x : LREAL; END_VAR x := {}; END_PROGRAM",
+            expression
+        );
+        assert_eq!(
+            interpret_error(&text).1,
+            format!("Runtime error at 1:42: {}", message)
+        );
+    }
+}
+
+#[test]
+fn interpret_strings() {
+    let text = "PROGRAM main
+        VAR
+            short : STRING[5] := 'Hello, world';
+            s : STRING;
+            w : WSTRING := \"$00C4pfel\";
+            position : INT;
+            same : BOOL;
+        END_VAR
+        s := CONCAT(short, '$'s ', MID('xworldx', 5, 2));
+        position := FIND(s, 'world');
+        same := LEFT(IN := s, L := 5) = short;
+        w := REPLACE(w, \"A\", 1, 1);
+        s := INSERT(s, w, 0);
+    END_PROGRAM";
+    let (interpreter, error) = interpret_error(text);
+    assert_eq!(
+        error,
+        "Runtime error at 13:14: INSERT is not defined for types STRING and WSTRING"
+    );
+    let scope = &interpreter.global_scope;
+    assert_eq!(scope["short"], Object::String("Hello".to_string(), 5));
+    assert_eq!(scope["s"], Object::string("Hello's world".to_string()));
+    assert_eq!(scope["position"], Object::Int(9));
+    assert_eq!(scope["same"], Object::Bool(true));
+    assert_eq!(scope["w"], Object::wstring("Apfel".to_string()));
+}
+
+#[test]
+fn interpret_dates_and_times() {
+    let text = "PROGRAM main
+        VAR
+            start : DT := DT#2024-01-31-22:30:00;
+            finish : DT;
+            day : DATE;
+            shift : TIME_OF_DAY := TOD#22:00:00;
+            elapsed : TIME;
+            late : BOOL;
+        END_VAR
+        finish := start + T#2h * 2;
+        day := DT_TO_DATE(finish);
+        elapsed := finish - CONCAT_DATE_TOD(D#2024-01-31, shift);
+        shift := shift + elapsed / 2;
+        late := elapsed > T#4h;
+        day := day - T#1d;
+    END_PROGRAM";
+    let (interpreter, error) = interpret_error(text);
+    assert_eq!(
+        error,
+        "Runtime error at 15:16: Subtraction is not defined for types DATE and TIME"
+    );
+    let scope = &interpreter.global_scope;
+    let text = |name: &str| scope[name].to_string();
+    assert_eq!(text("finish"), "DT#2024-02-01-02:30:00");
+    assert_eq!(text("day"), "D#2024-02-01");
+    assert_eq!(text("elapsed"), "T#4h30m");
+    assert_eq!(text("shift"), "TOD#00:15:00");
+    assert_eq!(scope["late"], Object::Bool(true));
+}
+
+#[test]
+fn interpret_time_overflow() {
+    let errors = [
+        (
+            "t := T#100000d * 1000;",
+            "T#100000d * 1000 is out of range for TIME",
+        ),
+        (
+            "t := T#100000d * 1.5;",
+            "T#100000d * 1.5 is out of range for TIME",
+        ),
+        (
+            "t := T#100000d + T#100000d;",
+            "T#100000d + T#100000d is out of range for TIME",
+        ),
+        (
+            "t := T#-100000d - T#100000d;",
+            "T#-100000d - T#100000d is out of range for TIME",
+        ),
+        (
+            "d := DT#2024-01-01-00:00:00 + T#100000d;",
+            "DT#2024-01-01-00:00:00 + T#100000d is out of range for DATE_AND_TIME",
+        ),
+    ];
+    for (statement, expected) in errors.iter() {
+        let text = format!(
+            "PROGRAM main VAR t : TIME; d : DT; END_VAR {} END_PROGRAM",
+            statement
+        );
+        let (_, error) = interpret_error(&text);
+        assert_eq!(error, format!("Runtime error at 1:49: {}", expected));
+    }
+}
+
+#[test]
+fn interpret_arrays() {
+    let text = "PROGRAM main
+        VAR
+            grid : ARRAY[1..3, 0..1] OF INT;
+            copy : ARRAY[1..3, 0..1] OF INT;
+            timers : ARRAY[0..1] OF TON;
+            i : INT;
+            sum : INT;
+        END_VAR
+        FOR i := 1 TO 3 DO
+            grid[i, 0] := i;
+            grid[i, 1] := grid[i, 0] * 10;
+        END_FOR;
+        copy := grid;
+        grid[2, 1] := 0;
+        timers[1].PT := T#5s;
+        FOR i := 1 TO 3 DO
+            sum := sum + copy[i, 1];
+        END_FOR;
+        sum := sum + grid[i, 0];
+    END_PROGRAM";
+    let (interpreter, error) = interpret_error(text);
+    assert_eq!(
+        error,
+        "Runtime error at 19:22: index 4 is outside the array bounds 1..3"
+    );
+    let scope = &interpreter.global_scope;
+    assert_eq!(scope["sum"], Object::Int(60));
+    assert_eq!(scope["grid"].to_string(), "[1, 10, 2, 0, 3, 30]");
+    assert_eq!(scope["copy"].to_string(), "[1, 10, 2, 20, 3, 30]");
+    match &scope["timers"] {
+        Object::Array(array) => match &array.elements[1] {
+            Object::Instance(instance) => {
+                assert_eq!(instance.vars["PT"], Object::Time(5_000_000_000))
+            }
+            element => panic!("Expected a TON instance, found {:?}", element),
+        },
+        value => panic!("Expected an array, found {:?}", value),
+    }
+    assert_eq!(
+        interpret_error(
+            "PROGRAM main VAR a : ARRAY[1..1000000, 1..1000000] OF INT; END_VAR END_PROGRAM"
+        )
+        .1,
+        "Parse error at 1:22: ARRAY[1..1000000, 1..1000000] has more than 1048576 elements"
+    );
+    assert_eq!(
+        interpret_error("PROGRAM main VAR i : INT; END_VAR i := i[1] + 1; END_PROGRAM").1,
+        "Runtime error at 1:40: Indexing is not defined for type INT"
+    );
+    let text = "TYPE Axis : STRUCT timer : TON; END_STRUCT; END_TYPE
+        PROGRAM main
+        VAR t : ARRAY[0..1] OF TON; axes : ARRAY[1..2] OF Axis; i : INT; q : BOOL; END_VAR
+        t[0](IN := TRUE, PT := T#0s);
+        FOR i := 1 TO 2 DO
+            axes[i].timer(IN := i = 2, PT := T#0s);
+        END_FOR;
+        q := t[0].Q AND NOT t[1].Q AND axes[2].timer.Q AND NOT axes[1].timer.Q;
+        t[1].Q();
+    END_PROGRAM";
+    let (interpreter, error) = interpret_error(text);
+    assert_eq!(
+        error,
+        "Runtime error at 9:9: Calling is not defined for type BOOL"
+    );
+    assert_eq!(interpreter.global_scope["q"], Object::Bool(true));
+}
+
+#[test]
+fn interpret_structs() {
+    let text = "TYPE
+            Point : STRUCT x, y : INT; END_STRUCT;
+            Path : STRUCT
+                points : ARRAY[1..3] OF Point;
+                closed : BOOL := TRUE;
+            END_STRUCT;
+        END_TYPE
+        PROGRAM main
+        VAR
+            origin, p : Point;
+            path : Path;
+            i : INT;
+            sum : INT;
+        END_VAR
+        p.x := 3;
+        p.y := p.x * 2;
+        path.points[2] := p;
+        path.points[3].x := path.points[2].y + 1;
+        origin := path.points[1];
+        FOR i := 1 TO 3 DO
+            sum := sum + path.points[i].x;
+        END_FOR;
+        path.points[1] := path;
+    END_PROGRAM";
+    let (interpreter, error) = interpret_error(text);
+    assert_eq!(
+        error,
+        "Runtime error at 23:9: cannot assign Path to a variable of type Point"
+    );
+    let scope = &interpreter.global_scope;
+    assert_eq!(scope["sum"], Object::Int(10));
+    match &scope["path"] {
+        Object::Struct(path) => {
+            assert_eq!(path.members["closed"], Object::Bool(true));
+            match &path.members["points"] {
+                Object::Array(points) => assert_eq!(points.elements[1], scope["p"]),
+                points => panic!("Expected an array, found {:?}", points),
+            }
+        }
+        path => panic!("Expected a struct, found {:?}", path),
+    }
+
+    let text = "TYPE Node : STRUCT next : Node; END_STRUCT; END_TYPE
+        PROGRAM main VAR n : Node; END_VAR END_PROGRAM";
+    assert_eq!(
+        interpret_error(text).1,
+        "Runtime error at 1:20: type Node contains itself"
+    );
+}
+
+#[test]
+fn interpret_enums_and_subranges() {
+    let text = "TYPE
+            Mode : (Idle, Run, Fault);
+            Level : (Low := 1, High := 5) SINT;
+            Percent : INT (0..100);
+        END_TYPE
+        PROGRAM main
+        VAR
+            mode : Mode;
+            level : Level := High;
+            speed : Percent;
+            steps : ARRAY[1..2] OF USINT (1..10);
+            action, i : INT;
+        END_VAR
+        mode := Mode#Run;
+        CASE mode OF
+            Idle: action := 1;
+            Mode#Run: action := 2;
+            Fault: action := 3;
+        END_CASE;
+        IF level = Level#High AND mode <> Idle THEN
+            speed := 100;
+        END_IF;
+        steps[2] := 10;
+        FOR i := 0 TO 100 BY 50 DO
+            speed := i;
+        END_FOR;
+        speed := speed + 1;
+    END_PROGRAM";
+    let (interpreter, error) = interpret_error(text);
+    assert_eq!(
+        error,
+        "Runtime error at 27:9: 101 is out of range for INT (0..100)"
+    );
+    let scope = &interpreter.global_scope;
+    assert_eq!(scope["mode"].to_string(), "Mode#Run");
+    assert_eq!(scope["action"], Object::Int(2));
+    assert_eq!(scope["speed"].operand(), Object::Int(100));
+    assert_eq!(scope["steps"].to_string(), "[1, 10]");
+
+    for (statement, message) in [
+        (
+            "mode := 1;",
+            "Runtime error at 3:13: cannot assign DINT to a variable of type Mode",
+        ),
+        (
+            "mode := Mode#Stop;",
+            "Runtime error at 3:21: Mode has no value Stop",
+        ),
+        (
+            "CASE mode OF 0: mode := Run; END_CASE;",
+            "Runtime error at 3:26: CASE is not defined for types Mode and DINT",
+        ),
+        (
+            "CASE mode OF Idle: mode := Run; Mode#IDLE: mode := Idle; END_CASE;",
+            "Runtime error at 3:45: duplicate CASE label Mode#Idle",
+        ),
+        (
+            "CASE level OF Low: mode := Run; Level#Normal: mode := Idle; END_CASE;",
+            "Runtime error at 3:45: duplicate CASE label Level#Normal",
+        ),
+        (
+            "CASE mode OF Idle: mode := Run; Other#Idle: mode := Idle; END_CASE;",
+            "Runtime error at 3:45: CASE is not defined for types Mode and Other",
+        ),
+    ] {
+        let text = format!(
+            "TYPE Mode : (Idle, Run); Other : (Idle, Busy); Level : (Low := 1, Normal := 1); END_TYPE
+            PROGRAM main VAR mode : Mode; level : Level; END_VAR
+            {}
+            END_PROGRAM",
+            statement
+        );
+        assert_eq!(interpret_error(&text).1, message);
+    }
+}
+
+#[test]
+fn interpret_case_insensitive_identifiers() {
+    let text = "type Point : struct X, Y : dint; end_struct; end_type
+        function Twice : Int var_input Value : INT; end_var twice := value * 2; end_function
+        Program Main
+        Var
+            Count : int := 1;
+            p : point;
+            edge : r_trig;
+            total : Dint;
+        End_Var
+        count := TWICE(VALUE := COUNT);
+        P.x := count + abs(-3) + max(in1 := -1, In2 := 0, in3 := -2);
+        Edge(clk := true);
+        If EDGE.q Then total := p.X; End_If;
+        for COUNT := 1 to 3 do total := TOTAL + 1; end_for;
+    End_Program";
+    let interpreter = interpret(text);
+    let scope = &interpreter.global_scope;
+    assert_eq!(scope["COUNT"], Object::Int(4));
+    assert_eq!(scope["total"], Object::DInt(8));
+    assert!(scope.iter().any(|(name, _)| name == "Count"));
+    match &scope["p"] {
+        Object::Struct(p) => assert_eq!(p.members["x"], Object::DInt(5)),
+        p => panic!("Expected a struct, found {:?}", p),
+    }
+
+    let text = "PROGRAM main VAR count : INT; COUNT : INT; END_VAR END_PROGRAM";
+    assert_eq!(
+        interpret_error(text).1,
+        "Runtime error at 1:31: COUNT is declared more than once"
+    );
+    let text = "TYPE Point : STRUCT x : INT; X : INT; END_STRUCT; END_TYPE
+        PROGRAM main VAR p : Point; END_VAR END_PROGRAM";
+    assert_eq!(
+        interpret_error(text).1,
+        "Runtime error at 1:30: X is declared more than once"
+    );
+    let text = "FUNCTION f : INT f := 1; END_FUNCTION FUNCTION F : INT F := 2; END_FUNCTION";
+    assert_eq!(
+        interpret_error(text).1,
+        "Runtime error at 1:39: F is declared more than once"
+    );
+    let text = "FUNCTION abs : INT VAR_INPUT x : INT; END_VAR abs := 42; END_FUNCTION";
+    assert_eq!(
+        interpret_error(text).1,
+        "Runtime error at 1:1: abs is declared more than once"
+    );
+}
+
+#[test]
+fn interpret_scan_cycles() {
+    let text = "PROGRAM main
+        VAR count : INT := 10; timer : TON; done : BOOL; cycles : INT; END_VAR
+        count := count + 1;
+        timer(IN := TRUE, PT := T#50ms);
+        IF timer.Q AND NOT done THEN
+            done := TRUE;
+            cycles := count - 10;
+        END_IF;
+    END_PROGRAM"
+        .to_string();
+    let clock = clock::VirtualClock::new();
+    let parser = Parser::new(Lexer::new(text.clone()));
+    let mut interpreter = Interpreter::with_clock(parser, Box::new(clock.clone()));
+
+    interpreter.load().unwrap();
+    assert_eq!(interpreter.global_scope["count"], Object::Int(10));
+    interpreter
+        .run_cycles(3, Duration::from_millis(10))
+        .unwrap();
+    assert_eq!(interpreter.global_scope["count"], Object::Int(13));
+    let cycles = interpreter.run_for(Duration::from_millis(95), Duration::from_millis(10));
+    assert_eq!(cycles.unwrap(), 10);
+    let scope = &interpreter.global_scope;
+    assert_eq!(scope["count"], Object::Int(23));
+    assert_eq!(scope["done"], Object::Bool(true));
+    // The timer starts in the first cycle and expires 50ms, 5 periods, later.
+    assert_eq!(scope["cycles"], Object::Int(6));
+    assert_eq!(clock::Clock::now(&clock), Duration::from_millis(130));
+
+    let parser = Parser::new(Lexer::new(text.clone()));
+    let mut interpreter = Interpreter::with_clock(parser, Box::new(clock));
+    interpreter.load().unwrap();
+    let cycles = interpreter.run_until(Duration::from_millis(1), |interpreter, _| {
+        interpreter.global_scope["done"] == Object::Bool(true)
+    });
+    assert_eq!(cycles.unwrap(), 51);
+
+    let parser = Parser::new(Lexer::new(text));
+    let mut interpreter = Interpreter::with_clock(parser, Box::new(clock::VirtualClock::new()));
+    interpreter.load().unwrap();
+    interpreter.run_cycles(2, Duration::ZERO).unwrap();
+    assert_eq!(interpreter.global_scope["count"], Object::Int(12));
+    match interpreter.run_for(Duration::from_secs(1), Duration::ZERO) {
+        Err(error) => assert_eq!(
+            error.to_string(),
+            "Runtime error at 1:1: a task with a period of 0s stops the clock"
+        ),
+        result => panic!("Expected runtime error, found {:?}", result),
+    }
+
+    assert_eq!(
+        load_error("1 + 2"),
+        "Runtime error at 1:1: no PROGRAM to run"
+    );
+}
+
+#[test]
+fn parse_cycle_options() {
+    let args = |text: &str| -> Vec<String> { text.split(' ').map(String::from).collect() };
+    assert_eq!(
+        CycleOptions::parse(&args("--cycles 100 --period 10ms")),
+        Some(CycleOptions {
+            cycles: Some(100),
+            duration: None,
+            period: Duration::from_millis(10),
+            simulate: false,
+        })
+    );
+    assert_eq!(
+        CycleOptions::parse(&args("--duration 1m30s --period 1s --simulate")),
+        Some(CycleOptions {
+            cycles: None,
+            duration: Some(Duration::from_secs(90)),
+            period: Duration::from_secs(1),
+            simulate: true,
+        })
+    );
+    for text in [
+        "--cycles",
+        "--cycles -1",
+        "--period 10",
+        "--period -5ms",
+        "--cycles 10 --period 0ms",
+        "--duration 1s --simulate",
+        "--simulate",
+        "--cycles 1 --duration 1s",
+        "--speed 2",
+    ] {
+        assert_eq!(CycleOptions::parse(&args(text)), None, "{}", text);
+    }
+}
+
+#[test]
+fn interpret_configuration() {
+    let text = "CONFIGURATION plant
+        RESOURCE cpu ON PLC
+            TASK fast(INTERVAL := T#10ms, PRIORITY := 1);
+            TASK slow(INTERVAL := T#25ms, PRIORITY := 0);
+            PROGRAM p1 WITH fast : Counter;
+            PROGRAM p2 WITH slow : Counter;
+            PROGRAM background : Counter;
+        END_RESOURCE
+    END_CONFIGURATION
+    PROGRAM Counter
+        VAR n : INT; END_VAR
+        n := n + 1;
+    END_PROGRAM"
+        .to_string();
+    let clock = clock::VirtualClock::new();
+    let parser = Parser::new(Lexer::new(text.clone()));
+    let mut interpreter = Interpreter::with_clock(parser, Box::new(clock.clone()));
+    interpreter.load().unwrap();
+
+    let period = Duration::from_millis(5);
+    let mut order = Vec::new();
+    for step in [0, 5, 5, 10, 5, 25] {
+        clock.advance(Duration::from_millis(step));
+        order.push(interpreter.tick(period).unwrap().join(" "));
+    }
+    assert_eq!(
+        order,
+        vec![
+            "p2 p1 background",
+            "background",
+            "p1 background",
+            "p1 background",
+            "p2 background",
+            // Missed releases are skipped.
+            "p2 p1 background",
+        ]
+    );
+    let count = |interpreter: &Interpreter, name: &str| match &interpreter.global_scope[name] {
+        Object::Instance(instance) => instance.vars["n"].clone(),
+        object => panic!("Expected a program instance, found {:?}", object),
+    };
+    assert_eq!(count(&interpreter, "p1"), Object::Int(4));
+    assert_eq!(count(&interpreter, "p2"), Object::Int(3));
+
+    let parser = Parser::new(Lexer::new(text));
+    let mut interpreter = Interpreter::with_clock(parser, Box::new(clock::VirtualClock::new()));
+    interpreter.load().unwrap();
+    let cycles = interpreter.run_for(Duration::from_millis(100), Duration::from_millis(50));
+    // Releases of fast at 0, 10, ... 90, slow at 25 and 75, the rest at 50.
+    assert_eq!(cycles.unwrap(), 12);
+    assert_eq!(count(&interpreter, "p1"), Object::Int(10));
+    assert_eq!(count(&interpreter, "p2"), Object::Int(4));
+    assert_eq!(count(&interpreter, "background"), Object::Int(2));
+
+    for (resource, message) in [
+        (
+            "TASK t(INTERVAL := 5); PROGRAM p WITH t : Counter;",
+            "Runtime error at 2:59: task INTERVAL must be TIME, found DINT",
+        ),
+        (
+            "TASK t(PRIORITY := 1, SINGLE := TRUE); PROGRAM p WITH t : Counter;",
+            "Runtime error at 2:45: t has no input named SINGLE",
+        ),
+        (
+            "PROGRAM p WITH t : Counter;",
+            "Runtime error at 2:48: unknown task t",
+        ),
+        (
+            "PROGRAM p : Counter; PROGRAM P : Counter;",
+            "Runtime error at 2:69: P is declared more than once",
+        ),
+    ] {
+        let text = format!(
+            "PROGRAM Counter VAR n : INT; END_VAR n := n + 1; END_PROGRAM
+            CONFIGURATION c RESOURCE r {} END_RESOURCE END_CONFIGURATION",
+            resource
+        );
+        assert_eq!(load_error(&text), message);
+    }
+}
+
+#[test]
+fn interpret_process_image() {
+    use image::Address;
+    use object::DataType;
+
+    let text = "PROGRAM main
+        VAR
+            start AT %IX0.0 : BOOL;
+            stop AT %IX0.1 : BOOL;
+            setpoint AT %IW2 : INT;
+            running AT %QX0.0 : BOOL;
+            speed AT %QW4 : INT;
+            starts AT %MD8 : UDINT;
+            level AT %QD12 : REAL := 0.5;
+        END_VAR
+        running := (running OR start) AND NOT stop;
+        IF running THEN
+            speed := setpoint;
+        ELSE
+            speed := 0;
+        END_IF;
+        IF start THEN
+            starts := starts + 1;
+        END_IF;
+    END_PROGRAM"
+        .to_string();
+    let address = |text| Address::parse(text).unwrap();
+    let clock = clock::VirtualClock::new();
+    let parser = Parser::new(Lexer::new(text));
+    let mut interpreter = Interpreter::with_clock(parser, Box::new(clock.clone()));
+    interpreter.load().unwrap();
+    let period = Duration::from_millis(10);
+
+    let image = &mut interpreter.image;
+    image.set(&address("%IX0.0"), &Object::Bool(true)).unwrap();
+    image.set(&address("%IW2"), &Object::Int(1500)).unwrap();
+    image.set(&address("%MD8"), &Object::UDInt(41)).unwrap();
+    interpreter.tick(period).unwrap();
+    let image = &interpreter.image;
+    assert_eq!(
+        image.get(&address("%QX0.0"), &DataType::Bool),
+        Object::Bool(true)
+    );
+    assert_eq!(
+        image.get(&address("%QW4"), &DataType::Int),
+        Object::Int(1500)
+    );
+    assert_eq!(
+        image.get(&address("%MD8"), &DataType::UDInt),
+        Object::UDInt(42)
+    );
+    assert_eq!(
+        image.get(&address("%QD12"), &DataType::Real),
+        Object::Real(0.5)
+    );
+
+    // Outputs keep their values while the inputs are unchanged.
+    interpreter
+        .image
+        .set(&address("%IX0.0"), &Object::Bool(false))
+        .unwrap();
+    clock.advance(period);
+    interpreter.tick(period).unwrap();
+    assert_eq!(
+        interpreter.image.get(&address("%QW4"), &DataType::Int),
+        Object::Int(1500)
+    );
+    interpreter
+        .image
+        .set(&address("%IX0.1"), &Object::Bool(true))
+        .unwrap();
+    clock.advance(period);
+    interpreter.tick(period).unwrap();
+    let image = &interpreter.image;
+    assert_eq!(
+        image.get(&address("%QX0.0"), &DataType::Bool),
+        Object::Bool(false)
+    );
+    assert_eq!(image.get(&address("%QW4"), &DataType::Int), Object::Int(0));
+    assert_eq!(
+        image.get(&address("%MD8"), &DataType::UDInt),
+        Object::UDInt(42)
+    );
 
-    assert_eq!(*interpreter.global_scope.get(&"x".to_string()).unwrap(), 2);
+    assert_eq!(
+        load_error("PROGRAM main VAR x AT %QW4 : BOOL; END_VAR END_PROGRAM"),
+        "Runtime error at 1:18: BOOL cannot be located at %QW4"
+    );
+    assert_eq!(
+        load_error(
+            "PROGRAM main VAR x AT %QW18446744073709551615 : WORD; END_VAR x := 1; END_PROGRAM"
+        ),
+        "Parse error at 1:23: invalid direct address %QW18446744073709551615"
+    );
 }
@@ -0,0 +1,1207 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::calendar::{self, NANOSECONDS_PER_DAY};
+use crate::error::RuntimeError;
+use crate::identifiers::Identifiers;
+
+/// The maximum length of a STRING or WSTRING declared without one.
+pub const DEFAULT_STRING_LENGTH: usize = 80;
+
+/// The maximum number of elements of an array.
+pub const MAX_ARRAY_ELEMENTS: usize = 1 << 20;
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum DataType {
+    Bool,
+    SInt,
+    Int,
+    DInt,
+    LInt,
+    USInt,
+    UInt,
+    UDInt,
+    ULInt,
+    Byte,
+    Word,
+    DWord,
+    LWord,
+    Real,
+    LReal,
+    Time,
+    LTime,
+    Date,
+    TimeOfDay,
+    DateAndTime,
+    /// A single-byte string of at most the given number of characters.
+    String(usize),
+    /// A double-byte string of at most the given number of characters.
+    WString(usize),
+    Array(Box<ArrayType>),
+    /// A value of the named STRUCT type.
+    Struct(Box<str>),
+    /// A value of the named enumerated type.
+    Enum(Box<str>),
+    /// An instance of the named function block type.
+    FunctionBlock(String),
+}
+
+/// The element type and the lower and upper bound of each dimension of an
+/// ARRAY type.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ArrayType {
+    pub element_type: DataType,
+    pub dimensions: Vec<(i128, i128)>,
+}
+
+impl DataType {
+    /// The elementary type named `name` in any case.
+    pub fn from_name(name: &str) -> Option<DataType> {
+        use DataType::*;
+        match name.to_ascii_uppercase().as_str() {
+            "BOOL" => Some(Bool),
+            "SINT" => Some(SInt),
+            "INT" => Some(Int),
+            "DINT" => Some(DInt),
+            "LINT" => Some(LInt),
+            "USINT" => Some(USInt),
+            "UINT" => Some(UInt),
+            "UDINT" => Some(UDInt),
+            "ULINT" => Some(ULInt),
+            "BYTE" => Some(Byte),
+            "WORD" => Some(Word),
+            "DWORD" => Some(DWord),
+            "LWORD" => Some(LWord),
+            "REAL" => Some(Real),
+            "LREAL" => Some(LReal),
+            "TIME" => Some(Time),
+            "LTIME" => Some(LTime),
+            "DATE" => Some(Date),
+            "TIME_OF_DAY" | "TOD" => Some(TimeOfDay),
+            "DATE_AND_TIME" | "DT" => Some(DateAndTime),
+            "STRING" => Some(String(DEFAULT_STRING_LENGTH)),
+            "WSTRING" => Some(WString(DEFAULT_STRING_LENGTH)),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        use DataType::*;
+        match self {
+            Bool => "BOOL",
+            SInt => "SINT",
+            Int => "INT",
+            DInt => "DINT",
+            LInt => "LINT",
+            USInt => "USINT",
+            UInt => "UINT",
+            UDInt => "UDINT",
+            ULInt => "ULINT",
+            Byte => "BYTE",
+            Word => "WORD",
+            DWord => "DWORD",
+            LWord => "LWORD",
+            Real => "REAL",
+            LReal => "LREAL",
+            Time => "TIME",
+            LTime => "LTIME",
+            Date => "DATE",
+            TimeOfDay => "TIME_OF_DAY",
+            DateAndTime => "DATE_AND_TIME",
+            String(_) => "STRING",
+            WString(_) => "WSTRING",
+            Array(..) => "ARRAY",
+            Struct(name) | Enum(name) => name,
+            FunctionBlock(name) => name,
+        }
+    }
+
+    /// Width of the type in bits, 0 for types that are not elementary.
+    pub fn width(&self) -> u32 {
+        use DataType::*;
+        match self {
+            Bool => 1,
+            SInt | USInt | Byte => 8,
+            Int | UInt | Word => 16,
+            DInt | UDInt | DWord | Real => 32,
+            LInt | ULInt | LWord | LReal => 64,
+            Time | LTime | Date | TimeOfDay | DateAndTime => 64,
+            String(_) | WString(_) | Array(..) | Struct(_) | Enum(_) | FunctionBlock(_) => 0,
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        use DataType::*;
+        matches!(self, SInt | Int | DInt | LInt | Real | LReal)
+    }
+
+    /// The smallest and largest value of an integer, bit string or BOOL type.
+    pub fn range(&self) -> (i128, i128) {
+        let width = self.width();
+        if self.is_signed() {
+            (-(1 << (width - 1)), (1 << (width - 1)) - 1)
+        } else {
+            (0, (1 << width) - 1)
+        }
+    }
+
+    /// ANY_INT
+    pub fn is_integer(&self) -> bool {
+        use DataType::*;
+        matches!(
+            self,
+            SInt | Int | DInt | LInt | USInt | UInt | UDInt | ULInt
+        )
+    }
+
+    /// ANY_REAL
+    pub fn is_real(&self) -> bool {
+        matches!(self, DataType::Real | DataType::LReal)
+    }
+
+    /// ANY_BIT, including BOOL
+    pub fn is_bit(&self) -> bool {
+        use DataType::*;
+        matches!(self, Bool | Byte | Word | DWord | LWord)
+    }
+
+    /// ANY_STRING
+    pub fn is_string(&self) -> bool {
+        matches!(self, DataType::String(_) | DataType::WString(_))
+    }
+
+    /// ANY_DURATION
+    pub fn is_duration(&self) -> bool {
+        matches!(self, DataType::Time | DataType::LTime)
+    }
+
+    /// ANY_DATE
+    pub fn is_date(&self) -> bool {
+        use DataType::*;
+        matches!(self, Date | TimeOfDay | DateAndTime)
+    }
+
+    /// ANY_NUM
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_real()
+    }
+
+    /// The initial value of an elementary type.
+    pub fn default_value(&self) -> Object {
+        match self {
+            _ if self.is_duration() || self.is_date() => Object::from_nanoseconds(0, self),
+            DataType::String(length) => Object::String(String::new(), *length),
+            DataType::WString(length) => Object::WString(String::new(), *length),
+            _ => Object::from_integer(0).convert(self.clone()),
+        }
+    }
+
+    /// The integer type with the given width and signedness.
+    fn integer(width: u32, signed: bool) -> DataType {
+        use DataType::*;
+        match (width, signed) {
+            (8, true) => SInt,
+            (16, true) => Int,
+            (32, true) => DInt,
+            (8, false) => USInt,
+            (16, false) => UInt,
+            (32, false) => UDInt,
+            (_, true) => LInt,
+            (_, false) => ULInt,
+        }
+    }
+
+    /// The type both operands of a binary arithmetic operation are widened to.
+    pub fn common_type(left: DataType, right: DataType) -> DataType {
+        if left == right {
+            left
+        } else if left == DataType::LReal || right == DataType::LReal {
+            DataType::LReal
+        } else if left.is_real() || right.is_real() {
+            DataType::Real
+        } else if left.is_signed() == right.is_signed() {
+            if left.width() >= right.width() {
+                left
+            } else {
+                right
+            }
+        } else {
+            let (signed, unsigned) = if left.is_signed() {
+                (left, right)
+            } else {
+                (right, left)
+            };
+            if signed.width() > unsigned.width() {
+                signed
+            } else {
+                DataType::integer(unsigned.width() * 2, true)
+            }
+        }
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataType::String(length) | DataType::WString(length)
+                if *length != DEFAULT_STRING_LENGTH =>
+            {
+                write!(f, "{}[{}]", self.name(), length)
+            }
+            DataType::Array(array_type) => {
+                write!(f, "ARRAY[")?;
+                for (index, (lower, upper)) in array_type.dimensions.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}..{}", lower, upper)?;
+                }
+                write!(f, "] OF {}", array_type.element_type)
+            }
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+/// The variables of a function block or program instance, kept between
+/// invocations.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Instance {
+    pub type_name: String,
+    pub vars: Identifiers<Object>,
+}
+
+impl Instance {
+    pub fn new(type_name: String, vars: Identifiers<Object>) -> Instance {
+        Instance { type_name, vars }
+    }
+}
+
+/// The members of a STRUCT value.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Struct {
+    pub type_name: String,
+    pub members: Identifiers<Object>,
+}
+
+impl Struct {
+    pub fn new(type_name: String, members: Identifiers<Object>) -> Struct {
+        Struct { type_name, members }
+    }
+}
+
+/// A value of an enumerated type and the integer it stands for.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Enumerated {
+    pub type_name: String,
+    pub name: String,
+    pub value: i128,
+}
+
+impl Enumerated {
+    pub fn new(type_name: String, name: String, value: i128) -> Enumerated {
+        Enumerated {
+            type_name,
+            name,
+            value,
+        }
+    }
+}
+
+/// A variable of a subrange type, which only ever holds integers from
+/// `lower` to `upper`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Subrange {
+    pub value: Box<Object>,
+    pub lower: i128,
+    pub upper: i128,
+}
+
+impl Subrange {
+    pub fn new(value: Object, lower: i128, upper: i128) -> Subrange {
+        Subrange {
+            value: Box::new(value),
+            lower,
+            upper,
+        }
+    }
+}
+
+/// The elements of an array, in row-major order.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Array {
+    pub element_type: DataType,
+    pub dimensions: Vec<(i128, i128)>,
+    pub elements: Vec<Object>,
+}
+
+impl Array {
+    /// An array with every element set to `element`. The dimensions must
+    /// have been checked with `element_count`.
+    pub fn new(element: Object, dimensions: Vec<(i128, i128)>) -> Array {
+        let count = match Array::element_count(&dimensions) {
+            Some(count) => count,
+            None => panic!("Array dimensions too large: {:?}", dimensions),
+        };
+        Array {
+            element_type: element.data_type(),
+            dimensions,
+            elements: vec![element; count],
+        }
+    }
+
+    /// The number of elements of an array with `dimensions`, `None` if it
+    /// exceeds `MAX_ARRAY_ELEMENTS`.
+    pub fn element_count(dimensions: &[(i128, i128)]) -> Option<usize> {
+        dimensions.iter().try_fold(1usize, |count, (lower, upper)| {
+            let length = usize::try_from(upper.checked_sub(*lower)?.checked_add(1)?).ok()?;
+            count
+                .checked_mul(length)
+                .filter(|count| *count <= MAX_ARRAY_ELEMENTS)
+        })
+    }
+
+    /// The position in `elements` of the element at `indices`.
+    pub fn offset(&self, indices: &[i128]) -> Result<usize, RuntimeError> {
+        if indices.len() != self.dimensions.len() {
+            return Err(RuntimeError::WrongIndexCount {
+                expected: self.dimensions.len(),
+                found: indices.len(),
+            });
+        }
+        let mut offset = 0;
+        for (&index, &(lower, upper)) in indices.iter().zip(&self.dimensions) {
+            if index < lower || index > upper {
+                return Err(RuntimeError::IndexOutOfBounds {
+                    index,
+                    lower,
+                    upper,
+                });
+            }
+            offset = offset * (upper - lower + 1) as usize + (index - lower) as usize;
+        }
+        Ok(offset)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Object {
+    Bool(bool),
+    SInt(i8),
+    Int(i16),
+    DInt(i32),
+    LInt(i64),
+    USInt(u8),
+    UInt(u16),
+    UDInt(u32),
+    ULInt(u64),
+    Byte(u8),
+    Word(u16),
+    DWord(u32),
+    LWord(u64),
+    Real(f32),
+    LReal(f64),
+    /// A duration in nanoseconds.
+    Time(i64),
+    LTime(i64),
+    /// Midnight at the start of a day, in nanoseconds since 1970-01-01.
+    Date(i64),
+    /// Nanoseconds since midnight.
+    TimeOfDay(i64),
+    /// Nanoseconds since 1970-01-01-00:00:00.
+    DateAndTime(i64),
+    /// A STRING value and the maximum length of its type.
+    String(String, usize),
+    /// A WSTRING value and the maximum length of its type.
+    WString(String, usize),
+    Array(Array),
+    Struct(Struct),
+    Enum(Enumerated),
+    /// Only stored in variables, expressions see the value of the base type,
+    /// see `operand`.
+    Subrange(Subrange),
+    Instance(Instance),
+}
+
+macro_rules! integer_op {
+    ($lhs:expr, $rhs:expr, $method:ident) => {
+        match ($lhs, $rhs) {
+            (Object::SInt(l), Object::SInt(r)) => Object::SInt(l.$method(r)),
+            (Object::Int(l), Object::Int(r)) => Object::Int(l.$method(r)),
+            (Object::DInt(l), Object::DInt(r)) => Object::DInt(l.$method(r)),
+            (Object::LInt(l), Object::LInt(r)) => Object::LInt(l.$method(r)),
+            (Object::USInt(l), Object::USInt(r)) => Object::USInt(l.$method(r)),
+            (Object::UInt(l), Object::UInt(r)) => Object::UInt(l.$method(r)),
+            (Object::UDInt(l), Object::UDInt(r)) => Object::UDInt(l.$method(r)),
+            (Object::ULInt(l), Object::ULInt(r)) => Object::ULInt(l.$method(r)),
+            _ => unreachable!(),
+        }
+    };
+}
+
+macro_rules! real_op {
+    ($lhs:expr, $rhs:expr, $op:tt) => {
+        match ($lhs, $rhs) {
+            (Object::Real(l), Object::Real(r)) => Object::Real(l $op r),
+            (Object::LReal(l), Object::LReal(r)) => Object::LReal(l $op r),
+            _ => unreachable!(),
+        }
+    };
+}
+
+impl Object {
+    /// An untyped integer literal, typed as the first of DINT, LINT and
+    /// ULINT it fits in.
+    pub fn from_integer(value: i128) -> Object {
+        let data_type = [DataType::DInt, DataType::LInt]
+            .iter()
+            .find(|data_type| {
+                let (min, max) = data_type.range();
+                value >= min && value <= max
+            })
+            .cloned()
+            .unwrap_or(DataType::ULInt);
+        Object::from_i128(value, data_type)
+    }
+
+    /// A duration, date or time of day of type `to`. A time of day wraps
+    /// around at midnight.
+    pub fn from_nanoseconds(value: i64, to: &DataType) -> Object {
+        match to {
+            DataType::Time => Object::Time(value),
+            DataType::LTime => Object::LTime(value),
+            DataType::Date => Object::Date(value),
+            DataType::TimeOfDay => Object::TimeOfDay(value.rem_euclid(NANOSECONDS_PER_DAY)),
+            DataType::DateAndTime => Object::DateAndTime(value),
+            _ => panic!("Cannot convert {} to {}", value, to),
+        }
+    }
+
+    /// The value of a duration, date or time of day object in nanoseconds.
+    pub fn as_nanoseconds(&self) -> Option<i64> {
+        match *self {
+            Object::Time(value)
+            | Object::LTime(value)
+            | Object::Date(value)
+            | Object::TimeOfDay(value)
+            | Object::DateAndTime(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// A STRING literal or function result, typed with the default maximum
+    /// length unless the value is longer.
+    pub fn string(value: String) -> Object {
+        let length = value.chars().count().max(DEFAULT_STRING_LENGTH);
+        Object::String(value, length)
+    }
+
+    pub fn wstring(value: String) -> Object {
+        let length = value.chars().count().max(DEFAULT_STRING_LENGTH);
+        Object::WString(value, length)
+    }
+
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Object::Bool(_) => DataType::Bool,
+            Object::SInt(_) => DataType::SInt,
+            Object::Int(_) => DataType::Int,
+            Object::DInt(_) => DataType::DInt,
+            Object::LInt(_) => DataType::LInt,
+            Object::USInt(_) => DataType::USInt,
+            Object::UInt(_) => DataType::UInt,
+            Object::UDInt(_) => DataType::UDInt,
+            Object::ULInt(_) => DataType::ULInt,
+            Object::Byte(_) => DataType::Byte,
+            Object::Word(_) => DataType::Word,
+            Object::DWord(_) => DataType::DWord,
+            Object::LWord(_) => DataType::LWord,
+            Object::Real(_) => DataType::Real,
+            Object::LReal(_) => DataType::LReal,
+            Object::Time(_) => DataType::Time,
+            Object::LTime(_) => DataType::LTime,
+            Object::Date(_) => DataType::Date,
+            Object::TimeOfDay(_) => DataType::TimeOfDay,
+            Object::DateAndTime(_) => DataType::DateAndTime,
+            Object::String(_, length) => DataType::String(*length),
+            Object::WString(_, length) => DataType::WString(*length),
+            Object::Array(array) => DataType::Array(Box::new(ArrayType {
+                element_type: array.element_type.clone(),
+                dimensions: array.dimensions.clone(),
+            })),
+            Object::Struct(value) => DataType::Struct(value.type_name.as_str().into()),
+            Object::Enum(value) => DataType::Enum(value.type_name.as_str().into()),
+            Object::Subrange(subrange) => subrange.value.data_type(),
+            Object::Instance(instance) => DataType::FunctionBlock(instance.type_name.clone()),
+        }
+    }
+
+    /// The value of an integer or bit string object.
+    pub fn as_i128(&self) -> Option<i128> {
+        match *self {
+            Object::Bool(value) => Some(value as i128),
+            Object::SInt(value) => Some(value.into()),
+            Object::Int(value) => Some(value.into()),
+            Object::DInt(value) => Some(value.into()),
+            Object::LInt(value) => Some(value.into()),
+            Object::USInt(value) | Object::Byte(value) => Some(value.into()),
+            Object::UInt(value) | Object::Word(value) => Some(value.into()),
+            Object::UDInt(value) | Object::DWord(value) => Some(value.into()),
+            Object::ULInt(value) | Object::LWord(value) => Some(value.into()),
+            Object::Subrange(ref subrange) => subrange.value.as_i128(),
+            Object::Real(_)
+            | Object::LReal(_)
+            | Object::Time(_)
+            | Object::LTime(_)
+            | Object::Date(_)
+            | Object::TimeOfDay(_)
+            | Object::DateAndTime(_)
+            | Object::String(..)
+            | Object::WString(..)
+            | Object::Array(_)
+            | Object::Struct(_)
+            | Object::Enum(_)
+            | Object::Instance(_) => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Object::Real(value) => Some(value.into()),
+            Object::LReal(value) => Some(value),
+            _ => self.as_i128().map(|value| value as f64),
+        }
+    }
+
+    /// Converts an elementary object to another elementary type. Integers and
+    /// bit strings wrap to the target width, reals are truncated towards zero.
+    pub fn convert(&self, to: DataType) -> Object {
+        if to.is_real() {
+            let value = self.as_f64().unwrap();
+            return match to {
+                DataType::Real => Object::Real(value as f32),
+                _ => Object::LReal(value),
+            };
+        }
+
+        let value = match self.as_i128() {
+            Some(value) => value,
+            None => self.as_f64().unwrap() as i128,
+        };
+        Object::from_i128(value, to)
+    }
+
+    /// An integer, bit string or BOOL object of type `to`, wrapping `value`
+    /// to the width of the type.
+    pub fn from_i128(value: i128, to: DataType) -> Object {
+        match to {
+            DataType::Bool => Object::Bool(value != 0),
+            DataType::SInt => Object::SInt(value as i8),
+            DataType::Int => Object::Int(value as i16),
+            DataType::DInt => Object::DInt(value as i32),
+            DataType::LInt => Object::LInt(value as i64),
+            DataType::USInt => Object::USInt(value as u8),
+            DataType::UInt => Object::UInt(value as u16),
+            DataType::UDInt => Object::UDInt(value as u32),
+            DataType::ULInt => Object::ULInt(value as u64),
+            DataType::Byte => Object::Byte(value as u8),
+            DataType::Word => Object::Word(value as u16),
+            DataType::DWord => Object::DWord(value as u32),
+            DataType::LWord => Object::LWord(value as u64),
+            DataType::Real
+            | DataType::LReal
+            | DataType::Time
+            | DataType::LTime
+            | DataType::Date
+            | DataType::TimeOfDay
+            | DataType::DateAndTime
+            | DataType::String(_)
+            | DataType::WString(_)
+            | DataType::Array(..)
+            | DataType::Struct(_)
+            | DataType::Enum(_)
+            | DataType::FunctionBlock(_) => panic!("Cannot convert {} to {}", value, to),
+        }
+    }
+
+    /// Converts a value for storage in a variable of type `to`, rejecting
+    /// conversions the standard does not allow implicitly.
+    pub fn assign_to(&self, to: DataType) -> Result<Object, RuntimeError> {
+        let from = self.data_type();
+        if from == to {
+            return Ok(self.clone());
+        }
+        match (self, &to) {
+            (Object::String(value, _), DataType::String(length)) => {
+                return Ok(Object::String(truncate(value, *length), *length))
+            }
+            (Object::WString(value, _), DataType::WString(length)) => {
+                return Ok(Object::WString(truncate(value, *length), *length))
+            }
+            _ => {}
+        }
+        let allowed = (to.is_real() && from.is_numeric())
+            || ((to.is_integer() || to.is_bit()) && to != DataType::Bool && from.is_integer())
+            || ((to.is_integer() || to.is_bit()) && from.is_bit() && from != DataType::Bool);
+        if !allowed {
+            return Err(RuntimeError::InvalidAssignment { from, to });
+        }
+        Ok(self.convert(to))
+    }
+
+    /// Stores `value` in the variable `self`, converting it to the type of
+    /// the variable. A subrange variable rejects values outside its bounds.
+    pub fn store(&mut self, value: Object) -> Result<(), RuntimeError> {
+        let value = value.assign_to(self.data_type())?;
+        match self {
+            Object::Subrange(subrange) => {
+                let integer = value.as_i128().unwrap();
+                if integer < subrange.lower || integer > subrange.upper {
+                    return Err(RuntimeError::OutOfRange {
+                        value: integer.to_string(),
+                        range: format!(
+                            "{} ({}..{})",
+                            value.data_type(),
+                            subrange.lower,
+                            subrange.upper
+                        ),
+                    });
+                }
+                *subrange.value = value;
+            }
+            _ => *self = value,
+        }
+        Ok(())
+    }
+
+    /// The value of a variable as an operand of an expression, the value of
+    /// the base type for a subrange variable.
+    pub fn operand(&self) -> Object {
+        match self {
+            Object::Subrange(subrange) => (*subrange.value).clone(),
+            _ => self.clone(),
+        }
+    }
+
+    fn promote(self, other: Object, operation: &str) -> Result<(Object, Object), RuntimeError> {
+        for operand in [&self, &other] {
+            if !operand.data_type().is_numeric() {
+                return Err(RuntimeError::InvalidOperand {
+                    operation: operation.to_string(),
+                    data_type: operand.data_type(),
+                });
+            }
+        }
+        let data_type = DataType::common_type(self.data_type(), other.data_type());
+        Ok((self.convert(data_type.clone()), other.convert(data_type)))
+    }
+
+    pub fn add(self, other: Object) -> Result<Object, RuntimeError> {
+        if self.as_nanoseconds().is_some() || other.as_nanoseconds().is_some() {
+            return self.add_time(other);
+        }
+        Ok(match self.promote(other, "Addition")? {
+            (lhs, rhs) if lhs.data_type().is_real() => real_op!(lhs, rhs, +),
+            (lhs, rhs) => integer_op!(lhs, rhs, wrapping_add),
+        })
+    }
+
+    pub fn sub(self, other: Object) -> Result<Object, RuntimeError> {
+        if self.as_nanoseconds().is_some() || other.as_nanoseconds().is_some() {
+            return self.sub_time(other);
+        }
+        Ok(match self.promote(other, "Subtraction")? {
+            (lhs, rhs) if lhs.data_type().is_real() => real_op!(lhs, rhs, -),
+            (lhs, rhs) => integer_op!(lhs, rhs, wrapping_sub),
+        })
+    }
+
+    pub fn mul(self, other: Object) -> Result<Object, RuntimeError> {
+        if other.as_nanoseconds().is_some() && self.data_type().is_numeric() {
+            return other.scale_time(self, "Multiplication");
+        }
+        if self.as_nanoseconds().is_some() || other.as_nanoseconds().is_some() {
+            return self.scale_time(other, "Multiplication");
+        }
+        Ok(match self.promote(other, "Multiplication")? {
+            (lhs, rhs) if lhs.data_type().is_real() => real_op!(lhs, rhs, *),
+            (lhs, rhs) => integer_op!(lhs, rhs, wrapping_mul),
+        })
+    }
+
+    pub fn div(self, other: Object) -> Result<Object, RuntimeError> {
+        if self.as_nanoseconds().is_some() || other.as_nanoseconds().is_some() {
+            return self.scale_time(other, "Division");
+        }
+        match self.promote(other, "Division")? {
+            (lhs, rhs) if lhs.data_type().is_real() => Ok(real_op!(lhs, rhs, /)),
+            (_, rhs) if rhs.as_i128() == Some(0) => Err(RuntimeError::DivisionByZero),
+            (lhs, rhs) => Ok(integer_op!(lhs, rhs, wrapping_div)),
+        }
+    }
+
+    pub fn neg(self) -> Result<Object, RuntimeError> {
+        match self {
+            Object::SInt(value) => Ok(Object::SInt(value.wrapping_neg())),
+            Object::Int(value) => Ok(Object::Int(value.wrapping_neg())),
+            Object::DInt(value) => Ok(Object::DInt(value.wrapping_neg())),
+            Object::LInt(value) => Ok(Object::LInt(value.wrapping_neg())),
+            Object::Real(value) => Ok(Object::Real(-value)),
+            Object::LReal(value) => Ok(Object::LReal(-value)),
+            Object::Time(value) => Ok(Object::Time(value.wrapping_neg())),
+            Object::LTime(value) => Ok(Object::LTime(value.wrapping_neg())),
+            _ => Err(RuntimeError::InvalidOperand {
+                operation: "Negation".to_string(),
+                data_type: self.data_type(),
+            }),
+        }
+    }
+
+    /// Adds a duration to a duration, time of day or date and time.
+    fn add_time(self, other: Object) -> Result<Object, RuntimeError> {
+        use DataType::*;
+        let (left, right) = (self.data_type(), other.data_type());
+        let valid = matches!(
+            (&left, &right),
+            (Time, Time) | (LTime, LTime) | (TimeOfDay | DateAndTime, Time | LTime)
+        );
+        if !valid {
+            return Err(RuntimeError::IncompatibleOperands {
+                operation: "Addition".to_string(),
+                left,
+                right,
+            });
+        }
+        let value = self
+            .as_nanoseconds()
+            .unwrap()
+            .checked_add(other.as_nanoseconds().unwrap())
+            .ok_or_else(|| RuntimeError::OutOfRange {
+                value: format!("{} + {}", self, other),
+                range: left.to_string(),
+            })?;
+        Ok(Object::from_nanoseconds(value, &left))
+    }
+
+    /// Subtracts a duration from a duration, time of day or date and time,
+    /// or gives the TIME between two dates or times of the same type.
+    fn sub_time(self, other: Object) -> Result<Object, RuntimeError> {
+        use DataType::*;
+        let (left, right) = (self.data_type(), other.data_type());
+        let result = match (&left, &right) {
+            (Time, Time) | (LTime, LTime) => left.clone(),
+            (TimeOfDay, Time | LTime) | (DateAndTime, Time | LTime) => left.clone(),
+            (Date, Date) | (TimeOfDay, TimeOfDay) | (DateAndTime, DateAndTime) => Time,
+            _ => {
+                return Err(RuntimeError::IncompatibleOperands {
+                    operation: "Subtraction".to_string(),
+                    left,
+                    right,
+                })
+            }
+        };
+        let value = self
+            .as_nanoseconds()
+            .unwrap()
+            .checked_sub(other.as_nanoseconds().unwrap())
+            .ok_or_else(|| RuntimeError::OutOfRange {
+                value: format!("{} - {}", self, other),
+                range: result.to_string(),
+            })?;
+        Ok(Object::from_nanoseconds(value, &result))
+    }
+
+    /// Multiplies or divides a duration by a number. A real factor rounds
+    /// the result to the nearest nanosecond, a result beyond the range of
+    /// the duration type is an error.
+    fn scale_time(self, other: Object, operation: &str) -> Result<Object, RuntimeError> {
+        let (left, right) = (self.data_type(), other.data_type());
+        if !left.is_duration() || !right.is_numeric() {
+            return Err(RuntimeError::IncompatibleOperands {
+                operation: operation.to_string(),
+                left,
+                right,
+            });
+        }
+        let value = i128::from(self.as_nanoseconds().unwrap());
+        let multiply = operation == "Multiplication";
+        let result = match other.as_i128() {
+            Some(0) if !multiply => return Err(RuntimeError::DivisionByZero),
+            Some(factor) if multiply => value.checked_mul(factor),
+            Some(divisor) => Some(value / divisor),
+            None => {
+                let factor = other.as_f64().unwrap();
+                if factor == 0.0 && !multiply {
+                    return Err(RuntimeError::DivisionByZero);
+                }
+                let result = if multiply {
+                    value as f64 * factor
+                } else {
+                    value as f64 / factor
+                };
+                Some(result.round() as i128)
+            }
+        };
+        match result.and_then(|result| i64::try_from(result).ok()) {
+            Some(result) => Ok(Object::from_nanoseconds(result, &left)),
+            None => Err(RuntimeError::OutOfRange {
+                value: format!("{} {} {}", self, if multiply { "*" } else { "/" }, other),
+                range: left.to_string(),
+            }),
+        }
+    }
+
+    pub fn modulo(self, other: Object) -> Result<Object, RuntimeError> {
+        match self.promote(other, "MOD")? {
+            (lhs, _) if lhs.data_type().is_real() => Err(RuntimeError::InvalidOperand {
+                operation: "MOD".to_string(),
+                data_type: lhs.data_type(),
+            }),
+            (_, rhs) if rhs.as_i128() == Some(0) => Err(RuntimeError::DivisionByZero),
+            (lhs, rhs) => Ok(integer_op!(lhs, rhs, wrapping_rem)),
+        }
+    }
+
+    /// Exponentiation, the result is REAL for a REAL base and LREAL otherwise.
+    pub fn pow(self, other: Object) -> Result<Object, RuntimeError> {
+        for operand in [&self, &other] {
+            if !operand.data_type().is_numeric() {
+                return Err(RuntimeError::InvalidOperand {
+                    operation: "**".to_string(),
+                    data_type: operand.data_type(),
+                });
+            }
+        }
+        let exponent = other.as_f64().unwrap();
+        Ok(match self {
+            Object::Real(base) => Object::Real(base.powf(exponent as f32)),
+            _ => Object::LReal(self.as_f64().unwrap().powf(exponent)),
+        })
+    }
+
+    /// Orders two objects for the comparison operators, `None` if either is NaN.
+    pub fn compare(self, other: Object) -> Result<Option<Ordering>, RuntimeError> {
+        let (left, right) = (self.data_type(), other.data_type());
+        match (&self, &other) {
+            (Object::String(lhs, _), Object::String(rhs, _))
+            | (Object::WString(lhs, _), Object::WString(rhs, _)) => return Ok(Some(lhs.cmp(rhs))),
+            _ if left == right && self.as_nanoseconds().is_some() => {
+                return Ok(Some(self.as_nanoseconds().cmp(&other.as_nanoseconds())))
+            }
+            (Object::Enum(lhs), Object::Enum(rhs)) if left == right => {
+                return Ok(Some(lhs.value.cmp(&rhs.value)))
+            }
+            _ => {}
+        }
+        let is_enum = |data_type: &DataType| matches!(data_type, DataType::Enum(_));
+        if self.as_nanoseconds().is_some()
+            || other.as_nanoseconds().is_some()
+            || left.is_string()
+            || right.is_string()
+            || is_enum(&left)
+            || is_enum(&right)
+        {
+            return Err(RuntimeError::IncompatibleOperands {
+                operation: "Comparison".to_string(),
+                left,
+                right,
+            });
+        }
+        for data_type in [&left, &right] {
+            if !data_type.is_numeric() && !data_type.is_bit() {
+                return Err(RuntimeError::InvalidOperand {
+                    operation: "Comparison".to_string(),
+                    data_type: data_type.clone(),
+                });
+            }
+        }
+        if left != right && (left == DataType::Bool || right == DataType::Bool) {
+            return Err(RuntimeError::IncompatibleOperands {
+                operation: "Comparison".to_string(),
+                left,
+                right,
+            });
+        }
+        if left.is_real() || right.is_real() {
+            Ok(self.as_f64().unwrap().partial_cmp(&other.as_f64().unwrap()))
+        } else {
+            Ok(Some(self.as_i128().unwrap().cmp(&other.as_i128().unwrap())))
+        }
+    }
+
+    fn bitwise(
+        self,
+        other: Object,
+        operation: &str,
+        op: fn(u64, u64) -> u64,
+    ) -> Result<Object, RuntimeError> {
+        let (left, right) = (self.data_type(), other.data_type());
+        let is_bit_string =
+            |data_type: &DataType| data_type.is_bit() && *data_type != DataType::Bool;
+        let data_type = if left == right && left.is_bit() {
+            left
+        } else if is_bit_string(&left) && is_bit_string(&right) {
+            if left.width() > right.width() {
+                left
+            } else {
+                right
+            }
+        } else if is_bit_string(&left) && right.is_integer() {
+            left
+        } else if is_bit_string(&right) && left.is_integer() {
+            right
+        } else {
+            return Err(RuntimeError::IncompatibleOperands {
+                operation: operation.to_string(),
+                left,
+                right,
+            });
+        };
+
+        let lhs = self.convert(data_type.clone()).as_i128().unwrap() as u64;
+        let rhs = other.convert(data_type.clone()).as_i128().unwrap() as u64;
+        Ok(Object::ULInt(op(lhs, rhs)).convert(data_type))
+    }
+
+    pub fn and(self, other: Object) -> Result<Object, RuntimeError> {
+        self.bitwise(other, "AND", |lhs, rhs| lhs & rhs)
+    }
+
+    pub fn or(self, other: Object) -> Result<Object, RuntimeError> {
+        self.bitwise(other, "OR", |lhs, rhs| lhs | rhs)
+    }
+
+    pub fn xor(self, other: Object) -> Result<Object, RuntimeError> {
+        self.bitwise(other, "XOR", |lhs, rhs| lhs ^ rhs)
+    }
+
+    pub fn not(self) -> Result<Object, RuntimeError> {
+        match self {
+            Object::Bool(value) => Ok(Object::Bool(!value)),
+            _ if self.data_type().is_bit() => {
+                Ok(Object::ULInt(!(self.as_i128().unwrap() as u64)).convert(self.data_type()))
+            }
+            _ => Err(RuntimeError::InvalidOperand {
+                operation: "NOT".to_string(),
+                data_type: self.data_type(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Object::Bool(true) => write!(f, "TRUE"),
+            Object::Bool(false) => write!(f, "FALSE"),
+            Object::Real(value) => write_real(f, *value),
+            Object::LReal(value) => write_real(f, *value),
+            Object::Time(value) => write_duration(f, "T#", *value),
+            Object::LTime(value) => write_duration(f, "LTIME#", *value),
+            Object::Date(value) => {
+                write!(f, "D#")?;
+                write_date(f, *value)
+            }
+            Object::TimeOfDay(value) => {
+                write!(f, "TOD#")?;
+                write_time_of_day(f, *value)
+            }
+            Object::DateAndTime(value) => {
+                write!(f, "DT#")?;
+                write_date(f, *value)?;
+                write!(f, "-")?;
+                write_time_of_day(f, value.rem_euclid(NANOSECONDS_PER_DAY))
+            }
+            Object::String(value, _) | Object::WString(value, _) => write!(f, "{}", value),
+            Object::Array(array) => {
+                write!(f, "[")?;
+                for (index, element) in array.elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Object::Struct(value) => write!(f, "{}", value.type_name),
+            Object::Enum(value) => write!(f, "{}#{}", value.type_name, value.name),
+            Object::Subrange(subrange) => write!(f, "{}", subrange.value),
+            Object::Instance(instance) => write!(f, "{}", instance.type_name),
+            _ => write!(f, "{}", self.as_i128().unwrap()),
+        }
+    }
+}
+
+/// Writes a real in scientific notation, e.g. `1e300`, when it is too large or
+/// too small to print as a short decimal.
+fn write_real<T>(f: &mut fmt::Formatter, value: T) -> fmt::Result
+where
+    T: Copy + Into<f64> + fmt::Display + fmt::LowerExp,
+{
+    let magnitude = value.into().abs();
+    if magnitude.is_finite() && magnitude != 0.0 && !(1e-6..1e16).contains(&magnitude) {
+        write!(f, "{:e}", value)
+    } else {
+        write!(f, "{}", value)
+    }
+}
+
+/// The first `length` characters of `value`.
+fn truncate(value: &str, length: usize) -> String {
+    value.chars().take(length).collect()
+}
+
+/// Writes a duration in nanoseconds as a literal with the given prefix, e.g.
+/// `T#1h2m3s4ms`.
+fn write_duration(f: &mut fmt::Formatter, prefix: &str, nanoseconds: i64) -> fmt::Result {
+    const UNITS: [(&str, u64); 7] = [
+        ("d", 86_400_000_000_000),
+        ("h", 3_600_000_000_000),
+        ("m", 60_000_000_000),
+        ("s", 1_000_000_000),
+        ("ms", 1_000_000),
+        ("us", 1_000),
+        ("ns", 1),
+    ];
+    write!(f, "{}", prefix)?;
+    if nanoseconds < 0 {
+        write!(f, "-")?;
+    }
+    let mut rest = nanoseconds.unsigned_abs();
+    if rest == 0 {
+        return write!(f, "0s");
+    }
+    for (unit, length) in UNITS.iter() {
+        if rest >= *length {
+            write!(f, "{}{}", rest / length, unit)?;
+            rest %= length;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the day of a point in time in nanoseconds as `2024-01-31`.
+fn write_date(f: &mut fmt::Formatter, nanoseconds: i64) -> fmt::Result {
+    let (year, month, day) = calendar::civil_from_days(nanoseconds.div_euclid(NANOSECONDS_PER_DAY));
+    write!(f, "{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Writes nanoseconds since midnight as `12:30:00`, with the fraction of the
+/// second if there is one.
+fn write_time_of_day(f: &mut fmt::Formatter, nanoseconds: i64) -> fmt::Result {
+    let seconds = nanoseconds / 1_000_000_000;
+    write!(
+        f,
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )?;
+    let fraction = nanoseconds % 1_000_000_000;
+    if fraction != 0 {
+        write!(f, ".{}", format!("{:09}", fraction).trim_end_matches('0'))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn integer_arithmetic_wraps_at_type_width() {
+    assert_eq!(
+        Object::SInt(127).add(Object::SInt(1)),
+        Ok(Object::SInt(-128))
+    );
+    assert_eq!(
+        Object::USInt(0).sub(Object::USInt(1)),
+        Ok(Object::USInt(255))
+    );
+    assert_eq!(Object::UInt(7).div(Object::UInt(2)), Ok(Object::UInt(3)));
+    assert_eq!(
+        Object::UInt(7).div(Object::UInt(0)),
+        Err(RuntimeError::DivisionByZero)
+    );
+}
+
+#[test]
+fn mixed_operands_are_widened() {
+    assert_eq!(
+        Object::SInt(100).add(Object::Int(100)),
+        Ok(Object::Int(200))
+    );
+    assert_eq!(
+        Object::USInt(200).add(Object::SInt(100)),
+        Ok(Object::Int(300))
+    );
+    assert_eq!(Object::Int(1).add(Object::Real(0.5)), Ok(Object::Real(1.5)));
+    assert_eq!(
+        Object::Real(1.0).add(Object::LReal(0.5)),
+        Ok(Object::LReal(1.5))
+    );
+}
+
+#[test]
+fn assignment_conversion() {
+    assert_eq!(
+        Object::DInt(300).assign_to(DataType::SInt),
+        Ok(Object::SInt(44))
+    );
+    assert_eq!(
+        Object::DInt(3).assign_to(DataType::LReal),
+        Ok(Object::LReal(3.0))
+    );
+    assert_eq!(
+        Object::DInt(255).assign_to(DataType::Byte),
+        Ok(Object::Byte(255))
+    );
+    assert_eq!(
+        Object::Real(1.5).assign_to(DataType::Int),
+        Err(RuntimeError::InvalidAssignment {
+            from: DataType::Real,
+            to: DataType::Int
+        })
+    );
+}
+
+#[test]
+fn bitwise_operations_keep_width() {
+    assert_eq!(Object::Byte(0x0F).not(), Ok(Object::Byte(0xF0)));
+    assert_eq!(
+        Object::Word(0xFF00).and(Object::DInt(0x0FF0)),
+        Ok(Object::Word(0x0F00))
+    );
+    assert_eq!(
+        Object::Bool(true).xor(Object::Bool(true)),
+        Ok(Object::Bool(false))
+    );
+    assert_eq!(
+        Object::DInt(-7).modulo(Object::DInt(3)),
+        Ok(Object::DInt(-1))
+    );
+}
+
+#[test]
+fn array_offsets_are_row_major() {
+    let array = Array::new(Object::Int(0), vec![(1, 3), (-1, 1)]);
+    assert_eq!(array.elements.len(), 9);
+    assert_eq!(array.offset(&[1, -1]), Ok(0));
+    assert_eq!(array.offset(&[2, 1]), Ok(5));
+    assert_eq!(
+        array.offset(&[3, 2]),
+        Err(RuntimeError::IndexOutOfBounds {
+            index: 2,
+            lower: -1,
+            upper: 1
+        })
+    );
+    assert_eq!(
+        array.offset(&[1]),
+        Err(RuntimeError::WrongIndexCount {
+            expected: 2,
+            found: 1
+        })
+    );
+    assert_eq!(Array::element_count(&[(0, 1023), (1, 1024)]), Some(1 << 20));
+    assert_eq!(Array::element_count(&[(0, 1024), (1, 1024)]), None);
+    assert_eq!(Array::element_count(&[(0, 1 << 62), (0, 1 << 62)]), None);
+    assert_eq!(Array::element_count(&[(i128::MIN, i128::MAX)]), None);
+}
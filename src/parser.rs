@@ -1,189 +1,945 @@
 use log::trace;
 
-use crate::ast::{Assignment, BinaryOp, CompoundStatement, Node, Num, UnaryOp, Variable};
+use crate::ast::{
+    Argument, Assignment, BinaryOp, Call, CaseElement, CaseLabel, CaseStatement, CompilationUnit,
+    CompoundStatement, Configuration, EnumValue, ForStatement, FunctionBlockDecl, FunctionDecl,
+    IfStatement, Index, Member, Node, Num, Program, ProgramConfiguration, RepeatStatement,
+    Resource, TaskDecl, Type, TypeDecl, TypeDefinition, UnaryOp, VarDecl, VarKind, Variable,
+    WhileStatement,
+};
+use crate::error::ParseError;
 use crate::lexer::Lexer;
-use crate::token::Token;
+use crate::object::Array;
+use crate::token::{Span, SpannedToken, Token};
 
 pub struct Parser {
     lexer: Lexer,
     current_token: Token,
+    current_span: Span,
+    /// The token after the current one, once `peek` has read it.
+    next: Option<SpannedToken>,
+    loop_depth: usize,
+    /// Whether the declarations of a PROGRAM are parsed, the only ones that
+    /// may be located `AT` a direct address.
+    in_program: bool,
 }
 
 impl Parser {
-    pub fn new(mut lexer: Lexer) -> Parser {
-        let current_token = lexer.get_next_token().expect("No first token");
-        trace!("New Parser, first token is: {:?}", current_token);
+    pub fn new(lexer: Lexer) -> Parser {
+        trace!("New Parser");
         Parser {
             lexer,
-            current_token,
+            current_token: Token::Eof,
+            current_span: Span::default(),
+            next: None,
+            loop_depth: 0,
+            in_program: false,
         }
     }
 
-    pub fn parse(&mut self) -> Node {
+    pub fn parse(&mut self) -> Result<Node, ParseError> {
         trace!("Starting parse");
-        let node: Node;
-        match self.current_token {
-            Token::Program => {
-                node = self.program();
-            }
-            _ => {
-                node = self.expr();
-            }
-        }
+        self.advance()?;
+        trace!("First token is: {:?}", self.current_token);
+        let node = match self.current_token {
+            Token::Program
+            | Token::Function
+            | Token::FunctionBlock
+            | Token::Type
+            | Token::Configuration => self.compilation_unit()?,
+            _ => self.expr()?,
+        };
+        self.eat(Token::Eof)?;
         trace!("Parse end");
-        node
+        Ok(node)
     }
 
-    fn eat(&mut self, token: Token) {
+    fn eat(&mut self, token: Token) -> Result<(), ParseError> {
         trace!("Consumed {:?}-token", token);
         if Token::variant_eq(token.clone(), &self.current_token) {
-            if let Some(token) = self.lexer.get_next_token() {
-                self.current_token = token;
-            }
+            self.advance()
         } else {
-            panic!(
-                "Wrong token, got {:?}, expected {:?}",
-                self.current_token, token
-            );
+            Err(self.unexpected(token))
+        }
+    }
+
+    fn advance(&mut self) -> Result<(), ParseError> {
+        let next = match self.next.take() {
+            Some(next) => next,
+            None => self.lexer.get_next_token()?,
+        };
+        self.current_token = next.token;
+        self.current_span = next.span;
+        Ok(())
+    }
+
+    /// The token after the current one, without consuming either.
+    fn peek(&mut self) -> Result<&Token, ParseError> {
+        if self.next.is_none() {
+            self.next = Some(self.lexer.get_next_token()?);
+        }
+        Ok(&self.next.as_ref().unwrap().token)
+    }
+
+    /// The current token together with its span.
+    fn spanned(&self) -> SpannedToken {
+        SpannedToken::new(self.current_token.clone(), self.current_span)
+    }
+
+    fn unexpected(&self, expected: impl ToString) -> ParseError {
+        ParseError::UnexpectedToken {
+            expected: expected.to_string(),
+            found: self.current_token.clone(),
+            span: self.current_span,
         }
     }
 
-    fn factor(&mut self) -> Node {
+    /// Consumes an identifier and returns its token.
+    fn id(&mut self) -> Result<SpannedToken, ParseError> {
+        match self.current_token {
+            Token::Id(_) => {
+                let token = self.spanned();
+                self.eat(Token::Id("".to_string()))?;
+                Ok(token)
+            }
+            _ => Err(self.unexpected("identifier")),
+        }
+    }
+
+    /// Consumes an identifier and returns its spelling.
+    fn name(&mut self) -> Result<String, ParseError> {
+        match self.id()?.token {
+            Token::Id(name) => Ok(name),
+            _ => unreachable!(),
+        }
+    }
+
+    fn factor(&mut self) -> Result<Node, ParseError> {
         trace!("Entering factor");
         let node: Node;
 
         match self.current_token {
-            Token::Plus => {
-                self.eat(Token::Plus);
-                node = Node::UnaryOp(UnaryOp::new(Token::Plus, self.factor()));
-            }
-            Token::Minus => {
-                self.eat(Token::Minus);
-                node = Node::UnaryOp(UnaryOp::new(Token::Minus, self.factor()));
-            }
-            Token::Integer(value) => {
-                self.eat(Token::Integer(0));
-                node = Node::Num(Num::new(Token::Integer(value)));
+            Token::Integer(..)
+            | Token::Real(..)
+            | Token::Bool(_)
+            | Token::Time(_)
+            | Token::LTime(_)
+            | Token::Date(_)
+            | Token::TimeOfDay(_)
+            | Token::DateAndTime(_)
+            | Token::String(_)
+            | Token::WString(_) => {
+                let token = self.spanned();
+                self.eat(token.token.clone())?;
+                node = Node::Num(Num::new(token));
             }
             Token::Lparen => {
-                self.eat(Token::Lparen);
-                node = self.expr();
-                self.eat(Token::Rparen);
+                self.eat(Token::Lparen)?;
+                node = self.expr()?;
+                self.eat(Token::Rparen)?;
             }
             Token::Id(_) => {
-                node = self.variable();
-                self.eat(Token::Id("".to_string()));
+                let token = self.id()?;
+                node = match self.current_token {
+                    Token::Hash => {
+                        self.eat(Token::Hash)?;
+                        Node::EnumValue(EnumValue::new(token, self.id()?))
+                    }
+                    _ => self.variable_or_call(token)?,
+                };
             }
-            _ => panic!("Unexpected token in factor: {:?}", self.current_token),
+            _ => return Err(self.unexpected("expression")),
         }
-        node
+        Ok(node)
     }
 
-    fn term(&mut self) -> Node {
-        trace!("Entering term");
-        let mut node = self.factor();
+    /// Parses a left associative chain of `operand`s joined by any of
+    /// `operators`.
+    fn binary_op(
+        &mut self,
+        operators: &[Token],
+        operand: fn(&mut Parser) -> Result<Node, ParseError>,
+    ) -> Result<Node, ParseError> {
+        let mut node = operand(self)?;
 
-        while (self.current_token == Token::Mul) | (self.current_token == Token::Div) {
-            match self.current_token {
-                Token::Mul => {
-                    self.eat(Token::Mul);
-                    node = Node::BinaryOp(BinaryOp::new(node, self.factor(), Token::Mul));
-                }
-                Token::Div => {
-                    self.eat(Token::Div);
-                    node = Node::BinaryOp(BinaryOp::new(node, self.factor(), Token::Div));
-                }
-                _ => panic!(),
+        while operators.contains(&self.current_token) {
+            let op = self.spanned();
+            self.eat(op.token.clone())?;
+            node = Node::BinaryOp(BinaryOp::new(node, operand(self)?, op));
+        }
+        Ok(node)
+    }
+
+    fn power(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering power");
+        self.binary_op(&[Token::Power], Parser::factor)
+    }
+
+    fn unary(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering unary");
+        match self.current_token {
+            Token::Plus | Token::Minus | Token::Not => {
+                let op = self.spanned();
+                self.eat(op.token.clone())?;
+                Ok(Node::UnaryOp(UnaryOp::new(op, self.unary()?)))
             }
+            _ => self.power(),
         }
-        node
     }
 
-    fn expr(&mut self) -> Node {
+    fn term(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering term");
+        self.binary_op(&[Token::Mul, Token::Div, Token::Mod], Parser::unary)
+    }
+
+    fn add_expr(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering add expr");
+        self.binary_op(&[Token::Plus, Token::Minus], Parser::term)
+    }
+
+    fn comparison(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering comparison");
+        let operators = [
+            Token::Less,
+            Token::Greater,
+            Token::LessEqual,
+            Token::GreaterEqual,
+        ];
+        self.binary_op(&operators, Parser::add_expr)
+    }
+
+    fn equality(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering equality");
+        self.binary_op(&[Token::Equal, Token::NotEqual], Parser::comparison)
+    }
+
+    fn and_expr(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering and expr");
+        self.binary_op(&[Token::And], Parser::equality)
+    }
+
+    fn xor_expr(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering xor expr");
+        self.binary_op(&[Token::Xor], Parser::and_expr)
+    }
+
+    /// Expressions in IEC 61131-3 precedence order, from lowest to highest:
+    /// OR, XOR, AND/&, = <>, < > <= >=, + -, * / MOD, unary - + NOT, **.
+    fn expr(&mut self) -> Result<Node, ParseError> {
         trace!("Entering expr");
-        let mut node = self.term();
+        self.binary_op(&[Token::Or], Parser::xor_expr)
+    }
+
+    fn no_op(&mut self) -> Result<Node, ParseError> {
+        Ok(Node::NoOp)
+    }
+
+    fn argument(&mut self) -> Result<Argument, ParseError> {
+        let value = self.expr()?;
+        match value {
+            Node::Variable(variable) if self.current_token == Token::Assign => {
+                self.eat(Token::Assign)?;
+                Ok(Argument::new(Some(variable.id), self.expr()?))
+            }
+            value => Ok(Argument::new(None, value)),
+        }
+    }
+
+    /// Parses the argument list of a call of the already parsed `callee`, a
+    /// name or an instance selected as in `timers[i]`.
+    fn call(&mut self, callee: Node) -> Result<Node, ParseError> {
+        trace!("Entering call");
+        self.eat(Token::Lparen)?;
+        let mut arguments = Vec::new();
+        if self.current_token != Token::Rparen {
+            arguments.push(self.argument()?);
+            while self.current_token == Token::Comma {
+                self.eat(Token::Comma)?;
+                arguments.push(self.argument()?);
+            }
+        }
+        let span = callee.span().to(self.current_span);
+        self.eat(Token::Rparen)?;
+
+        let formal = arguments.iter().filter(|arg| arg.name.is_some()).count();
+        if formal != 0 && formal != arguments.len() {
+            return Err(ParseError::MixedArguments(span));
+        }
+        let mut variable = &callee;
+        while let Node::Member(Member { base, .. }) | Node::Index(Index { base, .. }) = variable {
+            variable = base;
+        }
+        let name = match variable {
+            Node::Variable(variable) => variable.id.clone(),
+            node => panic!("Incorrect node in call: {:?}", node),
+        };
+        let mut call = Call::new(SpannedToken::new(Token::Id(name), span), arguments);
+        if !matches!(callee, Node::Variable(_)) {
+            call.instance = Some(Box::new(callee));
+        }
+        Ok(Node::Call(call))
+    }
+
+    /// A variable, possibly followed by selectors, or a call of a function
+    /// or function block instance, starting with the consumed identifier.
+    fn variable_or_call(&mut self, token: SpannedToken) -> Result<Node, ParseError> {
+        let node = self.selectors(Node::Variable(Variable::new(token)))?;
+        match self.current_token {
+            Token::Lparen => self.call(node),
+            _ => Ok(node),
+        }
+    }
 
-        while (self.current_token == Token::Plus) | (self.current_token == Token::Minus) {
+    /// Parses any `.member` accesses and `[i, j]` subscripts following
+    /// `base`.
+    fn selectors(&mut self, base: Node) -> Result<Node, ParseError> {
+        let mut node = base;
+        loop {
             match self.current_token {
-                Token::Plus => {
-                    self.eat(Token::Plus);
-                    node = Node::BinaryOp(BinaryOp::new(node, self.term(), Token::Plus));
+                Token::Dot => {
+                    self.eat(Token::Dot)?;
+                    node = Node::Member(Member::new(node, self.id()?));
                 }
-                Token::Minus => {
-                    self.eat(Token::Minus);
-                    node = Node::BinaryOp(BinaryOp::new(node, self.factor(), Token::Minus));
+                Token::Lbracket => {
+                    self.eat(Token::Lbracket)?;
+                    let mut indices = vec![self.expr()?];
+                    while self.current_token == Token::Comma {
+                        self.eat(Token::Comma)?;
+                        indices.push(self.expr()?);
+                    }
+                    let token = self.spanned();
+                    self.eat(Token::Rbracket)?;
+                    node = Node::Index(Index::new(node, indices, token));
                 }
-                _ => panic!(),
+                _ => return Ok(node),
             }
         }
-        node
-    }
-    fn no_op(&mut self) -> Node {
-        Node::NoOp
     }
 
-    fn variable(&mut self) -> Node {
-        trace!("Entering variable");
-        let node = Node::Variable(Variable::new(self.current_token.clone()));
-        self.eat(Token::Id("".to_string()));
-        node
+    /// An assignment or a call statement, both start with an identifier.
+    fn assignment_or_call(&mut self) -> Result<Node, ParseError> {
+        let token = self.id()?;
+        match self.variable_or_call(token)? {
+            Node::Call(call) => Ok(Node::Call(call)),
+            left => self.assignment(left),
+        }
     }
-    fn assignment(&mut self) -> Node {
+
+    fn assignment(&mut self, left: Node) -> Result<Node, ParseError> {
         trace!("Entering assignment");
-        let left = self.variable();
-        let token = self.current_token.clone();
-        self.eat(Token::Assign);
-        let right = self.expr();
-        Node::Assignment(Assignment::new(token, left, right))
+        let token = self.spanned();
+        self.eat(Token::Assign)?;
+        let right = self.expr()?;
+        Ok(Node::Assignment(Assignment::new(token, left, right)))
     }
 
-    fn statement(&mut self) -> Node {
-        trace!("Entering statement");
-        let node: Node;
-        match self.current_token {
-            Token::Program => {
-                node = self.compound_statement();
+    /// Parses the rest of an IF or ELSIF branch whose keyword `token` has
+    /// already been consumed.
+    fn conditional_branch(&mut self, token: SpannedToken) -> Result<Node, ParseError> {
+        let condition = self.expr()?;
+        self.eat(Token::Then)?;
+        let then_branch = self.compound_statement()?;
+
+        let else_branch = match self.current_token {
+            Token::Elsif => {
+                let token = self.spanned();
+                self.eat(Token::Elsif)?;
+                Some(self.conditional_branch(token)?)
+            }
+            Token::Else => {
+                self.eat(Token::Else)?;
+                Some(self.compound_statement()?)
             }
+            _ => None,
+        };
+        Ok(Node::IfStatement(IfStatement::new(
+            token,
+            condition,
+            then_branch,
+            else_branch,
+        )))
+    }
+
+    fn if_statement(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering if statement");
+        let token = self.spanned();
+        self.eat(Token::If)?;
+        let node = self.conditional_branch(token)?;
+        self.eat(Token::EndIf)?;
+        Ok(node)
+    }
+
+    fn case_label(&mut self) -> Result<CaseLabel, ParseError> {
+        let value = self.expr()?;
+        if self.current_token == Token::DotDot {
+            self.eat(Token::DotDot)?;
+            Ok(CaseLabel::Range(Box::new(value), Box::new(self.expr()?)))
+        } else {
+            Ok(CaseLabel::Value(Box::new(value)))
+        }
+    }
 
-            Token::Id(_) => node = self.assignment(),
+    fn case_element(&mut self) -> Result<CaseElement, ParseError> {
+        trace!("Entering case element");
+        let mut labels = vec![self.case_label()?];
+        while self.current_token == Token::Comma {
+            self.eat(Token::Comma)?;
+            labels.push(self.case_label()?);
+        }
+        self.eat(Token::Colon)?;
+        Ok(CaseElement::new(labels, self.case_body()?))
+    }
 
-            _ => {
-                node = self.no_op();
+    /// The statements of a CASE element, up to the label of the next
+    /// element, ELSE or END_CASE.
+    fn case_body(&mut self) -> Result<Node, ParseError> {
+        let mut compound_statement = CompoundStatement::new();
+        compound_statement.statements.push(self.statement()?);
+        while self.current_token == Token::Semicolon {
+            self.eat(Token::Semicolon)?;
+            if self.at_case_label()? {
+                break;
             }
+            compound_statement.statements.push(self.statement()?);
         }
-        node
+        Ok(Node::CompoundStatement(compound_statement))
     }
 
-    fn statement_list(&mut self) -> Vec<Node> {
+    /// Whether the current token starts a CASE label rather than a
+    /// statement. An identifier does if it is followed by one of the tokens
+    /// that can follow it in a label, as in `Idle:` or `Mode#Run, Mode#Stop:`.
+    fn at_case_label(&mut self) -> Result<bool, ParseError> {
+        Ok(match self.current_token {
+            Token::Integer(..) | Token::Minus => true,
+            Token::Id(_) => matches!(
+                self.peek()?,
+                Token::Colon | Token::Comma | Token::DotDot | Token::Hash
+            ),
+            _ => false,
+        })
+    }
+
+    fn case_statement(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering case statement");
+        let token = self.spanned();
+        self.eat(Token::Case)?;
+        let selector = self.expr()?;
+        self.eat(Token::Of)?;
+
+        let mut elements = Vec::new();
+        while !matches!(self.current_token, Token::Else | Token::EndCase) {
+            elements.push(self.case_element()?);
+        }
+
+        let else_branch = if self.current_token == Token::Else {
+            self.eat(Token::Else)?;
+            Some(self.compound_statement()?)
+        } else {
+            None
+        };
+        self.eat(Token::EndCase)?;
+        Ok(Node::CaseStatement(CaseStatement::new(
+            token,
+            selector,
+            elements,
+            else_branch,
+        )))
+    }
+
+    fn loop_body(&mut self) -> Result<Node, ParseError> {
+        self.loop_depth += 1;
+        let body = self.compound_statement();
+        self.loop_depth -= 1;
+        body
+    }
+
+    fn for_statement(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering for statement");
+        let token = self.spanned();
+        self.eat(Token::For)?;
+        let control_variable = Variable::new(self.id()?);
+        self.eat(Token::Assign)?;
+        let start = self.expr()?;
+        self.eat(Token::To)?;
+        let end = self.expr()?;
+        let step = if self.current_token == Token::By {
+            self.eat(Token::By)?;
+            Some(self.expr()?)
+        } else {
+            None
+        };
+        self.eat(Token::Do)?;
+        let body = self.loop_body()?;
+        self.eat(Token::EndFor)?;
+        Ok(Node::ForStatement(ForStatement::new(
+            token,
+            control_variable,
+            start,
+            end,
+            step,
+            body,
+        )))
+    }
+
+    fn while_statement(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering while statement");
+        let token = self.spanned();
+        self.eat(Token::While)?;
+        let condition = self.expr()?;
+        self.eat(Token::Do)?;
+        let body = self.loop_body()?;
+        self.eat(Token::EndWhile)?;
+        Ok(Node::WhileStatement(WhileStatement::new(
+            token, condition, body,
+        )))
+    }
+
+    fn repeat_statement(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering repeat statement");
+        let token = self.spanned();
+        self.eat(Token::Repeat)?;
+        let body = self.loop_body()?;
+        self.eat(Token::Until)?;
+        let condition = self.expr()?;
+        self.eat(Token::EndRepeat)?;
+        Ok(Node::RepeatStatement(RepeatStatement::new(
+            token, body, condition,
+        )))
+    }
+
+    fn loop_jump(&mut self) -> Result<Node, ParseError> {
+        let token = self.spanned();
+        if self.loop_depth == 0 {
+            return Err(ParseError::LoopJumpOutsideLoop(token.token, token.span));
+        }
+        self.eat(token.token.clone())?;
+        match token.token {
+            Token::Exit => Ok(Node::Exit(token)),
+            _ => Ok(Node::Continue(token)),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering statement");
+        match self.current_token {
+            Token::Program => self.compound_statement(),
+            Token::Id(_) => self.assignment_or_call(),
+            Token::If => self.if_statement(),
+            Token::Case => self.case_statement(),
+            Token::For => self.for_statement(),
+            Token::While => self.while_statement(),
+            Token::Repeat => self.repeat_statement(),
+            Token::Exit | Token::Continue => self.loop_jump(),
+            Token::Return => {
+                let token = self.spanned();
+                self.eat(Token::Return)?;
+                Ok(Node::Return(token))
+            }
+            _ => self.no_op(),
+        }
+    }
+
+    fn statement_list(&mut self) -> Result<Vec<Node>, ParseError> {
         trace!("Entering statement list");
         let mut list: Vec<Node> = Vec::new();
-        list.push(self.statement());
+        list.push(self.statement()?);
 
         while self.current_token == Token::Semicolon {
-            self.eat(Token::Semicolon);
-            list.push(self.statement());
+            self.eat(Token::Semicolon)?;
+            list.push(self.statement()?);
         }
-        list
+        Ok(list)
     }
 
-    fn compound_statement(&mut self) -> Node {
+    fn compound_statement(&mut self) -> Result<Node, ParseError> {
         trace!("Entering compound statement");
-        let nodes = self.statement_list();
+        let nodes = self.statement_list()?;
         let mut compound_statement = CompoundStatement::new();
         for node in nodes {
             compound_statement.statements.push(node);
         }
 
-        let root_node = Node::CompoundStatement(compound_statement);
-        root_node
+        Ok(Node::CompoundStatement(compound_statement))
     }
 
-    fn program(&mut self) -> Node {
+    fn type_spec(&mut self) -> Result<Type, ParseError> {
+        trace!("Entering type spec");
+        if self.current_token != Token::Array {
+            return self.element_type_spec();
+        }
+        let start = self.current_span;
+        self.eat(Token::Array)?;
+        self.eat(Token::Lbracket)?;
+        let mut dimensions = vec![self.array_dimension()?];
+        while self.current_token == Token::Comma {
+            self.eat(Token::Comma)?;
+            dimensions.push(self.array_dimension()?);
+        }
+        let end = self.current_span;
+        self.eat(Token::Rbracket)?;
+        if Array::element_count(&dimensions).is_none() {
+            let bounds: Vec<String> = dimensions
+                .iter()
+                .map(|(lower, upper)| format!("{}..{}", lower, upper))
+                .collect();
+            return Err(ParseError::ArrayTooLarge(
+                format!("ARRAY[{}]", bounds.join(", ")),
+                start.to(end),
+            ));
+        }
+        self.eat(Token::Of)?;
+        let mut var_type = self.element_type_spec()?;
+        var_type.dimensions = dimensions;
+        Ok(var_type)
+    }
+
+    /// The bounds `lower..upper` of one dimension of an ARRAY type.
+    fn array_dimension(&mut self) -> Result<(i128, i128), ParseError> {
+        let start = self.current_span;
+        let lower = self.signed_integer("array bound")?;
+        self.eat(Token::DotDot)?;
+        let end = self.current_span;
+        let upper = self.signed_integer("array bound")?;
+        if lower > upper {
+            return Err(ParseError::InvalidArrayBounds(start.to(end)));
+        }
+        Ok((lower, upper))
+    }
+
+    /// The bounds `(lower..upper)` of a subrange type.
+    fn subrange(&mut self) -> Result<(i128, i128), ParseError> {
+        self.eat(Token::Lparen)?;
+        let start = self.current_span;
+        let lower = self.signed_integer("subrange bound")?;
+        self.eat(Token::DotDot)?;
+        let end = self.current_span;
+        let upper = self.signed_integer("subrange bound")?;
+        if lower > upper {
+            return Err(ParseError::InvalidSubrange(start.to(end)));
+        }
+        self.eat(Token::Rparen)?;
+        Ok((lower, upper))
+    }
+
+    /// An untyped integer literal with an optional minus sign, described as
+    /// `expected` in errors.
+    fn signed_integer(&mut self, expected: &str) -> Result<i128, ParseError> {
+        let negative = self.current_token == Token::Minus;
+        if negative {
+            self.eat(Token::Minus)?;
+        }
+        match self.current_token {
+            Token::Integer(value, None) => {
+                self.advance()?;
+                Ok(if negative { -value } else { value })
+            }
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    /// A type name, with the maximum length of a STRING or WSTRING or the
+    /// bounds of a subrange.
+    fn element_type_spec(&mut self) -> Result<Type, ParseError> {
+        let mut var_type = Type::new(self.id()?);
+        let is_string = matches!(var_type.data_type(), Some(data_type) if data_type.is_string());
+        if is_string && self.current_token == Token::Lbracket {
+            self.eat(Token::Lbracket)?;
+            var_type.length = match self.current_token {
+                Token::Integer(length, None) if length > 0 && length <= i128::from(u16::MAX) => {
+                    Some(length as usize)
+                }
+                _ => return Err(self.unexpected("string length")),
+            };
+            self.advance()?;
+            self.eat(Token::Rbracket)?;
+        }
+        if self.current_token == Token::Lparen {
+            var_type.range = Some(self.subrange()?);
+        }
+        Ok(var_type)
+    }
+
+    fn variable_declaration(&mut self, kind: VarKind) -> Result<Vec<Node>, ParseError> {
+        trace!("Entering variable declaration");
+        let mut variables = vec![Variable::new(self.id()?)];
+
+        // Only a single variable can be located.
+        let address = if self.current_token == Token::At {
+            if !self.in_program {
+                return Err(ParseError::LocatedOutsideProgram(self.current_span));
+            }
+            self.eat(Token::At)?;
+            match self.current_token {
+                Token::DirectAddress(address) => {
+                    self.advance()?;
+                    Some(address)
+                }
+                _ => return Err(self.unexpected("direct address")),
+            }
+        } else {
+            while self.current_token == Token::Comma {
+                self.eat(Token::Comma)?;
+                variables.push(Variable::new(self.id()?));
+            }
+            None
+        };
+
+        self.eat(Token::Colon)?;
+        let var_type = self.type_spec()?;
+
+        let initial_value = if self.current_token == Token::Assign {
+            self.eat(Token::Assign)?;
+            Some(self.expr()?)
+        } else {
+            None
+        };
+
+        Ok(variables
+            .into_iter()
+            .map(|variable| {
+                Node::VarDecl(VarDecl::new(
+                    kind,
+                    variable,
+                    address,
+                    var_type.clone(),
+                    initial_value.clone(),
+                ))
+            })
+            .collect())
+    }
+
+    fn declarations(&mut self) -> Result<Vec<Node>, ParseError> {
+        trace!("Entering declarations");
+        let mut declarations: Vec<Node> = Vec::new();
+
+        loop {
+            let kind = match self.current_token {
+                Token::Var => VarKind::Var,
+                Token::VarInput => VarKind::Input,
+                Token::VarOutput => VarKind::Output,
+                _ => break,
+            };
+            self.eat(self.current_token.clone())?;
+            while let Token::Id(_) = self.current_token {
+                declarations.append(&mut self.variable_declaration(kind)?);
+                self.eat(Token::Semicolon)?;
+            }
+            self.eat(Token::EndVar)?;
+        }
+        Ok(declarations)
+    }
+
+    fn program(&mut self) -> Result<Node, ParseError> {
         trace!("Entering program");
-        self.eat(Token::Program);
-        let node = self.compound_statement();
-        self.eat(Token::EndProgram);
-        node
+        let token = self.spanned();
+        self.eat(Token::Program)?;
+        let name = self.name()?;
+        self.in_program = true;
+        let declarations = self.declarations();
+        self.in_program = false;
+        let declarations = declarations?;
+        let body = self.compound_statement()?;
+        self.eat(Token::EndProgram)?;
+        Ok(Node::Program(Program::new(token, name, declarations, body)))
+    }
+
+    fn function_decl(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering function declaration");
+        let token = self.spanned();
+        self.eat(Token::Function)?;
+        let name = self.name()?;
+        self.eat(Token::Colon)?;
+        let return_type = self.type_spec()?;
+        let declarations = self.declarations()?;
+        let body = self.compound_statement()?;
+        self.eat(Token::EndFunction)?;
+        Ok(Node::FunctionDecl(FunctionDecl::new(
+            token,
+            name,
+            return_type,
+            declarations,
+            body,
+        )))
+    }
+
+    fn function_block_decl(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering function block declaration");
+        let token = self.spanned();
+        self.eat(Token::FunctionBlock)?;
+        let name = self.name()?;
+        let declarations = self.declarations()?;
+        let body = self.compound_statement()?;
+        self.eat(Token::EndFunctionBlock)?;
+        Ok(Node::FunctionBlockDecl(FunctionBlockDecl::new(
+            token,
+            name,
+            declarations,
+            body,
+        )))
+    }
+
+    fn configuration(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering configuration");
+        let token = self.spanned();
+        self.eat(Token::Configuration)?;
+        let name = self.name()?;
+        let mut resources = Vec::new();
+        while self.current_token == Token::Resource {
+            resources.push(self.resource()?);
+        }
+        self.eat(Token::EndConfiguration)?;
+        Ok(Node::Configuration(Configuration::new(
+            token, name, resources,
+        )))
+    }
+
+    /// `RESOURCE name ON processor` with its tasks and then its program
+    /// instances. The processor type is optional and ignored.
+    fn resource(&mut self) -> Result<Resource, ParseError> {
+        self.eat(Token::Resource)?;
+        let name = self.name()?;
+        if self.current_token == Token::On {
+            self.eat(Token::On)?;
+            self.name()?;
+        }
+        let mut tasks = Vec::new();
+        while self.current_token == Token::Task {
+            tasks.push(self.task_decl()?);
+        }
+        let mut programs = Vec::new();
+        while self.current_token == Token::Program {
+            programs.push(self.program_configuration()?);
+        }
+        self.eat(Token::EndResource)?;
+        Ok(Resource::new(name, tasks, programs))
+    }
+
+    fn task_decl(&mut self) -> Result<TaskDecl, ParseError> {
+        self.eat(Token::Task)?;
+        let token = self.id()?;
+        self.eat(Token::Lparen)?;
+        let mut arguments = Vec::new();
+        loop {
+            let name = self.name()?;
+            self.eat(Token::Assign)?;
+            arguments.push(Argument::new(Some(name), self.expr()?));
+            if self.current_token != Token::Comma {
+                break;
+            }
+            self.eat(Token::Comma)?;
+        }
+        self.eat(Token::Rparen)?;
+        self.eat(Token::Semicolon)?;
+        Ok(TaskDecl::new(token, arguments))
+    }
+
+    fn program_configuration(&mut self) -> Result<ProgramConfiguration, ParseError> {
+        self.eat(Token::Program)?;
+        let token = self.id()?;
+        let task = if self.current_token == Token::With {
+            self.eat(Token::With)?;
+            Some(self.name()?)
+        } else {
+            None
+        };
+        self.eat(Token::Colon)?;
+        let program_type = self.name()?;
+        self.eat(Token::Semicolon)?;
+        Ok(ProgramConfiguration::new(token, task, program_type))
+    }
+
+    /// The declarations of a `TYPE ... END_TYPE` block.
+    fn data_types(&mut self) -> Result<Vec<Node>, ParseError> {
+        trace!("Entering data types");
+        self.eat(Token::Type)?;
+        let mut types = Vec::new();
+        while let Token::Id(_) = self.current_token {
+            let token = self.id()?;
+            self.eat(Token::Colon)?;
+            let definition = self.type_definition()?;
+            self.eat(Token::Semicolon)?;
+            types.push(Node::TypeDecl(TypeDecl::new(token, definition)));
+        }
+        self.eat(Token::EndType)?;
+        Ok(types)
+    }
+
+    fn type_definition(&mut self) -> Result<TypeDefinition, ParseError> {
+        match self.current_token {
+            Token::Struct => self.struct_definition(),
+            Token::Lparen => self.enum_definition(),
+            _ => Ok(TypeDefinition::Derived(self.type_spec()?)),
+        }
+    }
+
+    /// The values of an enumerated type, numbered from 0 or from the
+    /// previous explicit value, and its base type.
+    fn enum_definition(&mut self) -> Result<TypeDefinition, ParseError> {
+        self.eat(Token::Lparen)?;
+        let mut values = Vec::new();
+        let mut next = 0;
+        loop {
+            let name = self.name()?;
+            if self.current_token == Token::Assign {
+                self.eat(Token::Assign)?;
+                next = self.signed_integer("enumerated value")?;
+            }
+            values.push((name, next));
+            next += 1;
+            if self.current_token != Token::Comma {
+                break;
+            }
+            self.eat(Token::Comma)?;
+        }
+        self.eat(Token::Rparen)?;
+        let base = match self.current_token {
+            Token::Id(_) => self.element_type_spec()?,
+            _ => Type::new(SpannedToken::new(
+                Token::Id("INT".to_string()),
+                self.current_span,
+            )),
+        };
+        Ok(TypeDefinition::Enum(values, base))
+    }
+
+    fn struct_definition(&mut self) -> Result<TypeDefinition, ParseError> {
+        self.eat(Token::Struct)?;
+        let mut members = Vec::new();
+        while let Token::Id(_) = self.current_token {
+            members.append(&mut self.variable_declaration(VarKind::Var)?);
+            self.eat(Token::Semicolon)?;
+        }
+        self.eat(Token::EndStruct)?;
+        Ok(TypeDefinition::Struct(members))
+    }
+
+    fn compilation_unit(&mut self) -> Result<Node, ParseError> {
+        trace!("Entering compilation unit");
+        let mut pous = Vec::new();
+        loop {
+            match self.current_token {
+                Token::Program => pous.push(self.program()?),
+                Token::Function => pous.push(self.function_decl()?),
+                Token::FunctionBlock => pous.push(self.function_block_decl()?),
+                Token::Type => pous.append(&mut self.data_types()?),
+                Token::Configuration => pous.push(self.configuration()?),
+                _ => break,
+            }
+        }
+        Ok(Node::CompilationUnit(CompilationUnit::new(pous)))
+    }
+}
+
+/// Parses `text` and returns its first POU, which must be a PROGRAM.
+#[cfg(test)]
+fn parse_program(text: &str) -> Program {
+    let mut parser = Parser::new(Lexer::new(text.to_string()));
+    match parser.parse().unwrap() {
+        Node::CompilationUnit(unit) => match unit.pous.into_iter().next() {
+            Some(Node::Program(program)) => program,
+            pou => panic!("Expected a program, found {:?}", pou),
+        },
+        node => panic!("Expected a compilation unit, found {:?}", node),
     }
 }
 
@@ -192,24 +948,412 @@ fn parse_addition() {
     let text = "1+2".to_string();
     let lexer = Lexer::new(text);
     let mut parser = Parser::new(lexer);
-    if let Node::BinaryOp(binary_op) = parser.parse() {
-        assert_eq!(*binary_op.left, Node::Num(Num::new(Token::Integer(1))));
-        assert_eq!(*binary_op.right, Node::Num(Num::new(Token::Integer(2))));
+    if let Node::BinaryOp(binary_op) = parser.parse().unwrap() {
+        assert_eq!(
+            *binary_op.left,
+            Node::Num(Num::new(Token::Integer(1, None)))
+        );
+        assert_eq!(
+            *binary_op.right,
+            Node::Num(Num::new(Token::Integer(2, None)))
+        );
         assert_eq!(binary_op.op, Token::Plus);
     }
 }
 
 #[test]
 fn parse_assignment() {
-    let text = "PROGRAM x := 3 END_PROGRAM".to_string();
-    let lexer = Lexer::new(text);
-    let mut parser = Parser::new(lexer);
-    if let Node::Assignment(assignment) = parser.parse() {
+    let program = parse_program("PROGRAM main VAR x : INT; END_VAR x := 3 END_PROGRAM");
+    if let Node::CompoundStatement(body) = *program.body {
+        if let Node::Assignment(assignment) = &body.statements[0] {
+            assert_eq!(
+                *assignment.left,
+                Node::Variable(Variable::new(Token::Id("x".to_string())))
+            );
+            assert_eq!(
+                *assignment.right,
+                Node::Num(Num::new(Token::Integer(3, None)))
+            );
+            assert_eq!(assignment.op, Token::Assign);
+            return;
+        }
+    }
+    panic!("Expected program with an assignment");
+}
+
+#[test]
+fn parse_var_declarations() {
+    let program = parse_program(
+        "PROGRAM main
+        VAR
+            x, y : INT;
+            z : INT := 2;
+        END_VAR
+    END_PROGRAM",
+    );
+    assert_eq!(program.name, "main");
+    assert_eq!(program.declarations.len(), 3);
+    if let Node::VarDecl(var_decl) = &program.declarations[2] {
+        assert_eq!(var_decl.variable.id, "z");
+        assert_eq!(var_decl.var_type.name, "INT");
         assert_eq!(
-            *assignment.left,
-            Node::Variable(Variable::new(Token::Id("x".to_string())))
+            var_decl.initial_value,
+            Some(Box::new(Node::Num(Num::new(Token::Integer(2, None)))))
         );
-        assert_eq!(*assignment.right, Node::Num(Num::new(Token::Integer(3))));
-        assert_eq!(assignment.op, Token::Assign);
+        return;
+    }
+    panic!("Expected program with declarations");
+}
+
+#[test]
+fn parse_operator_precedence() {
+    let text = "a OR b AND NOT c = 1 + 2 * 3 ** 2".to_string();
+    let lexer = Lexer::new(text);
+    let mut parser = Parser::new(lexer);
+    let variable = |id: &str| Node::Variable(Variable::new(Token::Id(id.to_string())));
+    let num = |value| Node::Num(Num::new(Token::Integer(value, None)));
+    let binary = |left, right, op| Node::BinaryOp(BinaryOp::new(left, right, op));
+
+    let power = binary(num(3), num(2), Token::Power);
+    let sum = binary(num(1), binary(num(2), power, Token::Mul), Token::Plus);
+    let not_c = Node::UnaryOp(UnaryOp::new(Token::Not, variable("c")));
+    let and = binary(variable("b"), binary(not_c, sum, Token::Equal), Token::And);
+    assert_eq!(
+        parser.parse().unwrap(),
+        binary(variable("a"), and, Token::Or)
+    );
+}
+
+#[test]
+fn parse_errors() {
+    let parse = |text: &str| Parser::new(Lexer::new(text.to_string())).parse();
+
+    assert_eq!(
+        parse("PROGRAM main x := 1 END_IF"),
+        Err(ParseError::UnexpectedToken {
+            expected: "END_PROGRAM".to_string(),
+            found: Token::EndIf,
+            span: Span::new(20, 26, 1, 21),
+        })
+    );
+    assert_eq!(
+        parse("PROGRAM main EXIT; END_PROGRAM"),
+        Err(ParseError::LoopJumpOutsideLoop(
+            Token::Exit,
+            Span::new(13, 17, 1, 14)
+        ))
+    );
+    assert_eq!(
+        parse("1 + ?"),
+        Err(ParseError::Lex(crate::error::LexError::UnexpectedChar(
+            '?',
+            Span::new(4, 5, 1, 5)
+        )))
+    );
+    assert_eq!(
+        parse("FUNCTION_BLOCK fb VAR x AT %IX0.0 : BOOL; END_VAR END_FUNCTION_BLOCK"),
+        Err(ParseError::LocatedOutsideProgram(Span::new(24, 26, 1, 25)))
+    );
+    assert_eq!(
+        parse("PROGRAM main VAR x AT %IX0.8 : BOOL; END_VAR END_PROGRAM"),
+        Err(ParseError::Lex(crate::error::LexError::InvalidAddress(
+            "%IX0.8".to_string(),
+            Span::new(22, 28, 1, 23)
+        )))
+    );
+}
+
+#[test]
+fn parse_node_spans() {
+    let program = parse_program(
+        "PROGRAM main
+    VAR x : INT; END_VAR
+    x := (x + 1) * 2;
+END_PROGRAM",
+    );
+    assert_eq!(program.span(), Span::new(0, 7, 1, 1));
+    assert_eq!(program.declarations[0].span(), Span::new(21, 28, 2, 9));
+    if let Node::CompoundStatement(body) = *program.body {
+        if let Node::Assignment(assignment) = &body.statements[0] {
+            assert_eq!(assignment.span(), Span::new(42, 58, 3, 5));
+            assert_eq!(assignment.right.span(), Span::new(48, 58, 3, 11));
+            return;
+        }
+    }
+    panic!("Expected program with an assignment");
+}
+
+#[test]
+fn parse_function_and_calls() {
+    let text = "FUNCTION add : INT
+        VAR_INPUT a, b : INT; END_VAR
+        add := a + b;
+    END_FUNCTION
+    PROGRAM main
+        VAR x : INT; END_VAR
+        x := add(1, add(a := 2, b := 3));
+        add(4, 5);
+    END_PROGRAM";
+    let mut parser = Parser::new(Lexer::new(text.to_string()));
+    let unit = match parser.parse().unwrap() {
+        Node::CompilationUnit(unit) => unit,
+        node => panic!("Expected a compilation unit, found {:?}", node),
+    };
+    assert_eq!(unit.pous.len(), 2);
+    if let Node::FunctionDecl(function) = &unit.pous[0] {
+        assert_eq!(function.name, "add");
+        assert_eq!(function.return_type.name, "INT");
+        let inputs: Vec<&str> = function
+            .inputs()
+            .iter()
+            .map(|input| input.variable.id.as_str())
+            .collect();
+        assert_eq!(inputs, vec!["a", "b"]);
+    } else {
+        panic!("Expected a function declaration");
+    }
+
+    let num = |value| Node::Num(Num::new(Token::Integer(value, None)));
+    let call = |arguments| Node::Call(Call::new(Token::Id("add".to_string()), arguments));
+    let inner = call(vec![
+        Argument::new(Some("a".to_string()), num(2)),
+        Argument::new(Some("b".to_string()), num(3)),
+    ]);
+    let outer = call(vec![
+        Argument::new(None, num(1)),
+        Argument::new(None, inner),
+    ]);
+    if let Node::Program(program) = &unit.pous[1] {
+        if let Node::CompoundStatement(body) = &*program.body {
+            if let Node::Assignment(assignment) = &body.statements[0] {
+                assert_eq!(*assignment.right, outer);
+            }
+            assert_eq!(
+                body.statements[1],
+                call(vec![
+                    Argument::new(None, num(4)),
+                    Argument::new(None, num(5))
+                ])
+            );
+            return;
+        }
+    }
+    panic!("Expected program with calls");
+}
+
+#[test]
+fn parse_literals_with_declared_type() {
+    use crate::object::Object;
+    let cases = [
+        ("INT#5", Object::Int(5)),
+        ("WORD#16#FFFF", Object::Word(0xFFFF)),
+        ("REAL#1.5", Object::Real(1.5)),
+        ("16#FF", Object::DInt(255)),
+        ("5_000_000_000", Object::LInt(5_000_000_000)),
+        ("16#FFFF_FFFF_FFFF_FFFF", Object::ULInt(u64::MAX)),
+        ("1.5E3", Object::LReal(1500.0)),
+        ("BOOL#1", Object::Bool(true)),
+    ];
+    for (text, expected) in cases.iter() {
+        let mut parser = Parser::new(Lexer::new(text.to_string()));
+        match parser.parse().unwrap() {
+            Node::Num(num) => assert_eq!(num.value, *expected, "{}", text),
+            node => panic!("Expected a literal, found {:?}", node),
+        }
+    }
+}
+
+#[test]
+fn parse_arrays() {
+    let program = parse_program(
+        "PROGRAM main
+        VAR buf : ARRAY[1..10, -2..3] OF STRING[4]; END_VAR
+        buf[i + 1, 0] := buf[1, j]
+    END_PROGRAM",
+    );
+    match &program.declarations[0] {
+        Node::VarDecl(var_decl) => {
+            assert_eq!(var_decl.var_type.name, "STRING");
+            assert_eq!(var_decl.var_type.length, Some(4));
+            assert_eq!(var_decl.var_type.dimensions, vec![(1, 10), (-2, 3)]);
+        }
+        node => panic!("Expected a declaration, found {:?}", node),
+    }
+    let body = match *program.body {
+        Node::CompoundStatement(body) => body,
+        node => panic!("Expected statements, found {:?}", node),
+    };
+    match &body.statements[0] {
+        Node::Assignment(assignment) => match (&*assignment.left, &*assignment.right) {
+            (Node::Index(left), Node::Index(right)) => {
+                assert_eq!(left.indices.len(), 2);
+                assert!(matches!(left.indices[0], Node::BinaryOp(_)));
+                assert_eq!(right.span(), Span::new(98, 107, 3, 26));
+            }
+            nodes => panic!("Expected indexed operands, found {:?}", nodes),
+        },
+        node => panic!("Expected an assignment, found {:?}", node),
+    }
+
+    let parse = |text: &str| Parser::new(Lexer::new(text.to_string())).parse();
+    assert_eq!(
+        parse("PROGRAM main VAR a : ARRAY[3..1] OF INT; END_VAR END_PROGRAM"),
+        Err(ParseError::InvalidArrayBounds(Span::new(27, 31, 1, 28)))
+    );
+
+    let program = parse_program("PROGRAM main t[0].fb(IN := TRUE); END_PROGRAM");
+    match &*program.body {
+        Node::CompoundStatement(body) => match &body.statements[0] {
+            Node::Call(call) => {
+                assert_eq!(call.name, "t");
+                assert!(matches!(call.instance.as_deref(), Some(Node::Member(_))));
+                assert_eq!(call.span(), Span::new(13, 32, 1, 14));
+            }
+            node => panic!("Expected a call, found {:?}", node),
+        },
+        node => panic!("Expected statements, found {:?}", node),
     }
+    assert_eq!(
+        parse("PROGRAM main VAR a : ARRAY[0..100000000000] OF INT; END_VAR END_PROGRAM")
+            .unwrap_err()
+            .to_string(),
+        "ARRAY[0..100000000000] has more than 1048576 elements"
+    );
+}
+
+#[test]
+fn parse_struct_types() {
+    let mut parser = Parser::new(Lexer::new(
+        "TYPE
+            Point : STRUCT x, y : REAL; END_STRUCT;
+            Path : STRUCT points : ARRAY[1..4] OF Point; closed : BOOL := TRUE; END_STRUCT;
+        END_TYPE"
+            .to_string(),
+    ));
+    let pous = match parser.parse().unwrap() {
+        Node::CompilationUnit(unit) => unit.pous,
+        node => panic!("Expected a compilation unit, found {:?}", node),
+    };
+    let names: Vec<_> = pous
+        .iter()
+        .map(|pou| match pou {
+            Node::TypeDecl(type_decl) => match &type_decl.definition {
+                TypeDefinition::Struct(members) => (type_decl.name.as_str(), members.len()),
+                definition => panic!("Expected a STRUCT, found {:?}", definition),
+            },
+            node => panic!("Expected a type declaration, found {:?}", node),
+        })
+        .collect();
+    assert_eq!(names, vec![("Point", 2), ("Path", 2)]);
+}
+
+#[test]
+fn parse_enum_and_subrange_types() {
+    let mut parser = Parser::new(Lexer::new(
+        "TYPE
+            Mode : (Idle, Run, Fault);
+            Level : (Low := -1, Mid, High := 5) SINT;
+            Percent : INT (0..100);
+        END_TYPE"
+            .to_string(),
+    ));
+    let pous = match parser.parse().unwrap() {
+        Node::CompilationUnit(unit) => unit.pous,
+        node => panic!("Expected a compilation unit, found {:?}", node),
+    };
+    let definitions: Vec<_> = pous
+        .into_iter()
+        .map(|pou| match pou {
+            Node::TypeDecl(type_decl) => type_decl.definition,
+            node => panic!("Expected a type declaration, found {:?}", node),
+        })
+        .collect();
+    match &definitions[..] {
+        [TypeDefinition::Enum(modes, int), TypeDefinition::Enum(levels, sint), TypeDefinition::Derived(percent)] =>
+        {
+            assert_eq!(modes[2], ("Fault".to_string(), 2));
+            assert_eq!(int.name, "INT");
+            let values: Vec<_> = levels.iter().map(|(_, value)| *value).collect();
+            assert_eq!(values, vec![-1, 0, 5]);
+            assert_eq!(sint.name, "SINT");
+            assert_eq!(
+                (percent.name.as_str(), percent.range),
+                ("INT", Some((0, 100)))
+            );
+        }
+        definitions => panic!("Unexpected type definitions {:?}", definitions),
+    }
+
+    let program = parse_program("PROGRAM main mode := Mode#Run; END_PROGRAM");
+    match *program.body {
+        Node::CompoundStatement(body) => match &body.statements[0] {
+            Node::Assignment(assignment) => {
+                assert_eq!(
+                    *assignment.right,
+                    Node::EnumValue(EnumValue::new(
+                        Token::Id("Mode".to_string()),
+                        Token::Id("Run".to_string())
+                    ))
+                );
+                assert_eq!(assignment.right.span(), Span::new(21, 29, 1, 22));
+            }
+            node => panic!("Expected an assignment, found {:?}", node),
+        },
+        node => panic!("Expected statements, found {:?}", node),
+    }
+
+    let mut parser = Parser::new(Lexer::new(
+        "PROGRAM main VAR a : INT (5..1); END_VAR END_PROGRAM".to_string(),
+    ));
+    assert_eq!(
+        parser.parse(),
+        Err(ParseError::InvalidSubrange(Span::new(26, 30, 1, 27)))
+    );
+}
+
+#[test]
+fn parse_configuration() {
+    let mut parser = Parser::new(Lexer::new(
+        "CONFIGURATION plant
+            RESOURCE cpu ON PLC
+                TASK fast(INTERVAL := T#10ms, PRIORITY := 1);
+                PROGRAM p1 WITH fast : Main;
+                PROGRAM p2 : Main;
+            END_RESOURCE
+        END_CONFIGURATION"
+            .to_string(),
+    ));
+    let configuration = match parser.parse().unwrap() {
+        Node::CompilationUnit(unit) => match unit.pous.into_iter().next() {
+            Some(Node::Configuration(configuration)) => configuration,
+            pou => panic!("Expected a configuration, found {:?}", pou),
+        },
+        node => panic!("Expected a compilation unit, found {:?}", node),
+    };
+    assert_eq!(configuration.name, "plant");
+    let resource = &configuration.resources[0];
+    assert_eq!(resource.name, "cpu");
+    let task = &resource.tasks[0];
+    assert_eq!(task.name, "fast");
+    let parameters: Vec<_> = task
+        .arguments
+        .iter()
+        .map(|argument| argument.name.as_deref().unwrap())
+        .collect();
+    assert_eq!(parameters, vec!["INTERVAL", "PRIORITY"]);
+    let programs: Vec<_> = resource
+        .programs
+        .iter()
+        .map(|program| {
+            (
+                program.name.as_str(),
+                program.task.as_deref(),
+                program.program_type.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        programs,
+        vec![("p1", Some("fast"), "Main"), ("p2", None, "Main")]
+    );
 }
@@ -0,0 +1,54 @@
+use super::{get_bool, set, StandardFunctionBlock};
+use crate::identifiers::Identifiers;
+use crate::object::Object;
+
+/// Set-dominant bistable.
+pub const SR: StandardFunctionBlock = StandardFunctionBlock {
+    declaration: "FUNCTION_BLOCK SR
+        VAR_INPUT S1, R : BOOL; END_VAR
+        VAR_OUTPUT Q1 : BOOL; END_VAR
+    END_FUNCTION_BLOCK",
+    body: sr,
+};
+
+/// Reset-dominant bistable.
+pub const RS: StandardFunctionBlock = StandardFunctionBlock {
+    declaration: "FUNCTION_BLOCK RS
+        VAR_INPUT S, R1 : BOOL; END_VAR
+        VAR_OUTPUT Q1 : BOOL; END_VAR
+    END_FUNCTION_BLOCK",
+    body: rs,
+};
+
+fn sr(vars: &mut Identifiers<Object>, _now: i64) {
+    let q1 = get_bool(vars, "S1") || (!get_bool(vars, "R") && get_bool(vars, "Q1"));
+    set(vars, "Q1", Object::Bool(q1));
+}
+
+fn rs(vars: &mut Identifiers<Object>, _now: i64) {
+    let q1 = !get_bool(vars, "R1") && (get_bool(vars, "S") || get_bool(vars, "Q1"));
+    set(vars, "Q1", Object::Bool(q1));
+}
+
+#[test]
+fn bistables_latch_with_dominant_input() {
+    let steps = [(true, false), (false, false), (true, true), (false, true)];
+    for (function_block, set_id, reset_id, expected) in [
+        (SR, "S1", "R", [true, true, true, false]),
+        (RS, "S", "R1", [true, true, false, false]),
+    ]
+    .iter()
+    {
+        let mut vars = super::instance(function_block);
+        let outputs: Vec<bool> = steps
+            .iter()
+            .map(|&(s, r)| {
+                set(&mut vars, set_id, Object::Bool(s));
+                set(&mut vars, reset_id, Object::Bool(r));
+                (function_block.body)(&mut vars, 0);
+                get_bool(&vars, "Q1")
+            })
+            .collect();
+        assert_eq!(outputs, expected.to_vec());
+    }
+}
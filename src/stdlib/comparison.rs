@@ -0,0 +1,81 @@
+use std::cmp::Ordering;
+
+use super::StandardFunction;
+use crate::error::RuntimeError;
+use crate::object::Object;
+
+pub const FUNCTIONS: &[(&str, StandardFunction)] = &[
+    (
+        "GT",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: Some(3),
+            body: |_, inputs| chain(inputs, |ordering| ordering == Ordering::Greater),
+        },
+    ),
+    (
+        "GE",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: Some(3),
+            body: |_, inputs| chain(inputs, |ordering| ordering != Ordering::Less),
+        },
+    ),
+    (
+        "EQ",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: Some(3),
+            body: |_, inputs| chain(inputs, |ordering| ordering == Ordering::Equal),
+        },
+    ),
+    (
+        "LE",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: Some(3),
+            body: |_, inputs| chain(inputs, |ordering| ordering != Ordering::Greater),
+        },
+    ),
+    (
+        "LT",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: Some(3),
+            body: |_, inputs| chain(inputs, |ordering| ordering == Ordering::Less),
+        },
+    ),
+    (
+        "NE",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: None,
+            body: |_, inputs| chain(inputs, |ordering| ordering != Ordering::Equal),
+        },
+    ),
+];
+
+/// TRUE if every pair of neighbouring inputs is ordered as `accept` requires,
+/// e.g. GT(a, b, c) is `a > b AND b > c`. Comparisons with NaN are FALSE.
+fn chain(inputs: &[Object], accept: fn(Ordering) -> bool) -> Result<Object, RuntimeError> {
+    for pair in inputs.windows(2) {
+        match pair[0].clone().compare(pair[1].clone())? {
+            Some(ordering) if accept(ordering) => continue,
+            _ => return Ok(Object::Bool(false)),
+        }
+    }
+    Ok(Object::Bool(true))
+}
+
+#[test]
+fn comparisons_chain_over_all_inputs() {
+    let gt = |inputs: &[Object]| chain(inputs, |ordering| ordering == Ordering::Greater);
+    assert_eq!(
+        gt(&[Object::Int(3), Object::Int(2), Object::Int(1)]),
+        Ok(Object::Bool(true))
+    );
+    assert_eq!(
+        gt(&[Object::Int(3), Object::Int(2), Object::Int(2)]),
+        Ok(Object::Bool(false))
+    );
+}
@@ -0,0 +1,589 @@
+use std::convert::TryFrom;
+
+use super::{check_input, StandardFunction};
+use crate::ast::Num;
+use crate::error::RuntimeError;
+use crate::lexer::Lexer;
+use crate::object::{DataType, Object, DEFAULT_STRING_LENGTH};
+use crate::token::Token;
+
+/// `<from>_TO_<to>` and the generic `TO_<to>`.
+static CONVERSION: StandardFunction = StandardFunction {
+    inputs: &["IN"],
+    extensible: None,
+    body: conversion,
+};
+
+/// `TRUNC` and `<from>_TRUNC_<to>`.
+static TRUNC: StandardFunction = StandardFunction {
+    inputs: &["IN"],
+    extensible: None,
+    body: trunc,
+};
+
+/// `BCD_TO_<to>` and `<from>_BCD_TO_<to>`.
+static BCD_TO_INTEGER: StandardFunction = StandardFunction {
+    inputs: &["IN"],
+    extensible: None,
+    body: bcd_to_integer,
+};
+
+/// `<from>_TO_BCD` and `<from>_TO_BCD_<to>`.
+static INTEGER_TO_BCD: StandardFunction = StandardFunction {
+    inputs: &["IN"],
+    extensible: None,
+    body: integer_to_bcd,
+};
+
+/// The types taking part in the conversion functions.
+const TYPES: &[DataType] = &[
+    DataType::Bool,
+    DataType::SInt,
+    DataType::Int,
+    DataType::DInt,
+    DataType::LInt,
+    DataType::USInt,
+    DataType::UInt,
+    DataType::UDInt,
+    DataType::ULInt,
+    DataType::Byte,
+    DataType::Word,
+    DataType::DWord,
+    DataType::LWord,
+    DataType::Real,
+    DataType::LReal,
+    DataType::Time,
+    DataType::LTime,
+    DataType::Date,
+    DataType::TimeOfDay,
+    DataType::DateAndTime,
+    DataType::String(DEFAULT_STRING_LENGTH),
+    DataType::WString(DEFAULT_STRING_LENGTH),
+];
+
+/// The unsigned integer type with the width of each bit string type, as
+/// used by the BCD conversions.
+const BCD_TYPES: &[(DataType, DataType)] = &[
+    (DataType::Byte, DataType::USInt),
+    (DataType::Word, DataType::UInt),
+    (DataType::DWord, DataType::UDInt),
+    (DataType::LWord, DataType::ULInt),
+];
+
+pub fn functions() -> Vec<(String, &'static StandardFunction)> {
+    let mut functions = vec![("TRUNC".to_string(), &TRUNC)];
+    for to in TYPES {
+        for to_name in names(to) {
+            functions.push((format!("TO_{}", to_name), &CONVERSION));
+            for from in TYPES.iter().filter(|from| convertible(from, to)) {
+                for from_name in names(from) {
+                    functions.push((format!("{}_TO_{}", from_name, to_name), &CONVERSION));
+                }
+            }
+        }
+    }
+    for integer in TYPES.iter().filter(|data_type| data_type.is_integer()) {
+        functions.push((format!("REAL_TRUNC_{}", integer), &TRUNC));
+        functions.push((format!("LREAL_TRUNC_{}", integer), &TRUNC));
+        functions.push((format!("BCD_TO_{}", integer), &BCD_TO_INTEGER));
+        functions.push((format!("{}_TO_BCD", integer), &INTEGER_TO_BCD));
+    }
+    for (bits, integer) in BCD_TYPES {
+        functions.push((format!("{}_TO_BCD", bits), &INTEGER_TO_BCD));
+        functions.push((format!("{}_BCD_TO_{}", bits, integer), &BCD_TO_INTEGER));
+        functions.push((format!("{}_TO_BCD_{}", integer, bits), &INTEGER_TO_BCD));
+    }
+    functions
+}
+
+/// The names of a type in function names, including the short forms TOD and
+/// DT.
+fn names(data_type: &DataType) -> Vec<&str> {
+    match data_type {
+        DataType::TimeOfDay => vec!["TIME_OF_DAY", "TOD"],
+        DataType::DateAndTime => vec!["DATE_AND_TIME", "DT"],
+        _ => vec![data_type.name()],
+    }
+}
+
+/// Whether the standard defines a conversion function from `from` to `to`.
+/// Every elementary type converts to and from the string types. Durations
+/// convert to and from numbers and bit strings other than BOOL, the dates
+/// only to each other in `dates.rs`. Reals convert to and from integers, and
+/// to and from the bit string of the same width by binary transfer.
+fn convertible(from: &DataType, to: &DataType) -> bool {
+    use DataType::*;
+    if from.name() == to.name() {
+        return false;
+    }
+    if from.is_string() || to.is_string() {
+        return true;
+    }
+    if from.is_date() || to.is_date() {
+        return false;
+    }
+    if from.is_duration() || to.is_duration() {
+        return *from != Bool && *to != Bool;
+    }
+    match (from.is_real(), to.is_real()) {
+        (false, false) | (true, true) => true,
+        (true, false) => to.is_integer() || matches!((from, to), (Real, DWord) | (LReal, LWord)),
+        (false, true) => from.is_integer() || matches!((from, to), (DWord, Real) | (LWord, LReal)),
+    }
+}
+
+/// Converts `value` to `to`, reporting a value that does not fit as a runtime
+/// error instead of wrapping it. Reals are rounded to the nearest integer,
+/// ties to even. An integer and a bit string of the same width, such as INT
+/// and WORD, are converted by binary transfer. A value converts to a string
+/// as its literal, and a string back by reading such a literal.
+fn convert(value: &Object, to: &DataType) -> Result<Object, RuntimeError> {
+    let from = value.data_type();
+    if from == *to {
+        return Ok(value.clone());
+    }
+    let out_of_range = || RuntimeError::OutOfRange {
+        value: value.to_string(),
+        range: to.to_string(),
+    };
+    match (value, to) {
+        (Object::String(text, _), _) | (Object::WString(text, _), _) if !to.is_string() => {
+            let literal = parse_literal(text)
+                .filter(|literal| {
+                    literal.data_type() == *to || convertible(&literal.data_type(), to)
+                })
+                .ok_or_else(|| RuntimeError::InvalidConversion {
+                    text: text.clone(),
+                    to: to.clone(),
+                })?;
+            return convert(&literal, to);
+        }
+        (Object::String(text, _), _) | (Object::WString(text, _), _) => {
+            return Ok(text_object(text.clone(), to))
+        }
+        (_, DataType::String(_)) | (_, DataType::WString(_)) => {
+            return Ok(text_object(value.to_string(), to))
+        }
+        _ if from.is_duration() || to.is_duration() => return convert_duration(value, to),
+        (Object::Real(real), DataType::DWord) => return Ok(Object::DWord(real.to_bits())),
+        (Object::LReal(real), DataType::LWord) => return Ok(Object::LWord(real.to_bits())),
+        (Object::DWord(bits), DataType::Real) => return Ok(Object::Real(f32::from_bits(*bits))),
+        (Object::LWord(bits), DataType::LReal) => return Ok(Object::LReal(f64::from_bits(*bits))),
+        _ => {}
+    }
+
+    if to.is_real() {
+        let real = value.as_f64().unwrap();
+        if *to == DataType::Real && real.is_finite() && real.abs() > f64::from(f32::MAX) {
+            return Err(out_of_range());
+        }
+        return Ok(value.convert(to.clone()));
+    }
+    let transfer = from.width() == to.width()
+        && ((from.is_integer() && to.is_bit()) || (from.is_bit() && to.is_integer()));
+    if transfer {
+        return Ok(value.convert(to.clone()));
+    }
+    let integer = match value.as_i128() {
+        Some(integer) => integer,
+        None => {
+            let real = value.as_f64().unwrap().round_ties_even();
+            if !real.is_finite() {
+                return Err(out_of_range());
+            }
+            real as i128
+        }
+    };
+    let (min, max) = to.range();
+    if integer < min || integer > max {
+        return Err(out_of_range());
+    }
+    Ok(Object::from_i128(integer, to.clone()))
+}
+
+/// A STRING or WSTRING, as `to`, holding `text`.
+fn text_object(text: String, to: &DataType) -> Object {
+    match to {
+        DataType::String(_) => Object::string(text),
+        _ => Object::wstring(text),
+    }
+}
+
+/// The value of the single literal in `text`, such as `-42`, `1.5E3`, `TRUE`
+/// or `T#1s`, ignoring surrounding whitespace.
+fn parse_literal(text: &str) -> Option<Object> {
+    let mut lexer = Lexer::new(text.to_string());
+    let mut token = lexer.get_next_token().ok()?;
+    let negative = token.token == Token::Minus;
+    if negative {
+        token = lexer.get_next_token().ok()?;
+    }
+    let value = match token.token {
+        Token::Integer(..)
+        | Token::Real(..)
+        | Token::Bool(_)
+        | Token::Time(_)
+        | Token::LTime(_)
+        | Token::Date(_)
+        | Token::TimeOfDay(_)
+        | Token::DateAndTime(_) => Num::new(token).value,
+        _ => return None,
+    };
+    if lexer.get_next_token().ok()?.token != Token::Eof {
+        return None;
+    }
+    if negative {
+        value.neg().ok()
+    } else {
+        Some(value)
+    }
+}
+
+/// Converts a duration to or from a number of milliseconds for TIME and of
+/// nanoseconds for LTIME, or between TIME and LTIME. Durations convert to
+/// integers and bit strings by truncating towards zero, and reals to
+/// durations are rounded to the nearest nanosecond.
+fn convert_duration(value: &Object, to: &DataType) -> Result<Object, RuntimeError> {
+    let unit = |data_type: &DataType| match data_type {
+        DataType::Time => 1_000_000,
+        _ => 1,
+    };
+    let out_of_range = || RuntimeError::OutOfRange {
+        value: value.to_string(),
+        range: to.to_string(),
+    };
+    if let Some(nanoseconds) = value.as_nanoseconds() {
+        let unit = unit(&value.data_type());
+        return match to {
+            _ if to.is_duration() => Ok(Object::from_nanoseconds(nanoseconds, to)),
+            _ if to.is_real() => convert(&Object::LReal(nanoseconds as f64 / unit as f64), to),
+            _ => convert(&Object::LInt(nanoseconds / unit), to).map_err(|_| out_of_range()),
+        };
+    }
+    let unit = unit(to);
+    let nanoseconds = match value.as_i128() {
+        Some(integer) => integer.checked_mul(unit.into()),
+        None => {
+            let real = (value.as_f64().unwrap() * unit as f64).round_ties_even();
+            Some(real as i128).filter(|_| real.is_finite())
+        }
+    };
+    nanoseconds
+        .and_then(|nanoseconds| i64::try_from(nanoseconds).ok())
+        .map(|nanoseconds| Object::from_nanoseconds(nanoseconds, to))
+        .ok_or_else(out_of_range)
+}
+
+/// `value` as the input type `from` of a typed function such as
+/// `INT_TO_REAL`. It takes the values an assignment to `from` takes, but a
+/// value outside the range of `from` is an error instead of wrapping.
+fn typed_input(value: &Object, from: &DataType) -> Result<Object, RuntimeError> {
+    let data_type = value.data_type();
+    if data_type.name() == from.name() {
+        return Ok(value.clone());
+    }
+    let out_of_range = || RuntimeError::OutOfRange {
+        value: value.to_string(),
+        range: from.to_string(),
+    };
+    let integral = |data_type: &DataType| {
+        data_type.is_integer() || (data_type.is_bit() && *data_type != DataType::Bool)
+    };
+    if from.is_real() && data_type.is_numeric() {
+        let real = value.as_f64().unwrap();
+        if *from == DataType::Real && real.is_finite() && real.abs() > f64::from(f32::MAX) {
+            return Err(out_of_range());
+        }
+        return Ok(value.convert(from.clone()));
+    }
+    if integral(from) && integral(&data_type) {
+        let integer = value.as_i128().unwrap();
+        let (min, max) = from.range();
+        if integer < min || integer > max {
+            return Err(out_of_range());
+        }
+        return Ok(Object::from_i128(integer, from.clone()));
+    }
+    Err(RuntimeError::InvalidAssignment {
+        from: data_type,
+        to: from.clone(),
+    })
+}
+
+fn conversion(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let (input, to) = match name.strip_prefix("TO_") {
+        Some(to) => (inputs[0].clone(), to),
+        None => {
+            let (from, to) = name.split_once("_TO_").unwrap();
+            (
+                typed_input(&inputs[0], &DataType::from_name(from).unwrap())?,
+                to,
+            )
+        }
+    };
+    let to = DataType::from_name(to).unwrap();
+    if input.data_type().name() != to.name() && !convertible(&input.data_type(), &to) {
+        return Err(RuntimeError::InvalidOperand {
+            operation: name.to_string(),
+            data_type: input.data_type(),
+        });
+    }
+    convert(&input, &to)
+}
+
+/// Truncates a real towards zero. The generic TRUNC returns a DINT for a
+/// REAL and a LINT for a LREAL.
+fn trunc(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let (input, to) = match name.split_once("_TRUNC_") {
+        Some((from, to)) => (
+            typed_input(&inputs[0], &DataType::from_name(from).unwrap())?,
+            DataType::from_name(to).unwrap(),
+        ),
+        None => {
+            check_input(name, &inputs[0], "ANY_REAL", DataType::is_real)?;
+            let to = match inputs[0] {
+                Object::Real(_) => DataType::DInt,
+                _ => DataType::LInt,
+            };
+            (inputs[0].clone(), to)
+        }
+    };
+    let truncated = Object::LReal(input.as_f64().unwrap().trunc()).convert(input.data_type());
+    convert(&truncated, &to)
+}
+
+/// Decodes a BCD bit string. The generic form also takes an integer, such as
+/// the untyped literal in `BCD_TO_INT(16#12)`.
+fn bcd_to_integer(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let (from, to) = name.split_once("BCD_TO_").unwrap();
+    let input = match from.strip_suffix('_') {
+        Some(from) => typed_input(&inputs[0], &DataType::from_name(from).unwrap())?,
+        None => {
+            check_input(name, &inputs[0], "ANY_BIT", |data_type| {
+                data_type.is_integer() || (data_type.is_bit() && *data_type != DataType::Bool)
+            })?;
+            inputs[0].clone()
+        }
+    };
+    let bits = input.as_i128().unwrap();
+    if bits < 0 {
+        return Err(RuntimeError::OutOfRange {
+            value: bits.to_string(),
+            range: "BCD".to_string(),
+        });
+    }
+    let (mut digits, mut value, mut scale) = (bits, 0, 1);
+    while digits > 0 {
+        if digits & 0xF > 9 {
+            return Err(RuntimeError::OutOfRange {
+                value: format!("16#{:X}", bits),
+                range: "BCD".to_string(),
+            });
+        }
+        value += (digits & 0xF) * scale;
+        digits >>= 4;
+        scale *= 10;
+    }
+    convert(
+        &Object::ULInt(value as u64),
+        &DataType::from_name(to).unwrap(),
+    )
+}
+
+/// Encodes an unsigned value in BCD. Without an explicit result type it is
+/// the bit string as wide as the input type.
+fn integer_to_bcd(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let (from, to) = name.split_once("_TO_BCD").unwrap();
+    let from = DataType::from_name(from).unwrap();
+    let to = match to.strip_prefix('_') {
+        Some(to) => DataType::from_name(to).unwrap(),
+        None => match from.width() {
+            8 => DataType::Byte,
+            16 => DataType::Word,
+            32 => DataType::DWord,
+            _ => DataType::LWord,
+        },
+    };
+    let value = typed_input(&inputs[0], &from)?.as_i128().unwrap();
+    let (mut rest, mut bits, mut shift) = (value, 0, 0);
+    while rest > 0 {
+        bits |= (rest % 10) << shift;
+        rest /= 10;
+        shift += 4;
+    }
+    if value < 0 || bits > to.range().1 {
+        return Err(RuntimeError::OutOfRange {
+            value: value.to_string(),
+            range: format!("BCD {}", to),
+        });
+    }
+    Ok(Object::from_i128(bits, to))
+}
+
+#[test]
+fn conversions_round_and_check_range() {
+    let cases = [
+        ("REAL_TO_INT", Object::Real(2.5), Ok(Object::Int(2))),
+        ("REAL_TO_INT", Object::Real(3.5), Ok(Object::Int(4))),
+        ("LREAL_TO_DINT", Object::LReal(-1.5), Ok(Object::DInt(-2))),
+        ("WORD_TO_INT", Object::Word(0xFFFF), Ok(Object::Int(-1))),
+        ("INT_TO_REAL", Object::Int(-3), Ok(Object::Real(-3.0))),
+        ("TO_USINT", Object::Int(200), Ok(Object::USInt(200))),
+        (
+            "REAL_TO_DWORD",
+            Object::Real(1.0),
+            Ok(Object::DWord(0x3F80_0000)),
+        ),
+        (
+            "INT_TO_SINT",
+            Object::Int(300),
+            Err("300 is out of range for SINT"),
+        ),
+        (
+            "INT_TO_UDINT",
+            Object::Int(-1),
+            Err("-1 is out of range for UDINT"),
+        ),
+        (
+            "LREAL_TO_REAL",
+            Object::LReal(1e39),
+            Err("1e39 is out of range for REAL"),
+        ),
+        (
+            "LREAL_TO_REAL",
+            Object::LReal(1e300),
+            Err("1e300 is out of range for REAL"),
+        ),
+        (
+            "TIME_TO_DINT",
+            Object::Time(1_500_000_000),
+            Ok(Object::DInt(1500)),
+        ),
+        (
+            "LTIME_TO_LINT",
+            Object::LTime(1_500),
+            Ok(Object::LInt(1500)),
+        ),
+        ("TIME_TO_LTIME", Object::Time(7), Ok(Object::LTime(7))),
+        (
+            "DINT_TO_TIME",
+            Object::DInt(-20),
+            Ok(Object::Time(-20_000_000)),
+        ),
+        ("REAL_TO_TIME", Object::Real(0.5), Ok(Object::Time(500_000))),
+        (
+            "TIME_TO_SINT",
+            Object::Time(1_000_000_000),
+            Err("T#1s is out of range for SINT"),
+        ),
+        (
+            "LINT_TO_TIME",
+            Object::LInt(i64::MAX),
+            Err("9223372036854775807 is out of range for TIME"),
+        ),
+        (
+            "INT_TO_STRING",
+            Object::Int(-42),
+            Ok(Object::string("-42".into())),
+        ),
+        (
+            "REAL_TO_STRING",
+            Object::Real(1.5),
+            Ok(Object::string("1.5".into())),
+        ),
+        (
+            "TIME_TO_STRING",
+            Object::Time(1_000_000),
+            Ok(Object::string("T#1ms".into())),
+        ),
+        (
+            "STRING_TO_INT",
+            Object::string(" -42 ".into()),
+            Ok(Object::Int(-42)),
+        ),
+        (
+            "STRING_TO_REAL",
+            Object::string("1.5E3".into()),
+            Ok(Object::Real(1500.0)),
+        ),
+        (
+            "STRING_TO_TIME",
+            Object::string("T#2s".into()),
+            Ok(Object::Time(2_000_000_000)),
+        ),
+        (
+            "STRING_TO_BOOL",
+            Object::string("TRUE".into()),
+            Ok(Object::Bool(true)),
+        ),
+        (
+            "STRING_TO_INT",
+            Object::string("40000".into()),
+            Err("40000 is out of range for INT"),
+        ),
+        (
+            "STRING_TO_INT",
+            Object::string("12x".into()),
+            Err("'12x' cannot be converted to INT"),
+        ),
+        (
+            "STRING_TO_DINT",
+            Object::string("T#1s".into()),
+            Ok(Object::DInt(1000)),
+        ),
+    ];
+    for (name, input, expected) in cases.iter() {
+        let result =
+            conversion(name, std::slice::from_ref(input)).map_err(|error| error.to_string());
+        assert_eq!(result, expected.clone().map_err(String::from), "{}", name);
+    }
+}
+
+#[test]
+fn trunc_and_bcd() {
+    assert_eq!(trunc("TRUNC", &[Object::Real(-2.7)]), Ok(Object::DInt(-2)));
+    assert_eq!(
+        trunc("LREAL_TRUNC_SINT", &[Object::LReal(127.9)]),
+        Ok(Object::SInt(127))
+    );
+    assert_eq!(
+        bcd_to_integer("BCD_TO_INT", &[Object::Word(0x1234)]),
+        Ok(Object::Int(1234))
+    );
+    assert_eq!(
+        integer_to_bcd("UINT_TO_BCD_WORD", &[Object::UInt(9876)]),
+        Ok(Object::Word(0x9876))
+    );
+    assert_eq!(
+        bcd_to_integer("BCD_TO_INT", &[Object::Byte(0x1A)])
+            .unwrap_err()
+            .to_string(),
+        "16#1A is out of range for BCD"
+    );
+    assert_eq!(
+        integer_to_bcd("INT_TO_BCD", &[Object::Int(-5)])
+            .unwrap_err()
+            .to_string(),
+        "-5 is out of range for BCD WORD"
+    );
+    assert_eq!(
+        integer_to_bcd("WORD_TO_BCD", &[Object::Word(1234)]),
+        Ok(Object::Word(0x1234))
+    );
+    assert_eq!(
+        integer_to_bcd("BYTE_TO_BCD", &[Object::Byte(100)])
+            .unwrap_err()
+            .to_string(),
+        "100 is out of range for BCD BYTE"
+    );
+    assert_eq!(
+        bcd_to_integer("BCD_TO_INT", &[Object::DInt(0x12)]),
+        Ok(Object::Int(12))
+    );
+    assert_eq!(
+        bcd_to_integer("BCD_TO_INT", &[Object::DInt(-1)])
+            .unwrap_err()
+            .to_string(),
+        "-1 is out of range for BCD"
+    );
+}
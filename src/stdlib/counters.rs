@@ -0,0 +1,144 @@
+use super::{get_bool, get_int, rising_edge, set, StandardFunctionBlock};
+use crate::identifiers::Identifiers;
+use crate::object::Object;
+
+/// Up counter: CV counts rising edges of CU, Q is set once CV reaches PV.
+pub const CTU: StandardFunctionBlock = StandardFunctionBlock {
+    declaration: "FUNCTION_BLOCK CTU
+        VAR_INPUT CU, R : BOOL; PV : INT; END_VAR
+        VAR_OUTPUT Q : BOOL; CV : INT; END_VAR
+        VAR previous_cu : BOOL; END_VAR
+    END_FUNCTION_BLOCK",
+    body: ctu,
+};
+
+/// Down counter: LD loads PV into CV, rising edges of CD count down and Q
+/// is set once CV reaches zero.
+pub const CTD: StandardFunctionBlock = StandardFunctionBlock {
+    declaration: "FUNCTION_BLOCK CTD
+        VAR_INPUT CD, LD : BOOL; PV : INT; END_VAR
+        VAR_OUTPUT Q : BOOL; CV : INT; END_VAR
+        VAR previous_cd : BOOL; END_VAR
+    END_FUNCTION_BLOCK",
+    body: ctd,
+};
+
+/// Up-down counter. Simultaneous edges on CU and CD cancel out.
+pub const CTUD: StandardFunctionBlock = StandardFunctionBlock {
+    declaration: "FUNCTION_BLOCK CTUD
+        VAR_INPUT CU, CD, R, LD : BOOL; PV : INT; END_VAR
+        VAR_OUTPUT QU, QD : BOOL; CV : INT; END_VAR
+        VAR previous_cu, previous_cd : BOOL; END_VAR
+    END_FUNCTION_BLOCK",
+    body: ctud,
+};
+
+fn ctu(vars: &mut Identifiers<Object>, _now: i64) {
+    let up = rising_edge(vars, "CU", "previous_cu");
+    let mut cv = get_int(vars, "CV");
+    if get_bool(vars, "R") {
+        cv = 0;
+    } else if up && cv < i16::MAX {
+        cv += 1;
+    }
+    set(vars, "CV", Object::Int(cv));
+    set(vars, "Q", Object::Bool(cv >= get_int(vars, "PV")));
+}
+
+fn ctd(vars: &mut Identifiers<Object>, _now: i64) {
+    let down = rising_edge(vars, "CD", "previous_cd");
+    let mut cv = get_int(vars, "CV");
+    if get_bool(vars, "LD") {
+        cv = get_int(vars, "PV");
+    } else if down && cv > i16::MIN {
+        cv -= 1;
+    }
+    set(vars, "CV", Object::Int(cv));
+    set(vars, "Q", Object::Bool(cv <= 0));
+}
+
+fn ctud(vars: &mut Identifiers<Object>, _now: i64) {
+    let up = rising_edge(vars, "CU", "previous_cu");
+    let down = rising_edge(vars, "CD", "previous_cd");
+    let mut cv = get_int(vars, "CV");
+    if get_bool(vars, "R") {
+        cv = 0;
+    } else if get_bool(vars, "LD") {
+        cv = get_int(vars, "PV");
+    } else if up && !down && cv < i16::MAX {
+        cv += 1;
+    } else if down && !up && cv > i16::MIN {
+        cv -= 1;
+    }
+    set(vars, "CV", Object::Int(cv));
+    set(vars, "QU", Object::Bool(cv >= get_int(vars, "PV")));
+    set(vars, "QD", Object::Bool(cv <= 0));
+}
+
+#[test]
+fn ctu_counts_rising_edges() {
+    let mut vars = super::instance(&CTU);
+    set(&mut vars, "PV", Object::Int(2));
+    let steps = [
+        (true, false),
+        (true, false),
+        (false, false),
+        (true, false),
+        (false, true),
+    ];
+    let outputs: Vec<(bool, i16)> = steps
+        .iter()
+        .map(|&(cu, r)| {
+            set(&mut vars, "CU", Object::Bool(cu));
+            set(&mut vars, "R", Object::Bool(r));
+            ctu(&mut vars, 0);
+            (get_bool(&vars, "Q"), get_int(&vars, "CV"))
+        })
+        .collect();
+    assert_eq!(
+        outputs,
+        vec![(false, 1), (false, 1), (false, 1), (true, 2), (false, 0)]
+    );
+}
+
+#[test]
+fn ctud_loads_and_counts_both_ways() {
+    let mut vars = super::instance(&CTUD);
+    set(&mut vars, "PV", Object::Int(2));
+    // (CU, CD, LD)
+    let steps = [
+        (false, false, true),
+        (false, true, false),
+        (false, false, false),
+        (true, true, false),
+        (false, false, false),
+        (false, true, false),
+        (true, false, false),
+    ];
+    let outputs: Vec<(bool, bool, i16)> = steps
+        .iter()
+        .map(|&(cu, cd, ld)| {
+            set(&mut vars, "CU", Object::Bool(cu));
+            set(&mut vars, "CD", Object::Bool(cd));
+            set(&mut vars, "LD", Object::Bool(ld));
+            ctud(&mut vars, 0);
+            (
+                get_bool(&vars, "QU"),
+                get_bool(&vars, "QD"),
+                get_int(&vars, "CV"),
+            )
+        })
+        .collect();
+    assert_eq!(
+        outputs,
+        vec![
+            (true, false, 2),
+            (false, false, 1),
+            (false, false, 1),
+            (false, false, 1),
+            (false, false, 1),
+            (false, true, 0),
+            (false, false, 1),
+        ]
+    );
+}
@@ -0,0 +1,165 @@
+use super::StandardFunction;
+use crate::calendar::NANOSECONDS_PER_DAY;
+use crate::error::RuntimeError;
+use crate::object::{DataType, Object};
+
+/// The typed forms of the arithmetic operators, e.g. `ADD_TOD_TIME`.
+static OPERATION: StandardFunction = StandardFunction {
+    inputs: &["IN1", "IN2"],
+    extensible: None,
+    body: operation,
+};
+
+/// The named operations with the types of their inputs.
+const OPERATIONS: &[(&str, &str, &str)] = &[
+    ("ADD_TIME", "TIME", "TIME"),
+    ("ADD_LTIME", "LTIME", "LTIME"),
+    ("ADD_TOD_TIME", "TOD", "TIME"),
+    ("ADD_DT_TIME", "DT", "TIME"),
+    ("SUB_TIME", "TIME", "TIME"),
+    ("SUB_LTIME", "LTIME", "LTIME"),
+    ("SUB_DATE_DATE", "DATE", "DATE"),
+    ("SUB_TOD_TIME", "TOD", "TIME"),
+    ("SUB_TOD_TOD", "TOD", "TOD"),
+    ("SUB_DT_TIME", "DT", "TIME"),
+    ("SUB_DT_DT", "DT", "DT"),
+    ("MUL_TIME", "TIME", "ANY_NUM"),
+    ("MUL_LTIME", "LTIME", "ANY_NUM"),
+    ("DIV_TIME", "TIME", "ANY_NUM"),
+    ("DIV_LTIME", "LTIME", "ANY_NUM"),
+];
+
+pub const FUNCTIONS: &[(&str, StandardFunction)] = &[
+    (
+        "CONCAT_DATE_TOD",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: None,
+            body: concat_date_tod,
+        },
+    ),
+    (
+        "DT_TO_DATE",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: split_date_and_time,
+        },
+    ),
+    (
+        "DT_TO_TOD",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: split_date_and_time,
+        },
+    ),
+    (
+        "DATE_AND_TIME_TO_DATE",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: split_date_and_time,
+        },
+    ),
+    (
+        "DATE_AND_TIME_TO_TIME_OF_DAY",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: split_date_and_time,
+        },
+    ),
+];
+
+pub fn functions() -> impl Iterator<Item = (String, &'static StandardFunction)> {
+    OPERATIONS
+        .iter()
+        .map(|(name, _, _)| (name.to_string(), &OPERATION))
+}
+
+/// Checks that the input of function `name` has the type named `expected`,
+/// or any number for ANY_NUM.
+fn check_type(name: &str, input: &Object, expected: &str) -> Result<(), RuntimeError> {
+    let valid = match DataType::from_name(expected) {
+        Some(data_type) => input.data_type() == data_type,
+        None => input.data_type().is_numeric(),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(RuntimeError::UnexpectedType {
+            context: format!("input of {}", name),
+            expected: expected.to_string(),
+            found: input.data_type(),
+        })
+    }
+}
+
+fn operation(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let (_, first, second) = OPERATIONS
+        .iter()
+        .find(|(operation, _, _)| *operation == name)
+        .unwrap();
+    check_type(name, &inputs[0], first)?;
+    check_type(name, &inputs[1], second)?;
+    let (lhs, rhs) = (inputs[0].clone(), inputs[1].clone());
+    match &name[..3] {
+        "ADD" => lhs.add(rhs),
+        "SUB" => lhs.sub(rhs),
+        "MUL" => lhs.mul(rhs),
+        _ => lhs.div(rhs),
+    }
+}
+
+fn concat_date_tod(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    check_type(name, &inputs[0], "DATE")?;
+    check_type(name, &inputs[1], "TOD")?;
+    let date = inputs[0].as_nanoseconds().unwrap();
+    Ok(Object::DateAndTime(
+        date.wrapping_add(inputs[1].as_nanoseconds().unwrap()),
+    ))
+}
+
+/// The date or the time of day part of a DATE_AND_TIME.
+fn split_date_and_time(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    check_type(name, &inputs[0], "DT")?;
+    let value = inputs[0].as_nanoseconds().unwrap();
+    let time_of_day = value.rem_euclid(NANOSECONDS_PER_DAY);
+    Ok(if name.ends_with("_DATE") {
+        Object::Date(value - time_of_day)
+    } else {
+        Object::TimeOfDay(time_of_day)
+    })
+}
+
+#[test]
+fn date_and_time_operations() {
+    const HOUR: i64 = 3_600_000_000_000;
+    let date = Object::Date(19_723 * NANOSECONDS_PER_DAY);
+    let noon = Object::TimeOfDay(12 * HOUR);
+    let dt = concat_date_tod("CONCAT_DATE_TOD", &[date.clone(), noon.clone()]).unwrap();
+    assert_eq!(dt.to_string(), "DT#2024-01-01-12:00:00");
+    assert_eq!(
+        split_date_and_time("DT_TO_DATE", std::slice::from_ref(&dt)),
+        Ok(date)
+    );
+    assert_eq!(
+        split_date_and_time("DT_TO_TOD", std::slice::from_ref(&dt)),
+        Ok(noon.clone())
+    );
+    assert_eq!(
+        operation("ADD_TOD_TIME", &[noon.clone(), Object::Time(13 * HOUR)]),
+        Ok(Object::TimeOfDay(HOUR))
+    );
+    assert_eq!(
+        operation("DIV_TIME", &[Object::Time(HOUR), Object::Real(4.0)]),
+        Ok(Object::Time(HOUR / 4))
+    );
+    assert_eq!(
+        operation("SUB_DT_DT", &[noon, Object::Time(HOUR)])
+            .unwrap_err()
+            .to_string(),
+        "input of SUB_DT_DT must be DT, found TIME_OF_DAY"
+    );
+}
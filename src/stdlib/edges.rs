@@ -0,0 +1,58 @@
+use super::{get_bool, rising_edge, set, StandardFunctionBlock};
+use crate::identifiers::Identifiers;
+use crate::object::Object;
+
+/// Q is TRUE for one call after CLK rises.
+pub const R_TRIG: StandardFunctionBlock = StandardFunctionBlock {
+    declaration: "FUNCTION_BLOCK R_TRIG
+        VAR_INPUT CLK : BOOL; END_VAR
+        VAR_OUTPUT Q : BOOL; END_VAR
+        VAR M : BOOL; END_VAR
+    END_FUNCTION_BLOCK",
+    body: r_trig,
+};
+
+/// Q is TRUE for one call after CLK falls.
+pub const F_TRIG: StandardFunctionBlock = StandardFunctionBlock {
+    declaration: "FUNCTION_BLOCK F_TRIG
+        VAR_INPUT CLK : BOOL; END_VAR
+        VAR_OUTPUT Q : BOOL; END_VAR
+        VAR M : BOOL; END_VAR
+    END_FUNCTION_BLOCK",
+    body: f_trig,
+};
+
+fn r_trig(vars: &mut Identifiers<Object>, _now: i64) {
+    let q = rising_edge(vars, "CLK", "M");
+    set(vars, "Q", Object::Bool(q));
+}
+
+/// The standard body `Q := NOT CLK AND NOT M; M := NOT CLK;`, so M starts
+/// FALSE and a first call with CLK FALSE is an edge.
+fn f_trig(vars: &mut Identifiers<Object>, _now: i64) {
+    let clk = get_bool(vars, "CLK");
+    set(vars, "Q", Object::Bool(!clk && !get_bool(vars, "M")));
+    set(vars, "M", Object::Bool(!clk));
+}
+
+#[test]
+fn triggers_detect_edges_once() {
+    let clk = [false, true, true, false, false, true];
+    for (function_block, expected) in [
+        (R_TRIG, [false, true, false, false, false, true]),
+        (F_TRIG, [true, false, false, true, false, false]),
+    ]
+    .iter()
+    {
+        let mut vars = super::instance(function_block);
+        let outputs: Vec<bool> = clk
+            .iter()
+            .map(|&clk| {
+                set(&mut vars, "CLK", Object::Bool(clk));
+                (function_block.body)(&mut vars, 0);
+                get_bool(&vars, "Q")
+            })
+            .collect();
+        assert_eq!(outputs, expected.to_vec());
+    }
+}
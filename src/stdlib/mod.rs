@@ -0,0 +1,222 @@
+//! Functions and function blocks defined by IEC 61131-3 and available in
+//! every program.
+
+use crate::ast::{FunctionBlockDecl, Node};
+use crate::error::RuntimeError;
+use crate::identifiers::Identifiers;
+use crate::lexer::Lexer;
+use crate::object::{DataType, Object};
+use crate::parser::Parser;
+
+mod bistables;
+mod comparison;
+mod conversions;
+mod counters;
+mod dates;
+mod edges;
+mod numeric;
+mod selection;
+mod strings;
+mod timers;
+
+pub struct StandardFunction {
+    /// The inputs every call must assign.
+    inputs: &'static [&'static str],
+    /// For an extensible function, the number of the first `IN<n>` input
+    /// after `inputs` that a call may add.
+    extensible: Option<usize>,
+    /// Computes the result from all inputs in order. It also receives the
+    /// name the function was called by, for functions such as the type
+    /// conversions that are registered under many names.
+    pub body: fn(&str, &[Object]) -> Result<Object, RuntimeError>,
+}
+
+impl StandardFunction {
+    pub fn input_count(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_extensible(&self) -> bool {
+        self.extensible.is_some()
+    }
+
+    /// The position of the input `name`, `None` if there is no such input.
+    pub fn input_index(&self, name: &str) -> Option<usize> {
+        let name = name.to_ascii_uppercase();
+        self.inputs
+            .iter()
+            .position(|input| *input == name)
+            .or_else(|| {
+                let first = self.extensible?;
+                let number: usize = name.strip_prefix("IN")?.parse().ok()?;
+                if number >= first {
+                    Some(self.inputs.len() + number - first)
+                } else {
+                    None
+                }
+            })
+    }
+
+    pub fn input_name(&self, index: usize) -> String {
+        match self.inputs.get(index) {
+            Some(input) => input.to_string(),
+            None => format!("IN{}", self.extensible.unwrap() + index - self.inputs.len()),
+        }
+    }
+}
+
+/// All standard functions with the names they are called by.
+pub fn functions() -> impl Iterator<Item = (String, &'static StandardFunction)> {
+    numeric::FUNCTIONS
+        .iter()
+        .chain(selection::FUNCTIONS)
+        .chain(comparison::FUNCTIONS)
+        .chain(strings::FUNCTIONS)
+        .chain(dates::FUNCTIONS)
+        .map(|(name, function)| (name.to_string(), function))
+        .chain(conversions::functions())
+        .chain(dates::functions())
+}
+
+/// Native body of a standard function block. It runs with the variables of
+/// the instance, after the inputs are bound, and the current time of the
+/// interpreter's clock in nanoseconds.
+pub type Body = fn(&mut Identifiers<Object>, i64);
+
+pub struct StandardFunctionBlock {
+    /// The ST declaration of the interface and internal state, with an empty
+    /// body.
+    declaration: &'static str,
+    pub body: Body,
+}
+
+impl StandardFunctionBlock {
+    pub fn declaration(&self) -> FunctionBlockDecl {
+        let mut parser = Parser::new(Lexer::new(self.declaration.to_string()));
+        match parser.parse() {
+            Ok(Node::CompilationUnit(mut unit)) => match unit.pous.pop() {
+                Some(Node::FunctionBlockDecl(function_block)) => function_block,
+                _ => panic!("Incorrect standard function block {}", self.declaration),
+            },
+            _ => panic!("Incorrect standard function block {}", self.declaration),
+        }
+    }
+}
+
+pub const FUNCTION_BLOCKS: &[StandardFunctionBlock] = &[
+    bistables::SR,
+    bistables::RS,
+    edges::R_TRIG,
+    edges::F_TRIG,
+    counters::CTU,
+    counters::CTD,
+    counters::CTUD,
+    timers::TON,
+    timers::TOF,
+    timers::TP,
+];
+
+/// Checks that the input of function `name` has a type in the generic type
+/// `expected`, e.g. ANY_REAL.
+fn check_input(
+    name: &str,
+    input: &Object,
+    expected: &str,
+    check: fn(&DataType) -> bool,
+) -> Result<(), RuntimeError> {
+    if check(&input.data_type()) {
+        Ok(())
+    } else {
+        Err(RuntimeError::UnexpectedType {
+            context: format!("input of {}", name),
+            expected: expected.to_string(),
+            found: input.data_type(),
+        })
+    }
+}
+
+/// Converts the inputs of function `name` to one type, widening numbers like
+/// the arithmetic operators do. Strings of different lengths are taken as
+/// they are.
+fn common_inputs(name: &str, inputs: &[Object]) -> Result<Vec<Object>, RuntimeError> {
+    let mut data_type = inputs[0].data_type();
+    for input in &inputs[1..] {
+        let other = input.data_type();
+        if data_type.name() == other.name() {
+            continue;
+        }
+        if !data_type.is_numeric() || !other.is_numeric() {
+            return Err(RuntimeError::IncompatibleOperands {
+                operation: name.to_string(),
+                left: data_type,
+                right: other,
+            });
+        }
+        data_type = DataType::common_type(data_type, other);
+    }
+    Ok(inputs
+        .iter()
+        .map(|input| {
+            if input.data_type().name() == data_type.name() {
+                input.clone()
+            } else {
+                input.convert(data_type.clone())
+            }
+        })
+        .collect())
+}
+
+fn get_bool(vars: &Identifiers<Object>, id: &str) -> bool {
+    match vars[id] {
+        Object::Bool(value) => value,
+        _ => panic!("Incorrect type of {}", id),
+    }
+}
+
+fn get_int(vars: &Identifiers<Object>, id: &str) -> i16 {
+    match vars[id] {
+        Object::Int(value) => value,
+        _ => panic!("Incorrect type of {}", id),
+    }
+}
+
+fn get_time(vars: &Identifiers<Object>, id: &str) -> i64 {
+    match vars[id] {
+        Object::Time(value) => value,
+        _ => panic!("Incorrect type of {}", id),
+    }
+}
+
+fn set(vars: &mut Identifiers<Object>, id: &str, value: Object) {
+    vars.insert(id.to_string(), value);
+}
+
+/// Whether the BOOL input `id` rose since the last call, remembering its
+/// current value in the internal variable `memory`.
+fn rising_edge(vars: &mut Identifiers<Object>, id: &str, memory: &str) -> bool {
+    let value = get_bool(vars, id);
+    let rising = value && !get_bool(vars, memory);
+    set(vars, memory, Object::Bool(value));
+    rising
+}
+
+/// The variables of a fresh instance of `function_block`, for testing the
+/// bodies without an interpreter.
+#[cfg(test)]
+fn instance(function_block: &StandardFunctionBlock) -> Identifiers<Object> {
+    use crate::object::DataType;
+    function_block
+        .declaration()
+        .declarations
+        .iter()
+        .map(|declaration| match declaration {
+            Node::VarDecl(var_decl) => (
+                var_decl.variable.id.clone(),
+                DataType::from_name(&var_decl.var_type.name)
+                    .unwrap()
+                    .default_value(),
+            ),
+            _ => unreachable!(),
+        })
+        .collect()
+}
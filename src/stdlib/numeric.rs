@@ -0,0 +1,204 @@
+use super::{check_input, StandardFunction};
+use crate::error::RuntimeError;
+use crate::object::{DataType, Object};
+
+pub const FUNCTIONS: &[(&str, StandardFunction)] = &[
+    (
+        "ABS",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: abs,
+        },
+    ),
+    (
+        "SQRT",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: |name, inputs| real(name, &inputs[0], f64::sqrt),
+        },
+    ),
+    (
+        "LN",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: |name, inputs| real(name, &inputs[0], f64::ln),
+        },
+    ),
+    (
+        "LOG",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: |name, inputs| real(name, &inputs[0], f64::log10),
+        },
+    ),
+    (
+        "EXP",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: |name, inputs| real(name, &inputs[0], f64::exp),
+        },
+    ),
+    (
+        "SIN",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: |name, inputs| real(name, &inputs[0], f64::sin),
+        },
+    ),
+    (
+        "COS",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: |name, inputs| real(name, &inputs[0], f64::cos),
+        },
+    ),
+    (
+        "TAN",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: |name, inputs| real(name, &inputs[0], f64::tan),
+        },
+    ),
+    (
+        "ASIN",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: |name, inputs| real(name, &inputs[0], f64::asin),
+        },
+    ),
+    (
+        "ACOS",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: |name, inputs| real(name, &inputs[0], f64::acos),
+        },
+    ),
+    (
+        "ATAN",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: |name, inputs| real(name, &inputs[0], f64::atan),
+        },
+    ),
+    (
+        "EXPT",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: None,
+            body: expt,
+        },
+    ),
+    (
+        "ADD",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: Some(3),
+            body: |_, inputs| fold(inputs, Object::add),
+        },
+    ),
+    (
+        "MUL",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: Some(3),
+            body: |_, inputs| fold(inputs, Object::mul),
+        },
+    ),
+    (
+        "SUB",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: None,
+            body: |_, inputs| inputs[0].clone().sub(inputs[1].clone()),
+        },
+    ),
+    (
+        "DIV",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: None,
+            body: |_, inputs| inputs[0].clone().div(inputs[1].clone()),
+        },
+    ),
+];
+
+/// The absolute value of any number, in its own type. The most negative
+/// integer of a type has no absolute value in it and is reported instead of
+/// wrapping to itself.
+fn abs(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let input = &inputs[0];
+    check_input(name, input, "ANY_NUM", DataType::is_numeric)?;
+    let negative = match input.as_i128() {
+        Some(value) if -value > input.data_type().range().1 => {
+            return Err(RuntimeError::OutOfRange {
+                value: format!("{}({})", name, value),
+                range: input.data_type().to_string(),
+            })
+        }
+        Some(value) => value < 0,
+        None => input.as_f64().unwrap() < 0.0,
+    };
+    if negative {
+        input.clone().neg()
+    } else {
+        Ok(input.clone())
+    }
+}
+
+/// Applies `function` to an ANY_REAL input, keeping its type.
+fn real(name: &str, input: &Object, function: fn(f64) -> f64) -> Result<Object, RuntimeError> {
+    check_input(name, input, "ANY_REAL", DataType::is_real)?;
+    Ok(Object::LReal(function(input.as_f64().unwrap())).convert(input.data_type()))
+}
+
+/// IN1 raised to the power IN2, the same as `IN1 ** IN2`.
+fn expt(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    check_input(name, &inputs[0], "ANY_NUM", DataType::is_numeric)?;
+    check_input(name, &inputs[1], "ANY_NUM", DataType::is_numeric)?;
+    inputs[0].clone().pow(inputs[1].clone())
+}
+
+fn fold(
+    inputs: &[Object],
+    operation: fn(Object, Object) -> Result<Object, RuntimeError>,
+) -> Result<Object, RuntimeError> {
+    inputs[1..]
+        .iter()
+        .try_fold(inputs[0].clone(), |result, input| {
+            operation(result, input.clone())
+        })
+}
+
+#[test]
+fn real_functions_keep_input_type() {
+    let sqrt = |input| real("SQRT", &input, f64::sqrt);
+    assert_eq!(sqrt(Object::Real(2.25)), Ok(Object::Real(1.5)));
+    assert_eq!(sqrt(Object::LReal(2.25)), Ok(Object::LReal(1.5)));
+    assert_eq!(
+        sqrt(Object::Int(4)).unwrap_err().to_string(),
+        "input of SQRT must be ANY_REAL, found INT"
+    );
+    assert_eq!(abs("ABS", &[Object::SInt(-5)]), Ok(Object::SInt(5)));
+    assert_eq!(abs("ABS", &[Object::LReal(-0.5)]), Ok(Object::LReal(0.5)));
+    assert_eq!(
+        abs("ABS", &[Object::Int(i16::MIN)])
+            .unwrap_err()
+            .to_string(),
+        "ABS(-32768) is out of range for INT"
+    );
+    assert_eq!(
+        expt("EXPT", &[Object::DInt(2), Object::DInt(3)]),
+        Object::DInt(2).pow(Object::DInt(3))
+    );
+}
@@ -0,0 +1,122 @@
+use std::cmp::Ordering;
+
+use super::{check_input, common_inputs, StandardFunction};
+use crate::error::RuntimeError;
+use crate::object::{DataType, Object};
+
+pub const FUNCTIONS: &[(&str, StandardFunction)] = &[
+    (
+        "SEL",
+        StandardFunction {
+            inputs: &["G", "IN0", "IN1"],
+            extensible: None,
+            body: sel,
+        },
+    ),
+    (
+        "MAX",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: Some(3),
+            body: |name, inputs| extreme(name, inputs, Ordering::Greater),
+        },
+    ),
+    (
+        "MIN",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: Some(3),
+            body: |name, inputs| extreme(name, inputs, Ordering::Less),
+        },
+    ),
+    (
+        "LIMIT",
+        StandardFunction {
+            inputs: &["MN", "IN", "MX"],
+            extensible: None,
+            body: limit,
+        },
+    ),
+    (
+        "MUX",
+        StandardFunction {
+            inputs: &["K", "IN0", "IN1"],
+            extensible: Some(2),
+            body: mux,
+        },
+    ),
+];
+
+/// IN0 if G is FALSE, IN1 otherwise.
+fn sel(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    check_input(name, &inputs[0], "BOOL", |data_type| {
+        *data_type == DataType::Bool
+    })?;
+    let mut values = common_inputs(name, &inputs[1..])?;
+    Ok(values.swap_remove((inputs[0] == Object::Bool(true)) as usize))
+}
+
+/// The first input that no other input is `ordering` to.
+fn extreme(name: &str, inputs: &[Object], ordering: Ordering) -> Result<Object, RuntimeError> {
+    let mut values = common_inputs(name, inputs)?.into_iter();
+    let mut result = values.next().unwrap();
+    for value in values {
+        if value.clone().compare(result.clone())? == Some(ordering) {
+            result = value;
+        }
+    }
+    Ok(result)
+}
+
+/// IN clamped to the range MN..MX.
+fn limit(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let values = common_inputs(name, inputs)?;
+    let lower = extreme(name, &values[..2], Ordering::Greater)?;
+    extreme(name, &[lower, values[2].clone()], Ordering::Less)
+}
+
+/// The input selected by K, counting from IN0.
+fn mux(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    check_input(name, &inputs[0], "ANY_INT", DataType::is_integer)?;
+    let mut values = common_inputs(name, &inputs[1..])?;
+    let k = inputs[0].as_i128().unwrap();
+    if k < 0 || k as usize >= values.len() {
+        return Err(RuntimeError::OutOfRange {
+            value: k.to_string(),
+            range: format!("K of {} (0..{})", name, values.len() - 1),
+        });
+    }
+    Ok(values.swap_remove(k as usize))
+}
+
+#[test]
+fn selection_widens_inputs() {
+    assert_eq!(
+        extreme(
+            "MAX",
+            &[Object::Int(3), Object::DInt(7), Object::SInt(-1)],
+            Ordering::Greater
+        ),
+        Ok(Object::DInt(7))
+    );
+    assert_eq!(
+        limit(
+            "LIMIT",
+            &[Object::Int(0), Object::Int(150), Object::Int(100)]
+        ),
+        Ok(Object::Int(100))
+    );
+    assert_eq!(
+        sel(
+            "SEL",
+            &[Object::Bool(true), Object::Int(1), Object::Real(2.5)]
+        ),
+        Ok(Object::Real(2.5))
+    );
+    assert_eq!(
+        mux("MUX", &[Object::Int(3), Object::Int(1), Object::Int(2)])
+            .unwrap_err()
+            .to_string(),
+        "3 is out of range for K of MUX (0..1)"
+    );
+}
@@ -0,0 +1,287 @@
+use std::convert::TryFrom;
+
+use super::{check_input, StandardFunction};
+use crate::error::RuntimeError;
+use crate::object::{DataType, Object};
+
+pub const FUNCTIONS: &[(&str, StandardFunction)] = &[
+    (
+        "LEN",
+        StandardFunction {
+            inputs: &["IN"],
+            extensible: None,
+            body: len,
+        },
+    ),
+    (
+        "LEFT",
+        StandardFunction {
+            inputs: &["IN", "L"],
+            extensible: None,
+            body: left,
+        },
+    ),
+    (
+        "RIGHT",
+        StandardFunction {
+            inputs: &["IN", "L"],
+            extensible: None,
+            body: right,
+        },
+    ),
+    (
+        "MID",
+        StandardFunction {
+            inputs: &["IN", "L", "P"],
+            extensible: None,
+            body: mid,
+        },
+    ),
+    (
+        "CONCAT",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: Some(3),
+            body: concat,
+        },
+    ),
+    (
+        "INSERT",
+        StandardFunction {
+            inputs: &["IN1", "IN2", "P"],
+            extensible: None,
+            body: insert,
+        },
+    ),
+    (
+        "DELETE",
+        StandardFunction {
+            inputs: &["IN", "L", "P"],
+            extensible: None,
+            body: delete,
+        },
+    ),
+    (
+        "REPLACE",
+        StandardFunction {
+            inputs: &["IN1", "IN2", "L", "P"],
+            extensible: None,
+            body: replace,
+        },
+    ),
+    (
+        "FIND",
+        StandardFunction {
+            inputs: &["IN1", "IN2"],
+            extensible: None,
+            body: find,
+        },
+    ),
+];
+
+/// The characters of the string inputs of function `name`, which must all be
+/// STRING or all be WSTRING.
+fn texts(name: &str, inputs: &[&Object]) -> Result<Vec<Vec<char>>, RuntimeError> {
+    let first = inputs[0].data_type();
+    inputs
+        .iter()
+        .map(|input| match input {
+            Object::String(value, _) | Object::WString(value, _)
+                if input.data_type().name() == first.name() =>
+            {
+                Ok(value.chars().collect())
+            }
+            Object::String(..) | Object::WString(..) => Err(RuntimeError::IncompatibleOperands {
+                operation: name.to_string(),
+                left: first.clone(),
+                right: input.data_type(),
+            }),
+            _ => Err(RuntimeError::UnexpectedType {
+                context: format!("input of {}", name),
+                expected: "ANY_STRING".to_string(),
+                found: input.data_type(),
+            }),
+        })
+        .collect()
+}
+
+/// The integer input `parameter` of function `name`, checked to lie in
+/// `min..=max`.
+fn position(
+    name: &str,
+    parameter: &str,
+    input: &Object,
+    min: usize,
+    max: usize,
+) -> Result<usize, RuntimeError> {
+    check_input(name, input, "ANY_INT", DataType::is_integer)?;
+    let value = input.as_i128().unwrap();
+    match usize::try_from(value) {
+        Ok(value) if value >= min && value <= max => Ok(value),
+        _ => Err(RuntimeError::OutOfRange {
+            value: value.to_string(),
+            range: format!("{} of {} ({}..{})", parameter, name, min, max),
+        }),
+    }
+}
+
+/// A string of the same kind as `like`.
+fn result(like: &Object, value: impl IntoIterator<Item = char>) -> Object {
+    let value = value.into_iter().collect();
+    match like {
+        Object::WString(..) => Object::wstring(value),
+        _ => Object::string(value),
+    }
+}
+
+fn len(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let text = texts(name, &[&inputs[0]])?.remove(0);
+    match i16::try_from(text.len()) {
+        Ok(length) => Ok(Object::Int(length)),
+        Err(_) => Err(RuntimeError::OutOfRange {
+            value: text.len().to_string(),
+            range: DataType::Int.to_string(),
+        }),
+    }
+}
+
+fn left(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let text = texts(name, &[&inputs[0]])?.remove(0);
+    let length = position(name, "L", &inputs[1], 0, text.len())?;
+    Ok(result(&inputs[0], text[..length].iter().copied()))
+}
+
+fn right(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let text = texts(name, &[&inputs[0]])?.remove(0);
+    let length = position(name, "L", &inputs[1], 0, text.len())?;
+    Ok(result(
+        &inputs[0],
+        text[text.len() - length..].iter().copied(),
+    ))
+}
+
+/// The characters `L` characters from position `P`, counting from 1.
+fn mid(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let text = texts(name, &[&inputs[0]])?.remove(0);
+    let start = position(name, "P", &inputs[2], 1, text.len() + 1)? - 1;
+    let length = position(name, "L", &inputs[1], 0, text.len() - start)?;
+    Ok(result(
+        &inputs[0],
+        text[start..start + length].iter().copied(),
+    ))
+}
+
+fn concat(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let texts = texts(name, &inputs.iter().collect::<Vec<_>>())?;
+    Ok(result(&inputs[0], texts.into_iter().flatten()))
+}
+
+/// IN2 inserted after the first `P` characters of IN1.
+fn insert(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let mut texts = texts(name, &[&inputs[0], &inputs[1]])?;
+    let inserted = texts.pop().unwrap();
+    let mut text = texts.pop().unwrap();
+    let at = position(name, "P", &inputs[2], 0, text.len())?;
+    text.splice(at..at, inserted);
+    Ok(result(&inputs[0], text))
+}
+
+fn delete(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let mut text = texts(name, &[&inputs[0]])?.remove(0);
+    let start = position(name, "P", &inputs[2], 1, text.len() + 1)? - 1;
+    let length = position(name, "L", &inputs[1], 0, text.len() - start)?;
+    text.drain(start..start + length);
+    Ok(result(&inputs[0], text))
+}
+
+/// IN1 with `L` characters from position `P` replaced by IN2.
+fn replace(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let mut texts = texts(name, &[&inputs[0], &inputs[1]])?;
+    let replacement = texts.pop().unwrap();
+    let mut text = texts.pop().unwrap();
+    let start = position(name, "P", &inputs[3], 1, text.len() + 1)? - 1;
+    let length = position(name, "L", &inputs[2], 0, text.len() - start)?;
+    text.splice(start..start + length, replacement);
+    Ok(result(&inputs[0], text))
+}
+
+/// The position of the first occurrence of IN2 in IN1, counting from 1, or 0
+/// if there is none.
+fn find(name: &str, inputs: &[Object]) -> Result<Object, RuntimeError> {
+    let texts = texts(name, &[&inputs[0], &inputs[1]])?;
+    let (text, pattern) = (&texts[0], &texts[1]);
+    let found = if pattern.is_empty() {
+        None
+    } else {
+        text.windows(pattern.len())
+            .position(|window| window == pattern.as_slice())
+    };
+    Ok(Object::Int(found.map_or(0, |index| index as i16 + 1)))
+}
+
+#[test]
+fn string_functions_count_from_one() {
+    let text = |value: &str| Object::string(value.to_string());
+    let int = Object::Int;
+    let cases = [
+        (len("LEN", &[text("ASTUTE")]), int(6)),
+        (left("LEFT", &[text("ASTUTE"), int(3)]), text("AST")),
+        (right("RIGHT", &[text("ASTUTE"), int(3)]), text("UTE")),
+        (mid("MID", &[text("ASTUTE"), int(2), int(3)]), text("TU")),
+        (
+            concat("CONCAT", &[text("AB"), text("CD"), text("E")]),
+            text("ABCDE"),
+        ),
+        (
+            insert("INSERT", &[text("ABC"), text("XY"), int(2)]),
+            text("ABXYC"),
+        ),
+        (
+            delete("DELETE", &[text("ABXYC"), int(2), int(3)]),
+            text("ABC"),
+        ),
+        (
+            replace("REPLACE", &[text("ABCDE"), text("X"), int(2), int(3)]),
+            text("ABXE"),
+        ),
+        (find("FIND", &[text("ABCBC"), text("BC")]), int(2)),
+        (find("FIND", &[text("ABC"), text("D")]), int(0)),
+    ];
+    for (result, expected) in cases.iter() {
+        assert_eq!(result, &Ok(expected.clone()));
+    }
+}
+
+#[test]
+fn string_functions_check_inputs() {
+    let errors = [
+        (
+            left("LEFT", &[Object::string("ABC".to_string()), Object::Int(4)]),
+            "4 is out of range for L of LEFT (0..3)",
+        ),
+        (
+            mid(
+                "MID",
+                &[
+                    Object::string("ABC".to_string()),
+                    Object::Int(1),
+                    Object::Int(0),
+                ],
+            ),
+            "0 is out of range for P of MID (1..4)",
+        ),
+        (
+            concat(
+                "CONCAT",
+                &[
+                    Object::string("A".to_string()),
+                    Object::wstring("B".to_string()),
+                ],
+            ),
+            "CONCAT is not defined for types STRING and WSTRING",
+        ),
+    ];
+    for (result, expected) in errors.iter() {
+        assert_eq!(result.clone().unwrap_err().to_string(), *expected);
+    }
+}
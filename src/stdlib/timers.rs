@@ -0,0 +1,176 @@
+use super::{get_bool, get_time, rising_edge, set, StandardFunctionBlock};
+use crate::identifiers::Identifiers;
+use crate::object::Object;
+
+/// On-delay: Q rises once IN has been TRUE for PT.
+pub const TON: StandardFunctionBlock = StandardFunctionBlock {
+    declaration: "FUNCTION_BLOCK TON
+        VAR_INPUT IN : BOOL; PT : TIME; END_VAR
+        VAR_OUTPUT Q : BOOL; ET : TIME; END_VAR
+        VAR running : BOOL; start : TIME; END_VAR
+    END_FUNCTION_BLOCK",
+    body: ton,
+};
+
+/// Off-delay: Q follows IN up and falls once IN has been FALSE for PT.
+pub const TOF: StandardFunctionBlock = StandardFunctionBlock {
+    declaration: "FUNCTION_BLOCK TOF
+        VAR_INPUT IN : BOOL; PT : TIME; END_VAR
+        VAR_OUTPUT Q : BOOL; ET : TIME; END_VAR
+        VAR running : BOOL; start : TIME; END_VAR
+    END_FUNCTION_BLOCK",
+    body: tof,
+};
+
+/// Pulse: a rising edge of IN sets Q for exactly PT, regardless of IN.
+pub const TP: StandardFunctionBlock = StandardFunctionBlock {
+    declaration: "FUNCTION_BLOCK TP
+        VAR_INPUT IN : BOOL; PT : TIME; END_VAR
+        VAR_OUTPUT Q : BOOL; ET : TIME; END_VAR
+        VAR previous_in : BOOL; start : TIME; END_VAR
+    END_FUNCTION_BLOCK",
+    body: tp,
+};
+
+/// Starts timing if not running yet and returns the elapsed time, capped at PT.
+fn elapsed(vars: &mut Identifiers<Object>, now: i64) -> i64 {
+    if !get_bool(vars, "running") {
+        set(vars, "running", Object::Bool(true));
+        set(vars, "start", Object::Time(now));
+    }
+    (now - get_time(vars, "start")).min(get_time(vars, "PT"))
+}
+
+fn ton(vars: &mut Identifiers<Object>, now: i64) {
+    if get_bool(vars, "IN") {
+        let elapsed = elapsed(vars, now);
+        set(vars, "Q", Object::Bool(elapsed >= get_time(vars, "PT")));
+        set(vars, "ET", Object::Time(elapsed));
+    } else {
+        set(vars, "running", Object::Bool(false));
+        set(vars, "Q", Object::Bool(false));
+        set(vars, "ET", Object::Time(0));
+    }
+}
+
+fn tof(vars: &mut Identifiers<Object>, now: i64) {
+    if get_bool(vars, "IN") {
+        set(vars, "running", Object::Bool(false));
+        set(vars, "Q", Object::Bool(true));
+        set(vars, "ET", Object::Time(0));
+    } else if get_bool(vars, "Q") {
+        let elapsed = elapsed(vars, now);
+        if elapsed >= get_time(vars, "PT") {
+            set(vars, "running", Object::Bool(false));
+            set(vars, "Q", Object::Bool(false));
+        }
+        set(vars, "ET", Object::Time(elapsed));
+    }
+}
+
+fn tp(vars: &mut Identifiers<Object>, now: i64) {
+    let input = get_bool(vars, "IN");
+    let rising = rising_edge(vars, "IN", "previous_in");
+    if rising && !get_bool(vars, "Q") {
+        set(vars, "Q", Object::Bool(true));
+        set(vars, "start", Object::Time(now));
+    }
+    if get_bool(vars, "Q") {
+        let (preset, elapsed) = (get_time(vars, "PT"), now - get_time(vars, "start"));
+        set(vars, "Q", Object::Bool(elapsed < preset));
+        set(vars, "ET", Object::Time(elapsed.min(preset)));
+    } else if !input {
+        set(vars, "ET", Object::Time(0));
+    }
+}
+
+/// Calls the body of `function_block` once per step with the given IN at
+/// the given time in milliseconds, and returns Q and ET after each step.
+#[cfg(test)]
+fn run(function_block: &StandardFunctionBlock, steps: &[(i64, bool)]) -> Vec<(bool, i64)> {
+    let mut vars = super::instance(function_block);
+    set(&mut vars, "PT", Object::Time(100_000_000));
+    steps
+        .iter()
+        .map(|&(time, input)| {
+            set(&mut vars, "IN", Object::Bool(input));
+            (function_block.body)(&mut vars, time * 1_000_000);
+            (get_bool(&vars, "Q"), get_time(&vars, "ET") / 1_000_000)
+        })
+        .collect()
+}
+
+#[test]
+fn ton_delays_rising_edge() {
+    let steps = [
+        (0, false),
+        (10, true),
+        (60, true),
+        (110, true),
+        (150, true),
+        (160, false),
+    ];
+    assert_eq!(
+        run(&TON, &steps),
+        vec![
+            (false, 0),
+            (false, 0),
+            (false, 50),
+            (true, 100),
+            (true, 100),
+            (false, 0)
+        ]
+    );
+    // Releasing IN before PT restarts the delay.
+    let steps = [(0, true), (50, false), (60, true), (150, true), (160, true)];
+    assert_eq!(
+        run(&TON, &steps),
+        vec![(false, 0), (false, 0), (false, 0), (false, 90), (true, 100)]
+    );
+}
+
+#[test]
+fn tof_delays_falling_edge() {
+    let steps = [
+        (0, false),
+        (10, true),
+        (20, false),
+        (70, false),
+        (120, false),
+        (130, true),
+    ];
+    assert_eq!(
+        run(&TOF, &steps),
+        vec![
+            (false, 0),
+            (true, 0),
+            (true, 0),
+            (true, 50),
+            (false, 100),
+            (true, 0)
+        ]
+    );
+}
+
+#[test]
+fn tp_pulses_for_preset_time() {
+    let steps = [
+        (0, true),
+        (50, false),
+        (60, true),
+        (100, true),
+        (120, false),
+        (130, true),
+    ];
+    assert_eq!(
+        run(&TP, &steps),
+        vec![
+            (true, 0),
+            (true, 50),
+            (true, 60),
+            (false, 100),
+            (false, 0),
+            (true, 0)
+        ]
+    );
+}
@@ -1,17 +1,103 @@
+use std::fmt;
+
+use crate::image::Address;
+use crate::object::{DataType, Object};
+
 #[derive(PartialEq, Clone, Debug)]
 pub enum Token {
-    Integer(i32),
+    /// An integer literal with the type it was declared with, as in `INT#5`.
+    /// Untyped literals get their type from `Object::from_integer`.
+    Integer(i128, Option<DataType>),
+    /// A real literal, `LREAL` unless declared as in `REAL#1.5`.
+    Real(f64, Option<DataType>),
+    Bool(bool),
+    /// A `T#` duration literal in nanoseconds.
+    Time(i64),
+    LTime(i64),
+    Date(i64),
+    TimeOfDay(i64),
+    DateAndTime(i64),
+    /// A single-quoted STRING literal with escapes resolved.
+    String(String),
+    /// A double-quoted WSTRING literal with escapes resolved.
+    WString(String),
+    /// A direct address such as `%IX0.0`.
+    DirectAddress(Address),
     Plus,
     Minus,
     Mul,
     Div,
+    Mod,
+    Power,
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    And,
+    Or,
+    Xor,
+    Not,
     Rparen,
     Lparen,
     Program,
     EndProgram,
+    Function,
+    EndFunction,
+    FunctionBlock,
+    EndFunctionBlock,
+    Configuration,
+    EndConfiguration,
+    Resource,
+    EndResource,
+    Task,
+    With,
+    On,
+    Var,
+    VarInput,
+    VarOutput,
+    EndVar,
+    At,
+    Type,
+    EndType,
+    Struct,
+    EndStruct,
+    Array,
+    If,
+    Then,
+    Elsif,
+    Else,
+    EndIf,
+    Case,
+    Of,
+    EndCase,
+    For,
+    To,
+    By,
+    Do,
+    EndFor,
+    While,
+    EndWhile,
+    Repeat,
+    Until,
+    EndRepeat,
+    Exit,
+    Continue,
+    Return,
     Assign,
+    Colon,
+    Comma,
+    Lbracket,
+    Rbracket,
+    Dot,
+    DotDot,
     Semicolon,
+    /// The `#` joining the type and the name of an enumerated value, as in
+    /// `Mode#Run`. It is only a token between two identifiers.
+    Hash,
     Id(String),
+    Eof,
     #[allow(dead_code)]
     NoOp,
 }
@@ -20,19 +106,258 @@ impl Token {
     pub fn variant_eq(left: Token, right: &Token) -> bool {
         use Token::*;
         match (left, right) {
-            (Integer(_), Integer(_)) => true,
+            (Integer(..), Integer(..)) => true,
+            (Real(..), Real(..)) => true,
+            (Bool(_), Bool(_)) => true,
+            (Time(_), Time(_)) => true,
+            (LTime(_), LTime(_)) => true,
+            (Date(_), Date(_)) => true,
+            (TimeOfDay(_), TimeOfDay(_)) => true,
+            (DateAndTime(_), DateAndTime(_)) => true,
+            (String(_), String(_)) => true,
+            (WString(_), WString(_)) => true,
+            (DirectAddress(_), DirectAddress(_)) => true,
             (Plus, Plus) => true,
             (Minus, Minus) => true,
             (Mul, Mul) => true,
             (Div, Div) => true,
+            (Mod, Mod) => true,
+            (Power, Power) => true,
+            (Equal, Equal) => true,
+            (NotEqual, NotEqual) => true,
+            (Less, Less) => true,
+            (Greater, Greater) => true,
+            (LessEqual, LessEqual) => true,
+            (GreaterEqual, GreaterEqual) => true,
+            (And, And) => true,
+            (Or, Or) => true,
+            (Xor, Xor) => true,
+            (Not, Not) => true,
             (Rparen, Rparen) => true,
             (Lparen, Lparen) => true,
             (Program, Program) => true,
             (EndProgram, EndProgram) => true,
+            (Function, Function) => true,
+            (EndFunction, EndFunction) => true,
+            (FunctionBlock, FunctionBlock) => true,
+            (EndFunctionBlock, EndFunctionBlock) => true,
+            (Configuration, Configuration) => true,
+            (EndConfiguration, EndConfiguration) => true,
+            (Resource, Resource) => true,
+            (EndResource, EndResource) => true,
+            (Task, Task) => true,
+            (With, With) => true,
+            (On, On) => true,
+            (Var, Var) => true,
+            (VarInput, VarInput) => true,
+            (VarOutput, VarOutput) => true,
+            (EndVar, EndVar) => true,
+            (At, At) => true,
+            (Type, Type) => true,
+            (EndType, EndType) => true,
+            (Struct, Struct) => true,
+            (EndStruct, EndStruct) => true,
+            (Array, Array) => true,
+            (If, If) => true,
+            (Then, Then) => true,
+            (Elsif, Elsif) => true,
+            (Else, Else) => true,
+            (EndIf, EndIf) => true,
+            (Case, Case) => true,
+            (Of, Of) => true,
+            (EndCase, EndCase) => true,
+            (For, For) => true,
+            (To, To) => true,
+            (By, By) => true,
+            (Do, Do) => true,
+            (EndFor, EndFor) => true,
+            (While, While) => true,
+            (EndWhile, EndWhile) => true,
+            (Repeat, Repeat) => true,
+            (Until, Until) => true,
+            (EndRepeat, EndRepeat) => true,
+            (Exit, Exit) => true,
+            (Continue, Continue) => true,
+            (Return, Return) => true,
             (Assign, Assign) => true,
+            (Colon, Colon) => true,
+            (Comma, Comma) => true,
+            (Lbracket, Lbracket) => true,
+            (Rbracket, Rbracket) => true,
+            (Dot, Dot) => true,
+            (DotDot, DotDot) => true,
             (Semicolon, Semicolon) => true,
+            (Hash, Hash) => true,
             (Id(_), Id(_)) => true,
+            (Eof, Eof) => true,
             (_, _) => false,
         }
     }
 }
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Token::*;
+        let text = match self {
+            Integer(value, None) => return write!(f, "{}", value),
+            Integer(value, Some(data_type)) => return write!(f, "{}#{}", data_type, value),
+            Real(value, None) => return write!(f, "{:?}", value),
+            Real(value, Some(data_type)) => return write!(f, "{}#{:?}", data_type, value),
+            Time(value) => return write!(f, "T#{}ns", value),
+            LTime(value) => return write!(f, "LTIME#{}ns", value),
+            Date(value) => return write!(f, "{}", Object::Date(*value)),
+            TimeOfDay(value) => return write!(f, "{}", Object::TimeOfDay(*value)),
+            DateAndTime(value) => return write!(f, "{}", Object::DateAndTime(*value)),
+            String(value) => return write!(f, "'{}'", value),
+            WString(value) => return write!(f, "\"{}\"", value),
+            DirectAddress(address) => return write!(f, "{}", address),
+            Bool(true) => "TRUE",
+            Bool(false) => "FALSE",
+            Id(id) => return write!(f, "{}", id),
+            Plus => "+",
+            Minus => "-",
+            Mul => "*",
+            Div => "/",
+            Mod => "MOD",
+            Power => "**",
+            Equal => "=",
+            NotEqual => "<>",
+            Less => "<",
+            Greater => ">",
+            LessEqual => "<=",
+            GreaterEqual => ">=",
+            And => "AND",
+            Or => "OR",
+            Xor => "XOR",
+            Not => "NOT",
+            Rparen => ")",
+            Lparen => "(",
+            Program => "PROGRAM",
+            EndProgram => "END_PROGRAM",
+            Function => "FUNCTION",
+            EndFunction => "END_FUNCTION",
+            FunctionBlock => "FUNCTION_BLOCK",
+            EndFunctionBlock => "END_FUNCTION_BLOCK",
+            Configuration => "CONFIGURATION",
+            EndConfiguration => "END_CONFIGURATION",
+            Resource => "RESOURCE",
+            EndResource => "END_RESOURCE",
+            Task => "TASK",
+            With => "WITH",
+            On => "ON",
+            Var => "VAR",
+            VarInput => "VAR_INPUT",
+            VarOutput => "VAR_OUTPUT",
+            EndVar => "END_VAR",
+            At => "AT",
+            Type => "TYPE",
+            EndType => "END_TYPE",
+            Struct => "STRUCT",
+            EndStruct => "END_STRUCT",
+            Array => "ARRAY",
+            If => "IF",
+            Then => "THEN",
+            Elsif => "ELSIF",
+            Else => "ELSE",
+            EndIf => "END_IF",
+            Case => "CASE",
+            Of => "OF",
+            EndCase => "END_CASE",
+            For => "FOR",
+            To => "TO",
+            By => "BY",
+            Do => "DO",
+            EndFor => "END_FOR",
+            While => "WHILE",
+            EndWhile => "END_WHILE",
+            Repeat => "REPEAT",
+            Until => "UNTIL",
+            EndRepeat => "END_REPEAT",
+            Exit => "EXIT",
+            Continue => "CONTINUE",
+            Return => "RETURN",
+            Assign => ":=",
+            Colon => ":",
+            Comma => ",",
+            Lbracket => "[",
+            Rbracket => "]",
+            Dot => ".",
+            DotDot => "..",
+            Semicolon => ";",
+            Hash => "#",
+            Eof => "end of input",
+            NoOp => "",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// A region of the source text. `start` and `end` are byte offsets, `line`
+/// and `column` locate `start` and count from 1.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Span {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// Returns the smallest span covering both `self` and `other`.
+    pub fn to(self, other: Span) -> Span {
+        let first = if other.start < self.start {
+            other
+        } else {
+            self
+        };
+        Span {
+            start: first.start,
+            end: self.end.max(other.end),
+            line: first.line,
+            column: first.column,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A token together with the span it was read from.
+///
+/// Equality only compares the tokens, so a tree built by hand compares equal
+/// to the same tree produced by the parser.
+#[derive(Clone, Debug)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+impl SpannedToken {
+    pub fn new(token: Token, span: Span) -> SpannedToken {
+        SpannedToken { token, span }
+    }
+}
+
+impl PartialEq for SpannedToken {
+    fn eq(&self, other: &SpannedToken) -> bool {
+        self.token == other.token
+    }
+}
+
+impl From<Token> for SpannedToken {
+    fn from(token: Token) -> SpannedToken {
+        SpannedToken::new(token, Span::default())
+    }
+}